@@ -0,0 +1,33 @@
+use ag_lcd::{Cursor, LcdDisplay};
+use esp_idf_hal::delay::Ets;
+use esp_idf_hal::i2c::{I2cConfig, I2cDriver};
+use esp_idf_hal::peripherals::Peripherals;
+use esp_idf_hal::units::KiloHertz;
+use port_expander::dev::pcf8574::Pcf8574;
+
+fn main() {
+    esp_idf_svc::sys::link_patches();
+
+    let peripherals = Peripherals::take().unwrap();
+    let pins = peripherals.pins;
+
+    let config = I2cConfig::new().baudrate(KiloHertz(100).into());
+    let i2c = I2cDriver::new(peripherals.i2c0, pins.gpio21, pins.gpio22, &config).unwrap();
+    let mut i2c_expander = Pcf8574::new(i2c, true, true, true);
+
+    // Ets busy-waits with microsecond resolution, while FreeRtos rounds every
+    // delay up to a whole scheduler tick and makes the display very slow
+    let mut lcd: LcdDisplay<_, _> = LcdDisplay::new_pcf8574(&mut i2c_expander, Ets)
+        .with_cursor(Cursor::Off)
+        .build();
+
+    lcd.print("Hello, ESP-IDF");
+
+    if lcd.error() != ag_lcd::Error::None {
+        println!("the display reported error {}", lcd.error() as u8);
+    }
+
+    loop {
+        esp_idf_hal::delay::FreeRtos::delay_ms(1000);
+    }
+}
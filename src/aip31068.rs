@@ -0,0 +1,578 @@
+//! Allows controlling displays with an AIP31068L controller over its native I2C interface
+//!
+//! The AIP31068L accepts standard HD44780 instructions, but over I2C with a control byte
+//! selecting between commands and data, so no port expander is involved. Its SPI mode uses
+//! 9-bit frames, which [embedded-hal](https://crates.io/crates/embedded-hal) SPI devices
+//! can't send, so only I2C is supported.
+
+use crate::time::YieldHook;
+use crate::{
+    AutoScroll, Blink, CharacterDisplay, Cursor, Display, Error, Layout, Lines, Microseconds,
+    Scroll,
+};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::I2c;
+
+/// Default 7-bit I2C address of the AIP31068L
+pub const AIP31068_ADDRESS: u8 = 0x3E;
+
+/// Control byte for a single command (Co = 0, RS = 0)
+const CONTROL_COMMAND: u8 = 0x00;
+
+/// Control byte for a stream of data (Co = 0, RS = 1)
+const CONTROL_DATA: u8 = 0x40;
+
+const CLEAR_DISPLAY: u8 = 0x01;
+const RETURN_HOME: u8 = 0x02;
+const SET_DISPLAY_MODE: u8 = 0x04;
+const SET_DISPLAY_CTRL: u8 = 0x08;
+const CURSOR_SHIFT: u8 = 0x10;
+const SET_DISPLAY_FUNC: u8 = 0x20;
+const SET_CGRAM_ADDR: u8 = 0x40;
+const SET_DDRAM_ADDR: u8 = 0x80;
+
+/// Display shift (rather than cursor move) flag for [CURSOR_SHIFT]
+const MOVE_DISPLAY: u8 = 0x08;
+
+/// The AIP31068L always talks eight bits at a time
+const EIGHT_BITS: u8 = 0x10;
+
+const CMD_DELAY: u32 = 50;
+const CLEAR_DELAY: u32 = 2000;
+
+/// An LCD display driven by an AIP31068L controller over I2C
+///
+/// Methods called on this struct will fail silently if the bus reports an error, in
+/// which case the error code is set to [Error::BusError].
+pub struct Aip31068Lcd<I, D>
+where
+    I: I2c,
+    D: DelayNs,
+{
+    i2c: I,
+    address: u8,
+    delay: D,
+    display_func: u8,
+    display_ctrl: u8,
+    display_mode: u8,
+    cols: u8,
+    col: u8,
+    row: u8,
+    yielder: YieldHook,
+    code: Error,
+}
+
+impl<I, D> Aip31068Lcd<I, D>
+where
+    I: I2c,
+    D: DelayNs,
+{
+    /// Create a new AIP31068L display at the default address ([AIP31068_ADDRESS]),
+    /// configured as a 16x2 display.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let i2c_bus = arduino_hal::i2c::I2c::new(peripherals.TWI, sda, scl, 50000);
+    ///
+    /// let mut lcd = Aip31068Lcd::new(i2c_bus, delay).build();
+    /// lcd.print("Hello, World");
+    /// ```
+    pub fn new(i2c: I, delay: D) -> Self {
+        Self {
+            i2c,
+            address: AIP31068_ADDRESS,
+            delay,
+            display_func: EIGHT_BITS | Lines::TwoLines as u8,
+            display_ctrl: Display::On as u8,
+            display_mode: Layout::LeftToRight as u8,
+            cols: 16,
+            col: 0,
+            row: 0,
+            yielder: YieldHook::new(),
+            code: Error::None,
+        }
+    }
+
+    /// Set the 7-bit I2C address of the display.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd = Aip31068Lcd::new(i2c_bus, delay)
+    ///     .with_address(0x3F)
+    ///     .build();
+    /// ```
+    pub fn with_address(mut self, address: u8) -> Self {
+        self.address = address;
+        self
+    }
+
+    /// Set the number of lines on the display. (Default is Lines::TwoLines)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd = Aip31068Lcd::new(i2c_bus, delay)
+    ///     .with_lines(Lines::OneLine)
+    ///     .build();
+    /// ```
+    pub fn with_lines(mut self, value: Lines) -> Self {
+        self.display_func = EIGHT_BITS | value as u8;
+        self
+    }
+
+    /// Set the number of columns on the display. (Default is 16)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd = Aip31068Lcd::new(i2c_bus, delay)
+    ///     .with_cols(20)
+    ///     .build();
+    /// ```
+    pub fn with_cols(mut self, cols: u8) -> Self {
+        self.cols = cols.clamp(1, 40);
+        self
+    }
+
+    /// Set a hook that is called at least every `interval` during the driver's blocking
+    /// delays, so a watchdog can be fed while the display waits.
+    /// (See [LcdDisplay::with_yield_hook][crate::LcdDisplay::with_yield_hook])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// fn feed() {
+    ///     // reset the watchdog timer
+    /// }
+    ///
+    /// let mut lcd = Aip31068Lcd::new(i2c_bus, delay)
+    ///     .with_yield_hook(feed, 1000)
+    ///     .build();
+    /// ```
+    pub fn with_yield_hook<M: Microseconds>(mut self, hook: fn(), interval: M) -> Self {
+        self.set_yield_hook(Some(hook), interval);
+        self
+    }
+
+    /// Set or remove the hook called during blocking delays.
+    /// (See [with_yield_hook][Aip31068Lcd::with_yield_hook])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: Aip31068Lcd<_,_> = ...;
+    ///
+    /// lcd.set_yield_hook(Some(feed), 1000);
+    /// ...
+    /// lcd.set_yield_hook(None, 0);
+    /// ```
+    pub fn set_yield_hook<M: Microseconds>(&mut self, hook: Option<fn()>, interval: M) {
+        self.yielder.set(hook, interval.to_micros());
+    }
+
+    /// Finish construction and initialize the display.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd = Aip31068Lcd::new(i2c_bus, delay)
+    ///     .with_lines(Lines::TwoLines)
+    ///     .build();
+    /// ```
+    pub fn build(mut self) -> Self {
+        self.pause(50_000);
+
+        for _ in 0..3 {
+            self.command(SET_DISPLAY_FUNC | self.display_func);
+            self.pause(4500);
+        }
+
+        self.command(SET_DISPLAY_CTRL | self.display_ctrl);
+        self.command(SET_DISPLAY_MODE | self.display_mode);
+        self.clear();
+        self
+    }
+
+    /// Print a message to the display.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: Aip31068Lcd<_,_> = ...;
+    ///
+    /// lcd.print("Hello, World");
+    /// ```
+    pub fn print(&mut self, text: &str) {
+        for ch in text.chars() {
+            self.write(ch as u8);
+        }
+    }
+
+    /// Write a single character to the display.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: Aip31068Lcd<_,_> = ...;
+    ///
+    /// lcd.write(b'A');
+    /// ```
+    pub fn write(&mut self, value: u8) {
+        self.data(&[value]);
+        self.col = self.col.saturating_add(1);
+    }
+
+    /// Clear the display.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: Aip31068Lcd<_,_> = ...;
+    ///
+    /// lcd.clear();
+    /// ```
+    pub fn clear(&mut self) {
+        self.command(CLEAR_DISPLAY);
+        self.pause(CLEAR_DELAY);
+        (self.col, self.row) = (0, 0);
+    }
+
+    /// Move the cursor to the home position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: Aip31068Lcd<_,_> = ...;
+    ///
+    /// lcd.print("Hello");
+    /// lcd.home();
+    /// ```
+    pub fn home(&mut self) {
+        self.command(RETURN_HOME);
+        self.pause(CLEAR_DELAY);
+        (self.col, self.row) = (0, 0);
+    }
+
+    /// Set the position of the cursor. Rows past the last are clamped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: Aip31068Lcd<_,_> = ...;
+    ///
+    /// lcd.set_position(4, 1);
+    /// lcd.print("World");
+    /// ```
+    pub fn set_position(&mut self, col: u8, row: u8) {
+        let row = row.min(self.rows() - 1);
+        let offsets = [0x00, 0x40, self.cols, 0x40 + self.cols];
+        self.command(SET_DDRAM_ADDR | offsets[row as usize].saturating_add(col));
+        (self.col, self.row) = (col, row);
+    }
+
+    /// Get the cursor position as (column, row). Only positions set or written through
+    /// this struct are tracked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: Aip31068Lcd<_,_> = ...;
+    ///
+    /// lcd.print("Hello");
+    /// assert_eq!(lcd.position(), (5, 0));
+    /// ```
+    pub fn position(&self) -> (u8, u8) {
+        (self.col, self.row)
+    }
+
+    /// Get the number of columns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let lcd = Aip31068Lcd::new(i2c_bus, delay).with_cols(20).build();
+    /// assert_eq!(lcd.cols(), 20);
+    /// ```
+    pub fn cols(&self) -> u8 {
+        self.cols
+    }
+
+    /// Get the number of rows for the configured number of lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let lcd = Aip31068Lcd::new(i2c_bus, delay).with_lines(Lines::TwoLines).build();
+    /// assert_eq!(lcd.rows(), 2);
+    /// ```
+    pub fn rows(&self) -> u8 {
+        match self.display_func & Lines::FourLines as u8 {
+            0x0C => 4,
+            0x08 => 2,
+            _ => 1,
+        }
+    }
+
+    /// Turn the display on or off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: Aip31068Lcd<_,_> = ...;
+    ///
+    /// lcd.set_display(Display::Off);
+    /// ```
+    pub fn set_display(&mut self, display: Display) {
+        self.display_ctrl &= !(Display::On as u8);
+        self.display_ctrl |= display as u8;
+        self.command(SET_DISPLAY_CTRL | self.display_ctrl);
+    }
+
+    /// Turn the cursor on or off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: Aip31068Lcd<_,_> = ...;
+    ///
+    /// lcd.set_cursor(Cursor::On);
+    /// ```
+    pub fn set_cursor(&mut self, cursor: Cursor) {
+        self.display_ctrl &= !(Cursor::On as u8);
+        self.display_ctrl |= cursor as u8;
+        self.command(SET_DISPLAY_CTRL | self.display_ctrl);
+    }
+
+    /// Make the background of the cursor blink or stop blinking.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: Aip31068Lcd<_,_> = ...;
+    ///
+    /// lcd.set_blink(Blink::On);
+    /// ```
+    pub fn set_blink(&mut self, blink: Blink) {
+        self.display_ctrl &= !(Blink::On as u8);
+        self.display_ctrl |= blink as u8;
+        self.command(SET_DISPLAY_CTRL | self.display_ctrl);
+    }
+
+    /// Set the text direction layout.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: Aip31068Lcd<_,_> = ...;
+    ///
+    /// lcd.set_layout(Layout::RightToLeft);
+    /// ```
+    pub fn set_layout(&mut self, layout: Layout) {
+        self.display_mode &= !(Layout::LeftToRight as u8);
+        self.display_mode |= layout as u8;
+        self.command(SET_DISPLAY_MODE | self.display_mode);
+    }
+
+    /// Turn auto scroll on or off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: Aip31068Lcd<_,_> = ...;
+    ///
+    /// lcd.set_autoscroll(AutoScroll::On);
+    /// ```
+    pub fn set_autoscroll(&mut self, scroll: AutoScroll) {
+        self.display_mode &= !(AutoScroll::On as u8);
+        self.display_mode |= scroll as u8;
+        self.command(SET_DISPLAY_MODE | self.display_mode);
+    }
+
+    /// Scroll the display right or left.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: Aip31068Lcd<_,_> = ...;
+    ///
+    /// lcd.set_scroll(Scroll::Left, 2);
+    /// ```
+    pub fn set_scroll(&mut self, direction: Scroll, distance: u8) {
+        let command = CURSOR_SHIFT | MOVE_DISPLAY | direction as u8;
+        for _ in 0..distance {
+            self.command(command);
+        }
+    }
+
+    /// Add a new character map to CGRAM at a location from 0-7. Other locations set the
+    /// error code to [Error::OutOfBounds] and are masked to 0-7.
+    /// (See [LcdDisplay::set_character][crate::LcdDisplay::set_character])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: Aip31068Lcd<_,_> = ...;
+    ///
+    /// // set a sideways smiley face in CGRAM at location 0.
+    /// lcd.set_character(0u8,[
+    ///     0b00110,
+    ///     0b00001,
+    ///     0b11001,
+    ///     0b00001,
+    ///     0b00001,
+    ///     0b11001,
+    ///     0b00001,
+    ///     0b00110,
+    /// ]);
+    /// lcd.write(0u8);
+    /// ```
+    pub fn set_character(&mut self, location: u8, map: [u8; 8]) {
+        if location > 7 {
+            self.code = Error::OutOfBounds;
+        }
+
+        self.command(SET_CGRAM_ADDR | ((location & 0x7) << 3));
+        self.data(&map);
+        self.set_position(self.col, self.row);
+    }
+
+    /// Check that the display acknowledges its I2C address, by sending the current entry
+    /// mode again. Returns false and sets the error code to [Error::NotDetected] if it
+    /// doesn't answer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd = Aip31068Lcd::new(i2c_bus, delay).build();
+    ///
+    /// if !lcd.probe() {
+    ///     // check the address and the wiring
+    /// }
+    /// ```
+    pub fn probe(&mut self) -> bool {
+        let found = self
+            .i2c
+            .write(
+                self.address,
+                &[CONTROL_COMMAND, SET_DISPLAY_MODE | self.display_mode],
+            )
+            .is_ok();
+        if !found {
+            self.code = Error::NotDetected;
+        }
+        self.pause(CMD_DELAY);
+        found
+    }
+
+    /// Get the current error code.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: Aip31068Lcd<_,_> = ...;
+    ///
+    /// lcd.print("Hello");
+    ///
+    /// if lcd.error() == Error::BusError {
+    ///     // the display didn't acknowledge
+    /// }
+    /// ```
+    pub fn error(&self) -> Error {
+        self.code.clone()
+    }
+
+    /// Release the underlying I2C bus and delay.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let lcd = Aip31068Lcd::new(i2c_bus, delay).build();
+    /// let (i2c_bus, delay) = lcd.release();
+    /// ```
+    pub fn release(self) -> (I, D) {
+        (self.i2c, self.delay)
+    }
+
+    /// Block for `us` microseconds, calling the yield hook as often as it asks for.
+    fn pause(&mut self, us: u32) {
+        self.yielder.pause(&mut self.delay, us);
+    }
+
+    /// Send a single instruction byte.
+    fn command(&mut self, value: u8) {
+        if self
+            .i2c
+            .write(self.address, &[CONTROL_COMMAND, value])
+            .is_err()
+        {
+            self.code = Error::BusError;
+        }
+        self.pause(CMD_DELAY);
+    }
+
+    /// Send data bytes to the currently selected memory (DDRAM or CGRAM).
+    fn data(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            if self
+                .i2c
+                .write(self.address, &[CONTROL_DATA, *byte])
+                .is_err()
+            {
+                self.code = Error::BusError;
+            }
+            self.pause(CMD_DELAY);
+        }
+    }
+}
+
+impl<I, D> CharacterDisplay for Aip31068Lcd<I, D>
+where
+    I: I2c,
+    D: DelayNs,
+{
+    fn cols(&self) -> u8 {
+        Aip31068Lcd::cols(self)
+    }
+
+    fn rows(&self) -> u8 {
+        Aip31068Lcd::rows(self)
+    }
+
+    fn print(&mut self, text: &str) {
+        Aip31068Lcd::print(self, text);
+    }
+
+    fn write(&mut self, value: u8) {
+        Aip31068Lcd::write(self, value);
+    }
+
+    fn set_position(&mut self, col: u8, row: u8) {
+        Aip31068Lcd::set_position(self, col, row);
+    }
+
+    fn position(&self) -> (u8, u8) {
+        Aip31068Lcd::position(self)
+    }
+
+    fn clear(&mut self) {
+        Aip31068Lcd::clear(self);
+    }
+
+    fn home(&mut self) {
+        Aip31068Lcd::home(self);
+    }
+
+    fn set_character(&mut self, location: u8, map: [u8; 8]) {
+        Aip31068Lcd::set_character(self, location, map);
+    }
+
+    fn set_cursor(&mut self, cursor: Cursor) {
+        Aip31068Lcd::set_cursor(self, cursor);
+    }
+
+    fn set_blink(&mut self, blink: Blink) {
+        Aip31068Lcd::set_blink(self, blink);
+    }
+}
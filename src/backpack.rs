@@ -0,0 +1,193 @@
+//! A simple serial command protocol, for using a spare microcontroller as a serial LCD backpack
+
+use crate::{Backlight, LcdDisplay};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+
+/// Starts a command
+const ESCAPE: u8 = 0x1B;
+
+/// Clears the display
+const FORM_FEED: u8 = 0x0C;
+
+/// Moves to the start of the next row
+const LINE_FEED: u8 = b'\n';
+
+/// Moves to the start of the current row
+const CARRIAGE_RETURN: u8 = b'\r';
+
+/// What the next byte means
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum State {
+    /// Text or a control byte
+    Text,
+
+    /// The command letter after an escape
+    Command,
+
+    /// The arguments of a command, until `len` of them have arrived
+    Arguments { command: u8, len: usize },
+}
+
+/// An interpreter for a simple byte protocol that drives a display
+///
+/// Bytes received from a UART (or any other source) are passed to [feed][Backpack::feed]
+/// one at a time, which makes a microcontroller with a display into a serial LCD
+/// backpack. Printable bytes are shown at the cursor, bytes 0x00-0x07 show the custom
+/// characters, and a few control bytes and escape (0x1B) commands do the rest:
+///
+/// | Bytes                       | Action                                          |
+/// |-----------------------------|-------------------------------------------------|
+/// | `0x0C`                      | Clear the display                               |
+/// | `\n` (`0x0A`)               | Move to the start of the next row               |
+/// | `\r` (`0x0D`)               | Move to the start of the current row            |
+/// | `ESC 'H'`                   | Move the cursor home                            |
+/// | `ESC 'G' col row`           | Move the cursor to a position                   |
+/// | `ESC 'B' 0/1`               | Turn the backlight off or on                    |
+/// | `ESC 'D' 0/1`               | Turn the display off or on                      |
+/// | `ESC 'C' slot b0 .. b7`     | Set custom character `slot` (0-7) to 8 rows     |
+/// | `ESC 'I' byte`              | Send a raw instruction to the controller        |
+///
+/// Arguments are raw bytes, not ASCII digits. Unknown commands are ignored.
+///
+/// # Examples
+///
+/// ```
+/// let mut lcd: LcdDisplay<_,_> = ...;
+/// let mut backpack = Backpack::new();
+///
+/// loop {
+///     if let Ok(byte) = serial.read() {
+///         backpack.feed(&mut lcd, byte);
+///     }
+/// }
+/// ```
+pub struct Backpack {
+    state: State,
+    args: [u8; 9],
+    len: usize,
+}
+
+impl Backpack {
+    /// Create an interpreter that expects text.
+    pub fn new() -> Self {
+        Self {
+            state: State::Text,
+            args: [0; 9],
+            len: 0,
+        }
+    }
+
+    /// Handle a single received byte.
+    pub fn feed<T, D>(&mut self, lcd: &mut LcdDisplay<T, D>, byte: u8)
+    where
+        T: OutputPin,
+        D: DelayNs,
+    {
+        match self.state {
+            State::Text => self.text(lcd, byte),
+            State::Command => {
+                let len = match byte {
+                    b'G' => 2,
+                    b'B' | b'D' | b'I' => 1,
+                    b'C' => 9,
+                    _ => 0,
+                };
+                self.len = 0;
+                self.state = State::Arguments { command: byte, len };
+                if len == 0 {
+                    self.run(lcd, byte);
+                }
+            }
+            State::Arguments { command, len } => {
+                self.args[self.len] = byte;
+                self.len += 1;
+                if self.len >= len {
+                    self.run(lcd, command);
+                }
+            }
+        }
+    }
+
+    /// Read and handle every byte that is waiting in `serial`. Returns false if the
+    /// serial port reported an error. This method is only available if the `serial`
+    /// feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// loop {
+    ///     backpack.poll(&mut lcd, &mut serial);
+    /// }
+    /// ```
+    #[cfg(feature = "serial")]
+    pub fn poll<T, D, S>(&mut self, lcd: &mut LcdDisplay<T, D>, serial: &mut S) -> bool
+    where
+        T: OutputPin,
+        D: DelayNs,
+        S: embedded_hal_nb::serial::Read<u8>,
+    {
+        loop {
+            match serial.read() {
+                Ok(byte) => self.feed(lcd, byte),
+                Err(nb::Error::WouldBlock) => return true,
+                Err(nb::Error::Other(_)) => return false,
+            }
+        }
+    }
+
+    /// Show a byte of text or handle a control byte.
+    fn text<T, D>(&mut self, lcd: &mut LcdDisplay<T, D>, byte: u8)
+    where
+        T: OutputPin,
+        D: DelayNs,
+    {
+        match byte {
+            ESCAPE => self.state = State::Command,
+            FORM_FEED => lcd.clear(),
+            LINE_FEED => {
+                let row = (lcd.position().1 + 1) % lcd.rows();
+                lcd.set_position(0, row);
+            }
+            CARRIAGE_RETURN => lcd.set_position(0, lcd.position().1),
+            0x00..=0x07 | 0x20..=0xFF => lcd.write(byte),
+            _ => {}
+        }
+    }
+
+    /// Run a command once all of its arguments have arrived.
+    fn run<T, D>(&mut self, lcd: &mut LcdDisplay<T, D>, command: u8)
+    where
+        T: OutputPin,
+        D: DelayNs,
+    {
+        let args = self.args;
+        match command {
+            b'H' => lcd.home(),
+            b'G' => lcd.set_position(args[0], args[1]),
+            b'B' => lcd.set_backlight(match args[0] {
+                0 => Backlight::Off,
+                _ => Backlight::On,
+            }),
+            b'D' => match args[0] {
+                0 => lcd.display_off(),
+                _ => lcd.display_on(),
+            },
+            b'C' => {
+                let mut map = [0; 8];
+                map.copy_from_slice(&args[1..9]);
+                lcd.set_character(args[0], map);
+                lcd.set_position(lcd.position().0, lcd.position().1);
+            }
+            b'I' => lcd.raw_command(args[0]),
+            _ => {}
+        }
+        self.state = State::Text;
+    }
+}
+
+impl Default for Backpack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
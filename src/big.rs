@@ -0,0 +1,182 @@
+//! Large letters and digits drawn across 2x2 character cells
+
+use crate::CharacterDisplay;
+
+/// Left stroke with a bar across the top
+const LT: u8 = 0;
+
+/// Right stroke with a bar across the top
+const RT: u8 = 1;
+
+/// Left stroke with a bar across the bottom
+const LB: u8 = 2;
+
+/// Right stroke with a bar across the bottom
+const RB: u8 = 3;
+
+/// Left stroke
+const L: u8 = 4;
+
+/// Right stroke
+const R: u8 = 5;
+
+/// Left stroke with bars across the top and the bottom
+const LTB: u8 = 6;
+
+/// Right stroke with bars across the top and the bottom
+const RTB: u8 = 7;
+
+/// Blank cell (a space in the character ROM)
+const SP: u8 = b' ';
+
+/// The segments that big characters are built from, stored in CGRAM slots 0-7
+#[rustfmt::skip]
+const SEGMENTS: [[u8; 8]; 8] = [
+    [0b11111, 0b11111, 0b11000, 0b11000, 0b11000, 0b11000, 0b11000, 0b11000], // LT
+    [0b11111, 0b11111, 0b00011, 0b00011, 0b00011, 0b00011, 0b00011, 0b00011], // RT
+    [0b11000, 0b11000, 0b11000, 0b11000, 0b11000, 0b11000, 0b11111, 0b11111], // LB
+    [0b00011, 0b00011, 0b00011, 0b00011, 0b00011, 0b00011, 0b11111, 0b11111], // RB
+    [0b11000, 0b11000, 0b11000, 0b11000, 0b11000, 0b11000, 0b11000, 0b11000], // L
+    [0b00011, 0b00011, 0b00011, 0b00011, 0b00011, 0b00011, 0b00011, 0b00011], // R
+    [0b11111, 0b11111, 0b11000, 0b11000, 0b11000, 0b11000, 0b11111, 0b11111], // LTB
+    [0b11111, 0b11111, 0b00011, 0b00011, 0b00011, 0b00011, 0b11111, 0b11111], // RTB
+];
+
+/// Cells of 'A' to 'Z' as top left, top right, bottom left, bottom right
+#[rustfmt::skip]
+const LETTERS: [[u8; 4]; 26] = [
+    [LT,  RT,  LT,  RT ], // A
+    [LTB, RTB, LB,  RB ], // B
+    [LT,  SP,  LB,  SP ], // C
+    [SP,  R,   LTB, RTB], // D (lowercase, so it differs from O)
+    [LTB, SP,  LB,  SP ], // E
+    [LTB, SP,  L,   SP ], // F
+    [LT,  SP,  LB,  RTB], // G
+    [L,   R,   LT,  RT ], // H
+    [RT,  LT,  RB,  LB ], // I
+    [SP,  R,   LB,  RB ], // J
+    [L,   RB,  LT,  R  ], // K
+    [L,   SP,  LB,  SP ], // L
+    [LT,  RT,  L,   R  ], // M
+    [LT,  R,   L,   RB ], // N
+    [LT,  RT,  LB,  RB ], // O
+    [LTB, RTB, L,   SP ], // P
+    [LT,  RT,  LB,  RTB], // Q
+    [LTB, RTB, L,   R  ], // R
+    [LTB, SP,  SP,  RTB], // S
+    [RT,  LT,  R,   L  ], // T
+    [L,   R,   LB,  RB ], // U
+    [L,   R,   RB,  LB ], // V
+    [L,   R,   LTB, RTB], // W
+    [RB,  LB,  RT,  LT ], // X
+    [RB,  LB,  R,   L  ], // Y
+    [SP,  RTB, LTB, SP ], // Z
+];
+
+/// Cells of '0' to '9' as top left, top right, bottom left, bottom right
+#[rustfmt::skip]
+const DIGITS: [[u8; 4]; 10] = [
+    [LT,  RT,  LB,  RB ], // 0
+    [SP,  R,   SP,  R  ], // 1
+    [SP,  RTB, LTB, SP ], // 2
+    [SP,  RTB, SP,  RTB], // 3
+    [LB,  RB,  SP,  R  ], // 4
+    [LTB, SP,  SP,  RTB], // 5
+    [L,   SP,  LTB, RTB], // 6
+    [SP,  RT,  SP,  R  ], // 7
+    [LTB, RTB, LB,  RB ], // 8
+    [LTB, RTB, SP,  R  ], // 9
+];
+
+/// Letters and digits two characters wide and two rows high
+///
+/// Each big character is drawn from eight line segments that are stored in CGRAM
+/// slots 0-7 the first time something is printed, so other custom characters can't be
+/// shown at the same time. The letters A-Z (in either case) and the digits 0-9 are
+/// covered, and every other character is left blank. With only eight segments some
+/// shapes are compromises: D is drawn in lowercase so it doesn't look like O, and
+/// letters with diagonals (K, M, N, V, W, X, Y) are approximations. Characters are
+/// separated by a blank column, so a word of `n` characters is `3n - 1` columns wide.
+///
+/// # Examples
+///
+/// ```
+/// let mut lcd: LcdDisplay<_,_> = ...;
+/// let mut big = BigFont::new();
+///
+/// big.print(&mut lcd, 0, 0, "RUN");
+/// // later, on the same rows
+/// big.print(&mut lcd, 0, 0, "STOP");
+/// ```
+pub struct BigFont {
+    loaded: bool,
+}
+
+impl BigFont {
+    /// Create a big font. The segments are stored the first time it prints.
+    pub const fn new() -> Self {
+        Self { loaded: false }
+    }
+
+    /// Get the number of columns that `text` takes up when printed.
+    pub fn width(text: &str) -> u8 {
+        (text.chars().count() * 3)
+            .saturating_sub(1)
+            .min(u8::MAX as usize) as u8
+    }
+
+    /// Store the segments again on the next print, for example after other custom
+    /// characters were stored in CGRAM.
+    pub fn invalidate(&mut self) {
+        self.loaded = false;
+    }
+
+    /// Print `text` in big characters with the top left corner at `col`, `row`. Text
+    /// past the right edge of the display is cut off. The cursor position is not changed.
+    pub fn print<L>(&mut self, lcd: &mut L, col: u8, row: u8, text: &str)
+    where
+        L: CharacterDisplay,
+    {
+        if !self.loaded {
+            for (slot, map) in (0..).zip(SEGMENTS) {
+                lcd.set_character(slot, map);
+            }
+            self.loaded = true;
+        }
+
+        let cols = lcd.cols();
+        let position = lcd.position();
+        for (line, half) in [0, 2].into_iter().enumerate() {
+            let mut x = col;
+            lcd.set_position(col, row.saturating_add(line as u8));
+
+            for (index, ch) in text.chars().enumerate() {
+                let cells = Self::cells(ch);
+                let gap = if index > 0 { Some(SP) } else { None };
+                for code in gap.into_iter().chain([cells[half], cells[half + 1]]) {
+                    if x >= cols {
+                        break;
+                    }
+                    lcd.write(code);
+                    x += 1;
+                }
+            }
+        }
+        lcd.set_position(position.0, position.1);
+    }
+
+    /// Find the cells that draw a character.
+    fn cells(ch: char) -> [u8; 4] {
+        match ch.to_ascii_uppercase() {
+            c @ 'A'..='Z' => LETTERS[c as usize - 'A' as usize],
+            c @ '0'..='9' => DIGITS[c as usize - '0' as usize],
+            _ => [SP; 4],
+        }
+    }
+}
+
+impl Default for BigFont {
+    fn default() -> Self {
+        Self::new()
+    }
+}
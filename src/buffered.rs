@@ -0,0 +1,383 @@
+//! A display that draws into a buffer in RAM and only sends the characters that changed
+
+use crate::{rotate_glyph, LcdDisplay};
+use core::fmt;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+
+/// How the display is mounted
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Rotation {
+    /// The right way up (default)
+    Normal,
+
+    /// Rotated by 180°, so the first row is at the bottom and runs right to left
+    UpsideDown,
+}
+
+/// An [LcdDisplay] with a `COLS` x `ROWS` shadow buffer
+///
+/// Text is written to a frame buffer in RAM, and [flush][BufferedLcd::flush] compares it
+/// to what is already on the glass and only sends the characters that changed. Outside
+/// of a group of updates (see [begin][BufferedLcd::begin]) every print is flushed right
+/// away; inside a group nothing is sent until [commit][BufferedLcd::commit], so the
+/// screen never shows a half-updated frame.
+///
+/// Text that runs past the end of a row is dropped rather than wrapped. Anything sent
+/// directly to the display with [lcd_mut][BufferedLcd::lcd_mut] isn't tracked, so call
+/// [invalidate][BufferedLcd::invalidate] afterwards to redraw the whole frame.
+///
+/// For a module that has to be mounted upside down, [with_rotation][BufferedLcd::with_rotation]
+/// sends the frame in reverse, so rows and columns come out in the right order. The
+/// character ROM can't be turned around, so only custom characters (which
+/// [set_character][BufferedLcd::set_character] flips) and symmetric ones like blocks
+/// and spaces look right.
+///
+/// # Examples
+///
+/// ```
+/// let lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+///     .with_half_bus(d4, d5, d6, d7)
+///     .with_lines(Lines::TwoLines)
+///     .build();
+///
+/// let mut screen: BufferedLcd<_, _, 16, 2> = BufferedLcd::new(lcd);
+///
+/// screen.begin();
+/// screen.print_at(0, 0, "Temp: 21.5 C");
+/// screen.print_at(0, 1, "Fan:  45 %");
+/// screen.commit(); // only the changed characters are sent
+/// ```
+pub struct BufferedLcd<T, D, const COLS: usize, const ROWS: usize>
+where
+    T: OutputPin + Sized,
+    D: DelayNs + Sized,
+{
+    lcd: LcdDisplay<T, D>,
+    frame: [[u8; COLS]; ROWS],
+    shown: [[u8; COLS]; ROWS],
+    col: usize,
+    row: usize,
+    rotation: Rotation,
+}
+
+impl<T, D, const COLS: usize, const ROWS: usize> BufferedLcd<T, D, COLS, ROWS>
+where
+    T: OutputPin + Sized,
+    D: DelayNs + Sized,
+{
+    /// Create a buffered display from a built [LcdDisplay]. The display is cleared.
+    pub fn new(mut lcd: LcdDisplay<T, D>) -> Self {
+        lcd.clear();
+        Self {
+            lcd,
+            frame: [[b' '; COLS]; ROWS],
+            shown: [[b' '; COLS]; ROWS],
+            col: 0,
+            row: 0,
+            rotation: Rotation::Normal,
+        }
+    }
+
+    /// Set how the display is mounted. With [Rotation::UpsideDown] the order of the rows
+    /// and columns is reversed when the frame is sent. (Default is Rotation::Normal)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut screen: BufferedLcd<_, _, 16, 2> = BufferedLcd::new(lcd)
+    ///     .with_rotation(Rotation::UpsideDown);
+    /// ```
+    pub fn with_rotation(mut self, rotation: Rotation) -> Self {
+        self.set_rotation(rotation);
+        self
+    }
+
+    /// Set how the display is mounted and redraw the frame. (See [with_rotation][BufferedLcd::with_rotation])
+    pub fn set_rotation(&mut self, rotation: Rotation) {
+        self.rotation = rotation;
+        self.invalidate();
+        self.auto_flush();
+    }
+
+    /// Get how the display is mounted.
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+
+    /// Create a custom character, turned upside down if the display is
+    /// [rotated][BufferedLcd::with_rotation]. To upload a map as it is, use
+    /// [lcd_mut][BufferedLcd::lcd_mut]. (See [LcdDisplay::set_character])
+    pub fn set_character(&mut self, location: u8, map: [u8; 8]) {
+        let map = match self.rotation {
+            Rotation::Normal => map,
+            Rotation::UpsideDown => rotate_glyph(map),
+        };
+        self.lcd.set_character(location, map);
+    }
+
+    /// Print a message at the cursor position.
+    pub fn print(&mut self, text: &str) {
+        for ch in text.chars() {
+            self.put(ch as u8);
+        }
+        self.auto_flush();
+    }
+
+    /// Move the cursor to a position and print a message there.
+    pub fn print_at(&mut self, col: u8, row: u8, text: &str) {
+        self.set_position(col, row);
+        self.print(text);
+    }
+
+    /// Write a single character at the cursor position.
+    pub fn write(&mut self, value: u8) {
+        self.put(value);
+        self.auto_flush();
+    }
+
+    /// Move the cursor to a position.
+    pub fn set_position(&mut self, col: u8, row: u8) {
+        self.col = col as usize;
+        self.row = row as usize;
+    }
+
+    /// Get the cursor position as (column, row).
+    pub fn position(&self) -> (u8, u8) {
+        (self.col as u8, self.row as u8)
+    }
+
+    /// Clear the frame and move the cursor home.
+    pub fn clear(&mut self) {
+        self.frame = [[b' '; COLS]; ROWS];
+        self.col = 0;
+        self.row = 0;
+        self.auto_flush();
+    }
+
+    /// Get the character at a position in the frame, or None if the position is
+    /// outside of the frame.
+    pub fn get(&self, col: u8, row: u8) -> Option<u8> {
+        self.frame
+            .get(row as usize)
+            .and_then(|r| r.get(col as usize))
+            .copied()
+    }
+
+    /// Get a copy of the frame, which is what the screen shows once it is flushed. This is
+    /// useful for checking a user interface in host-side tests, or for dumping the screen
+    /// over a serial port.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// screen.print_at(0, 0, "Temp: 21.5 C");
+    ///
+    /// let snapshot = screen.snapshot();
+    /// assert_eq!(snapshot.row(0), Some(&b"Temp: 21.5 C    "[..]));
+    /// ```
+    pub fn snapshot(&self) -> Snapshot<COLS, ROWS> {
+        Snapshot { frame: self.frame }
+    }
+
+    /// Start a group of updates. Nothing is sent to the display until the matching
+    /// [commit][BufferedLcd::commit]. (See [LcdDisplay::begin])
+    pub fn begin(&mut self) {
+        self.lcd.begin();
+    }
+
+    /// Finish a group of updates and send everything that changed. (See [LcdDisplay::commit])
+    pub fn commit(&mut self) {
+        self.lcd.commit();
+        self.auto_flush();
+    }
+
+    /// Send every character that differs from what is on the display, with the
+    /// cursor hidden, then move the cursor to its position in the frame.
+    pub fn flush(&mut self) {
+        let mut changed = false;
+        for y in 0..ROWS {
+            let mut next = None;
+            for x in 0..COLS {
+                // walk the glass in order, so consecutive changes stay consecutive
+                let (col, row) = self.locate(x, y);
+                let value = self.frame[row][col];
+                if self.shown[row][col] == value {
+                    continue;
+                }
+
+                if !changed {
+                    changed = true;
+                    self.lcd.begin();
+                }
+
+                // consecutive changes don't need a new position
+                if next != Some(x) {
+                    self.lcd.set_position(x as u8, y as u8);
+                }
+                self.lcd.write(value);
+                self.shown[row][col] = value;
+                next = Some(x + 1);
+            }
+        }
+
+        if changed {
+            let (col, row) = match self.rotation {
+                Rotation::Normal => (self.col, self.row),
+                Rotation::UpsideDown => self.locate(
+                    self.col.min(COLS.saturating_sub(1)),
+                    self.row.min(ROWS.saturating_sub(1)),
+                ),
+            };
+            self.lcd.set_position(col as u8, row as u8);
+            self.lcd.commit();
+        }
+    }
+
+    /// Mark the whole frame as changed, so the next flush redraws every character.
+    pub fn invalidate(&mut self) {
+        for (shown, frame) in self.shown.iter_mut().zip(self.frame.iter()) {
+            for (shown, frame) in shown.iter_mut().zip(frame.iter()) {
+                *shown = !*frame;
+            }
+        }
+    }
+
+    /// Get the underlying display.
+    pub fn lcd(&self) -> &LcdDisplay<T, D> {
+        &self.lcd
+    }
+
+    /// Get mutable access to the underlying display, for settings that don't go through
+    /// the buffer. (See [invalidate][BufferedLcd::invalidate])
+    pub fn lcd_mut(&mut self) -> &mut LcdDisplay<T, D> {
+        &mut self.lcd
+    }
+
+    /// Release the underlying display.
+    pub fn release(self) -> LcdDisplay<T, D> {
+        self.lcd
+    }
+
+    /// Map a position on the glass to a position in the frame, or the other way around
+    /// (turning the display around twice puts it back the way it was).
+    fn locate(&self, col: usize, row: usize) -> (usize, usize) {
+        match self.rotation {
+            Rotation::Normal => (col, row),
+            Rotation::UpsideDown => (COLS - 1 - col, ROWS - 1 - row),
+        }
+    }
+
+    /// Store a character in the frame and advance the cursor.
+    fn put(&mut self, value: u8) {
+        if let Some(cell) = self
+            .frame
+            .get_mut(self.row)
+            .and_then(|r| r.get_mut(self.col))
+        {
+            *cell = value;
+        }
+        self.col = self.col.saturating_add(1);
+    }
+
+    /// Flush unless a group of updates is open.
+    fn auto_flush(&mut self) {
+        if !self.lcd.in_transaction() {
+            self.flush();
+        }
+    }
+}
+
+/// Implementation of ufmt::uWrite, so the uwrite/uwriteln macros can print into the frame
+#[cfg(feature = "ufmt")]
+impl<T, D, const COLS: usize, const ROWS: usize> ufmt::uWrite for BufferedLcd<T, D, COLS, ROWS>
+where
+    T: OutputPin + Sized,
+    D: DelayNs + Sized,
+{
+    type Error = core::convert::Infallible;
+
+    fn write_str(&mut self, s: &str) -> Result<(), Self::Error> {
+        self.print(s);
+        Ok(())
+    }
+}
+
+/// The contents of a [BufferedLcd] frame, returned by [snapshot][BufferedLcd::snapshot]
+///
+/// Formatting a snapshot (with `core::fmt`, or ufmt if the `ufmt` feature is enabled)
+/// writes the rows separated by newlines, with custom characters and other codes
+/// outside of printable ASCII shown as `?`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Snapshot<const COLS: usize, const ROWS: usize> {
+    frame: [[u8; COLS]; ROWS],
+}
+
+impl<const COLS: usize, const ROWS: usize> Snapshot<COLS, ROWS> {
+    /// Get the character codes in a row, or None if the row is outside of the frame.
+    pub fn row(&self, row: usize) -> Option<&[u8]> {
+        self.frame.get(row).map(|r| &r[..])
+    }
+
+    /// Write the rows into `buffer` as text, separated by newlines, and return the text.
+    /// The text is cut off if the buffer is too small.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut buffer = [0u8; 34];
+    /// assert_eq!(screen.snapshot().render_to_str(&mut buffer), "Hello           \n                ");
+    /// ```
+    pub fn render_to_str<'b>(&self, buffer: &'b mut [u8]) -> &'b str {
+        let mut len = 0;
+        for byte in self.chars() {
+            match buffer.get_mut(len) {
+                Some(slot) => *slot = byte,
+                None => break,
+            }
+            len += 1;
+        }
+
+        // every byte is printable ASCII or a newline
+        core::str::from_utf8(&buffer[..len]).unwrap_or_default()
+    }
+
+    /// Get the text of the frame, one byte at a time.
+    fn chars(&self) -> impl Iterator<Item = u8> + '_ {
+        self.frame.iter().enumerate().flat_map(|(i, row)| {
+            let separator = match i {
+                0 => None,
+                _ => Some(b'\n'),
+            };
+            separator
+                .into_iter()
+                .chain(row.iter().map(|byte| match byte {
+                    0x20..=0x7E => *byte,
+                    _ => b'?',
+                }))
+        })
+    }
+}
+
+impl<const COLS: usize, const ROWS: usize> fmt::Display for Snapshot<COLS, ROWS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.chars() {
+            fmt::Write::write_char(f, byte as char)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl<const COLS: usize, const ROWS: usize> ufmt::uDisplay for Snapshot<COLS, ROWS> {
+    fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+    where
+        W: ufmt::uWrite + ?Sized,
+    {
+        for byte in self.chars() {
+            f.write_char(byte as char)?;
+        }
+        Ok(())
+    }
+}
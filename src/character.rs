@@ -0,0 +1,195 @@
+//! A trait for character displays, so user interface code can be written for any backend
+
+use crate::{frame, Blink, BufferedLcd, Cursor, LcdDisplay, Window};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+
+/// The core operations of a character display
+///
+/// This is implemented by [LcdDisplay], [BufferedLcd], [MultiLcd][crate::MultiLcd],
+/// [NewhavenLcd][crate::NewhavenLcd] and [Aip31068Lcd][crate::Aip31068Lcd], and the
+/// widgets in this crate ([Scene][crate::Scene], [Marquee][crate::Marquee],
+/// [Sparkline][crate::Sparkline], [Scrollbar][crate::Scrollbar],
+/// [StatusLog][crate::StatusLog], the [frames][CharacterDisplay::draw_frame], the
+/// [Spinner][crate::Spinner] and [BlinkText][crate::BlinkText] elements and the
+/// `LcdLog`) draw on anything that implements it. Application code written against the
+/// trait can be tested on the host by implementing it for a type that records what
+/// would be shown.
+///
+/// # Examples
+///
+/// ```
+/// fn show_temperature<L: CharacterDisplay>(lcd: &mut L, text: &str) {
+///     lcd.print_at(0, 0, "Temp:");
+///     lcd.print_at(6, 0, text);
+/// }
+///
+/// let mut lcd: LcdDisplay<_,_> = ...;
+/// let mut newhaven = NewhavenLcd::new_i2c(i2c_bus, NEWHAVEN_ADDRESS, delay);
+///
+/// show_temperature(&mut lcd, "21.5");
+/// show_temperature(&mut newhaven, "21.5");
+/// ```
+pub trait CharacterDisplay {
+    /// Get the number of columns.
+    fn cols(&self) -> u8;
+
+    /// Get the number of rows.
+    fn rows(&self) -> u8;
+
+    /// Print a message at the cursor position.
+    fn print(&mut self, text: &str);
+
+    /// Write a single character code at the cursor position.
+    fn write(&mut self, value: u8);
+
+    /// Move the cursor to a position.
+    fn set_position(&mut self, col: u8, row: u8);
+
+    /// Get the cursor position as (column, row).
+    fn position(&self) -> (u8, u8);
+
+    /// Clear the display and move the cursor home.
+    fn clear(&mut self);
+
+    /// Move the cursor home.
+    fn home(&mut self) {
+        self.set_position(0, 0);
+    }
+
+    /// Store a custom character in CGRAM slot `location` (0-7). Other locations set the
+    /// error code to [Error::OutOfBounds][crate::Error::OutOfBounds] and are masked to 0-7.
+    fn set_character(&mut self, location: u8, map: [u8; 8]);
+
+    /// Show or hide the cursor.
+    fn set_cursor(&mut self, cursor: Cursor);
+
+    /// Turn blinking of the cursor position on or off.
+    fn set_blink(&mut self, blink: Blink);
+
+    /// Move the cursor to a position and print a message there.
+    fn print_at(&mut self, col: u8, row: u8, text: &str) {
+        self.set_position(col, row);
+        self.print(text);
+    }
+
+    /// Store the border glyphs in CGRAM slots 2 to 7.
+    /// (See [LcdDisplay::upload_frame_glyphs])
+    fn upload_frame_glyphs(&mut self) {
+        frame::upload_frame_glyphs(self);
+    }
+
+    /// Draw a box along the edges of `window`. (See [LcdDisplay::draw_frame])
+    fn draw_frame(&mut self, window: Window) {
+        frame::draw_frame(self, window);
+    }
+
+    /// Draw a horizontal divider on `row`. (See [LcdDisplay::draw_hline])
+    fn draw_hline(&mut self, row: u8, from: u8, to: u8) {
+        frame::draw_hline(self, row, from, to);
+    }
+}
+
+impl<T, D> CharacterDisplay for LcdDisplay<T, D>
+where
+    T: OutputPin + Sized,
+    D: DelayNs + Sized,
+{
+    fn cols(&self) -> u8 {
+        LcdDisplay::cols(self)
+    }
+
+    fn rows(&self) -> u8 {
+        LcdDisplay::rows(self)
+    }
+
+    fn print(&mut self, text: &str) {
+        LcdDisplay::print(self, text);
+    }
+
+    fn write(&mut self, value: u8) {
+        LcdDisplay::write(self, value);
+    }
+
+    fn set_position(&mut self, col: u8, row: u8) {
+        LcdDisplay::set_position(self, col, row);
+    }
+
+    fn position(&self) -> (u8, u8) {
+        LcdDisplay::position(self)
+    }
+
+    fn clear(&mut self) {
+        LcdDisplay::clear(self);
+    }
+
+    fn home(&mut self) {
+        LcdDisplay::home(self);
+    }
+
+    fn set_character(&mut self, location: u8, map: [u8; 8]) {
+        LcdDisplay::set_character(self, location, map);
+    }
+
+    fn set_cursor(&mut self, cursor: Cursor) {
+        LcdDisplay::set_cursor(self, cursor);
+    }
+
+    fn set_blink(&mut self, blink: Blink) {
+        LcdDisplay::set_blink(self, blink);
+    }
+
+    fn print_at(&mut self, col: u8, row: u8, text: &str) {
+        LcdDisplay::print_at(self, col, row, text);
+    }
+}
+
+impl<T, D, const COLS: usize, const ROWS: usize> CharacterDisplay for BufferedLcd<T, D, COLS, ROWS>
+where
+    T: OutputPin + Sized,
+    D: DelayNs + Sized,
+{
+    fn cols(&self) -> u8 {
+        COLS as u8
+    }
+
+    fn rows(&self) -> u8 {
+        ROWS as u8
+    }
+
+    fn print(&mut self, text: &str) {
+        BufferedLcd::print(self, text);
+    }
+
+    fn write(&mut self, value: u8) {
+        BufferedLcd::write(self, value);
+    }
+
+    fn set_position(&mut self, col: u8, row: u8) {
+        BufferedLcd::set_position(self, col, row);
+    }
+
+    fn position(&self) -> (u8, u8) {
+        BufferedLcd::position(self)
+    }
+
+    fn clear(&mut self) {
+        BufferedLcd::clear(self);
+    }
+
+    fn set_character(&mut self, location: u8, map: [u8; 8]) {
+        BufferedLcd::set_character(self, location, map);
+    }
+
+    fn set_cursor(&mut self, cursor: Cursor) {
+        self.lcd_mut().set_cursor(cursor);
+    }
+
+    fn set_blink(&mut self, blink: Blink) {
+        self.lcd_mut().set_blink(blink);
+    }
+
+    fn print_at(&mut self, col: u8, row: u8, text: &str) {
+        BufferedLcd::print_at(self, col, row, text);
+    }
+}
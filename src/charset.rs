@@ -0,0 +1,204 @@
+//! Mapping from unicode characters to the codes in the display's character ROM
+
+/// Flag that selects the character ROM built into the display controller
+///
+/// The lower half of both ROMs is close to ASCII, but the upper half is either
+/// Japanese katakana (A00) or western European and Cyrillic characters (A02).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Rom {
+    /// Japanese ROM, fitted to most displays (default)
+    A00,
+
+    /// European ROM
+    A02,
+}
+
+/// Flag that selects what [print_utf8][crate::LcdDisplay::print_utf8] does with characters
+/// that aren't in the character ROM
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Unmappable {
+    /// Leave the character out
+    Skip,
+
+    /// Print the given ROM code instead (default is Replace(b'?'))
+    Replace(u8),
+
+    /// Leave the character out and set [Error::Unmappable][crate::Error::Unmappable]
+    Error,
+}
+
+/// Flag that selects the direction for [print_arrow][crate::LcdDisplay::print_arrow]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Arrow {
+    /// Arrow pointing left
+    Left,
+
+    /// Arrow pointing right
+    Right,
+
+    /// Arrow pointing up (only in the A02 ROM)
+    Up,
+
+    /// Arrow pointing down (only in the A02 ROM)
+    Down,
+}
+
+impl Arrow {
+    /// Get the unicode character for the arrow
+    pub(crate) const fn as_char(self) -> char {
+        match self {
+            Arrow::Left => '←',
+            Arrow::Right => '→',
+            Arrow::Up => '↑',
+            Arrow::Down => '↓',
+        }
+    }
+}
+
+impl Rom {
+    /// Get the ROM code for a character, or None if the ROM doesn't contain it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(Rom::A00.encode('°'), Some(0xDF));
+    /// assert_eq!(Rom::A02.encode('°'), Some(0xB0));
+    /// ```
+    pub const fn encode(self, ch: char) -> Option<u8> {
+        match self {
+            Rom::A00 => encode_a00(ch),
+            Rom::A02 => encode_a02(ch),
+        }
+    }
+
+    /// Get the ROM code for a full-width katakana or hiragana character, along with the
+    /// voicing mark (dakuten or handakuten) printed after it if it has one. Only the A00
+    /// ROM contains katakana, so this is always None for other ROMs.
+    ///
+    /// Hiragana are shown as the matching katakana, since the ROM has no hiragana, and the
+    /// Japanese full stop, comma, brackets, middle dot and long vowel mark are mapped too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(Rom::A00.encode_kana('カ'), Some((0xB6, None)));
+    /// assert_eq!(Rom::A00.encode_kana('が'), Some((0xB6, Some(0xDE))));
+    /// ```
+    pub const fn encode_kana(self, ch: char) -> Option<(u8, Option<u8>)> {
+        if !matches!(self, Rom::A00) {
+            return None;
+        }
+
+        let code = ch as u32;
+        let katakana = match ch {
+            '\u{3041}'..='\u{3096}' => code + 0x60,
+            '\u{30A1}'..='\u{30FA}' => code,
+            '。' => return Some((0xA1, None)),
+            '「' => return Some((0xA2, None)),
+            '」' => return Some((0xA3, None)),
+            '、' => return Some((0xA4, None)),
+            '・' => return Some((0xA5, None)),
+            'ー' => return Some((0xB0, None)),
+            '\u{3099}' | '\u{309B}' => return Some((0xDE, None)),
+            '\u{309A}' | '\u{309C}' => return Some((0xDF, None)),
+            _ => return None,
+        };
+
+        let (base, mark) = KATAKANA[(katakana - 0x30A1) as usize];
+        if mark == 0 {
+            Some((base, None))
+        } else {
+            Some((base, Some(mark)))
+        }
+    }
+}
+
+/// Half-width codes and voicing marks (0 for none) for the katakana from U+30A1 to U+30FA
+#[rustfmt::skip]
+const KATAKANA: [(u8, u8); 90] = [
+    (0xA7, 0), (0xB1, 0), (0xA8, 0), (0xB2, 0), (0xA9, 0), (0xB3, 0),
+    (0xAA, 0), (0xB4, 0), (0xAB, 0), (0xB5, 0), (0xB6, 0), (0xB6, 0xDE),
+    (0xB7, 0), (0xB7, 0xDE), (0xB8, 0), (0xB8, 0xDE), (0xB9, 0), (0xB9, 0xDE),
+    (0xBA, 0), (0xBA, 0xDE), (0xBB, 0), (0xBB, 0xDE), (0xBC, 0), (0xBC, 0xDE),
+    (0xBD, 0), (0xBD, 0xDE), (0xBE, 0), (0xBE, 0xDE), (0xBF, 0), (0xBF, 0xDE),
+    (0xC0, 0), (0xC0, 0xDE), (0xC1, 0), (0xC1, 0xDE), (0xAF, 0), (0xC2, 0),
+    (0xC2, 0xDE), (0xC3, 0), (0xC3, 0xDE), (0xC4, 0), (0xC4, 0xDE), (0xC5, 0),
+    (0xC6, 0), (0xC7, 0), (0xC8, 0), (0xC9, 0), (0xCA, 0), (0xCA, 0xDE),
+    (0xCA, 0xDF), (0xCB, 0), (0xCB, 0xDE), (0xCB, 0xDF), (0xCC, 0), (0xCC, 0xDE),
+    (0xCC, 0xDF), (0xCD, 0), (0xCD, 0xDE), (0xCD, 0xDF), (0xCE, 0), (0xCE, 0xDE),
+    (0xCE, 0xDF), (0xCF, 0), (0xD0, 0), (0xD1, 0), (0xD2, 0), (0xD3, 0),
+    (0xAC, 0), (0xD4, 0), (0xAD, 0), (0xD5, 0), (0xAE, 0), (0xD6, 0),
+    (0xD7, 0), (0xD8, 0), (0xD9, 0), (0xDA, 0), (0xDB, 0), (0xDC, 0),
+    (0xDC, 0), (0xB2, 0), (0xB4, 0), (0xA6, 0), (0xDD, 0), (0xB3, 0xDE),
+    (0xB6, 0), (0xB9, 0), (0xDC, 0xDE), (0xB2, 0xDE), (0xB4, 0xDE), (0xA6, 0xDE),
+];
+
+/// Map a character to the A00 (Japanese) ROM
+const fn encode_a00(ch: char) -> Option<u8> {
+    let code = ch as u32;
+    match ch {
+        // backslash and tilde are replaced by yen and arrows
+        '\\' | '~' => None,
+        ' '..='}' => Some(code as u8),
+        '¥' => Some(0x5C),
+        '→' => Some(0x7E),
+        '←' => Some(0x7F),
+
+        // half-width katakana and punctuation are stored in JIS X 0201 order
+        '\u{FF61}'..='\u{FF9F}' => Some((code - 0xFF61 + 0xA1) as u8),
+        '°' => Some(0xDF),
+        'α' => Some(0xE0),
+        'ä' => Some(0xE1),
+        'β' => Some(0xE2),
+        'ε' => Some(0xE3),
+        'μ' | 'µ' => Some(0xE4),
+        'σ' => Some(0xE5),
+        'ρ' => Some(0xE6),
+        '√' => Some(0xE8),
+        '¢' => Some(0xEC),
+        'ñ' => Some(0xEE),
+        'ö' => Some(0xEF),
+        'θ' => Some(0xF2),
+        '∞' => Some(0xF3),
+        '\u{3A9}' | '\u{2126}' => Some(0xF4), // omega and ohm sign
+        'ü' => Some(0xF5),
+        'Σ' => Some(0xF6),
+        'π' => Some(0xF7),
+        '÷' => Some(0xFD),
+        '█' => Some(0xFF),
+        _ => None,
+    }
+}
+
+/// Map a character to the A02 (European) ROM
+const fn encode_a02(ch: char) -> Option<u8> {
+    let code = ch as u32;
+    match ch {
+        ' '..='~' => Some(code as u8),
+        '►' => Some(0x10),
+        '◄' => Some(0x11),
+        '↑' => Some(0x18),
+        '↓' => Some(0x19),
+        '→' => Some(0x1A),
+        '←' => Some(0x1B),
+        '≤' => Some(0x1C),
+        '≥' => Some(0x1D),
+        '▲' => Some(0x1E),
+        '▼' => Some(0x1F),
+        'α' => Some(0x90),
+        'π' => Some(0x93),
+        'Σ' => Some(0x94),
+        'σ' => Some(0x95),
+        '\u{3A9}' | '\u{2126}' => Some(0x9A), // omega and ohm sign
+        '∞' => Some(0x9C),
+        'ε' => Some(0x9E),
+        'μ' => Some(0xB5),
+
+        // the upper quarter follows Latin-1
+        '\u{A0}'..='\u{FF}' => Some(code as u8),
+        _ => None,
+    }
+}
@@ -0,0 +1,63 @@
+//! A delay that counts CPU cycles, for targets where the HAL's delay is needed elsewhere
+
+use embedded_hal::delay::DelayNs;
+
+/// Nanoseconds in a second
+const NANOS: u64 = 1_000_000_000;
+
+/// A busy-wait delay that spins for a number of CPU cycles
+///
+/// [LcdDisplay][crate::LcdDisplay] takes ownership of its delay, which is a problem on
+/// targets with a single `Delay` or SysTick that the rest of the firmware needs. A
+/// CycleDelay only needs the core clock frequency, and waits by counting cycles with
+/// `cortex_m::asm::delay` on Cortex-M (with the `cortex-m` feature) or with a busy loop
+/// on AVR and other targets (with the `avr-delay` feature). The loop is plain safe Rust,
+/// so it works on stable, and it errs on the long side since a pass takes several
+/// cycles. Interrupts and a clock set lower than given only make the delays longer,
+/// which the display tolerates.
+///
+/// # Examples
+///
+/// ```
+/// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, CycleDelay::new(16_000_000))
+///     .with_half_bus(d4, d5, d6, d7)
+///     .build();
+/// ```
+pub struct CycleDelay {
+    clock_hz: u32,
+}
+
+impl CycleDelay {
+    /// Create a delay for a CPU running at `clock_hz` cycles per second.
+    pub const fn new(clock_hz: u32) -> Self {
+        Self { clock_hz }
+    }
+
+    /// Get the clock frequency the delay counts cycles at.
+    pub fn clock_hz(&self) -> u32 {
+        self.clock_hz
+    }
+}
+
+impl DelayNs for CycleDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        let cycles = (ns as u64 * self.clock_hz as u64).div_ceil(NANOS);
+        spin(cycles.min(u32::MAX as u64) as u32);
+    }
+}
+
+/// Wait for at least `cycles` CPU cycles.
+#[cfg(all(feature = "cortex-m", not(target_arch = "avr")))]
+fn spin(cycles: u32) {
+    cortex_m::asm::delay(cycles);
+}
+
+/// Wait for at least `cycles` CPU cycles. Every pass of the loop takes at least one
+/// cycle (several on AVR), and black_box keeps the compiler from removing it.
+#[cfg(not(all(feature = "cortex-m", not(target_arch = "avr"))))]
+fn spin(cycles: u32) {
+    let mut passes = cycles;
+    while core::hint::black_box(passes) > 0 {
+        passes -= 1;
+    }
+}
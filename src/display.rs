@@ -1,6 +1,9 @@
-use crate::Error;
+use crate::time::YieldHook;
+use crate::{Arrow, Error, IoPin, Microseconds, Rom, Unmappable};
 use embedded_hal::delay::DelayNs;
-use embedded_hal::digital::OutputPin;
+use embedded_hal::digital::{InputPin, OutputPin};
+
+use crate::storage::checksum;
 
 #[repr(u8)]
 #[allow(dead_code)]
@@ -22,8 +25,53 @@ enum Move {
     Cursor = 0x00,  // LCD_CURSORMOVE
 }
 
+/// Mode and power flags for the WS0010 (sent with Command::CursorShift)
+#[repr(u8)]
+enum Ws0010Mode {
+    Character = 0x07, // internal power on, character mode
+    Graphic = 0x0F,   // internal power on, graphic mode
+}
+
+/// Extended instructions of the ST7036 and SPLC792
+#[repr(u8)]
+enum St7036 {
+    DoubleHeight = 0x04, // DH
+    Table1 = 0x01,       // IS1, selects instruction table 1
+    Table2 = 0x02,       // IS2, selects instruction table 2
+    Upper = 0x08,        // UD, double height position select
+    IconAddr = 0x40,     // set ICON RAM address (table 1)
+    PowerIcon = 0x50,    // power, icon and contrast high bits (table 1)
+    Contrast = 0x70,     // contrast low bits (table 1)
+}
+
+/// Extended instructions of the KS0073
+#[repr(u8)]
+enum Ks0073 {
+    Extended = 0x04,    // RE, selects the extended instructions
+    FunctionSet = 0x08, // extended function set
+    FourLines = 0x01,   // NW, four-line mode
+}
+
+/// DDRAM address at the start of each row in the native four-line mode of the KS0073
+const KS0073_OFFSETS: [u8; 4] = [0x00, 0x20, 0x40, 0x60];
+
+/// Flags of the power, icon and contrast instruction
+#[repr(u8)]
+enum PowerIcon {
+    IconOn = 0x08,  // Ion
+    Booster = 0x04, // Bon
+}
+
+/// Number of ICON RAM addresses
+const ICON_ADDRESSES: u8 = 16;
+
+/// Contrast sent with the icon setting if none was set (out of 63)
+const DEFAULT_CONTRAST: u8 = 32;
+
 /// Flag that controls text direction
 #[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Layout {
     /// Text runs from right to left
     RightToLeft = 0x00, // LCD_ENTRYRIGHT
@@ -34,6 +82,8 @@ pub enum Layout {
 
 /// Flag that sets the display to autoscroll
 #[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AutoScroll {
     /// Turn AutoScroll on
     On = 0x01, // LCD_ENTRYSHIFTINCREMENT
@@ -44,6 +94,8 @@ pub enum AutoScroll {
 
 /// Flag that sets the display on/off
 #[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Display {
     /// Turn Display on (default)
     On = 0x04, // LCD_DISPLAYON
@@ -54,6 +106,8 @@ pub enum Display {
 
 /// Flag that sets the cursor on/off
 #[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Cursor {
     /// Turn Cursor on
     On = 0x02, // LCD_CURSORON
@@ -64,6 +118,8 @@ pub enum Cursor {
 
 /// Flag that sets cursor background to blink
 #[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Blink {
     /// Turn Blink on
     On = 0x01, // LCD_BLINKON
@@ -72,7 +128,27 @@ pub enum Blink {
     Off = 0x00, // LCD_BLINKOFF
 }
 
+/// Flag that sets the cursor and blink state together
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CursorStyle {
+    /// No cursor is shown (default)
+    Hidden = 0x00, // LCD_CURSOROFF | LCD_BLINKOFF
+
+    /// An underline is shown under the cursor position
+    Underline = 0x02, // LCD_CURSORON | LCD_BLINKOFF
+
+    /// The whole character cell at the cursor position blinks
+    BlinkingBlock = 0x01, // LCD_CURSOROFF | LCD_BLINKON
+
+    /// An underline is shown and the character cell blinks
+    UnderlineAndBlinkingBlock = 0x03, // LCD_CURSORON | LCD_BLINKON
+}
+
 /// Flag that sets backlight state
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Backlight {
     /// Turn Backlight on (default)
     On,
@@ -83,6 +159,8 @@ pub enum Backlight {
 
 /// Flag used to indicate direction for display scrolling
 #[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Scroll {
     /// Scroll display right
     Right = 0x04, // LCD_MOVERIGHT
@@ -93,6 +171,8 @@ pub enum Scroll {
 
 /// Flag for the bus mode of the display
 #[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Mode {
     /// Use eight-bit bus (Set by [with_full_bus][LcdDisplay::with_full_bus])
     EightBits = 0x10, // LCD_8BITMODE
@@ -103,6 +183,8 @@ pub enum Mode {
 
 /// Flag for the number of lines in the display
 #[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Lines {
     /// Use four lines if available
     ///
@@ -124,6 +206,8 @@ pub enum Lines {
 
 /// Flag for the character size of the display
 #[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Size {
     /// Use display with 5x10 characters
     Dots5x10 = 0x04, // LCD_5x10DOTS
@@ -132,6 +216,469 @@ pub enum Size {
     Dots5x8 = 0x00, // LCD_5x8DOTS
 }
 
+/// Flag for which rows use a double-height font (see [set_double_height][LcdDisplay::set_double_height])
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DoubleHeight {
+    /// Every row uses the normal font (default)
+    Off,
+
+    /// The top two rows are merged into one double-height row
+    Top,
+
+    /// The bottom two rows are merged into one double-height row
+    Bottom,
+}
+
+/// Flag that selects a set of delays used when talking to the display
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Timing {
+    /// Close to the datasheet minimums, for genuine HD44780U controllers at 5V
+    Fast,
+
+    /// Generous delays that work with most displays (default)
+    Standard,
+
+    /// Long delays and enable pulses for cheap clones, 3.3V operation and long wires
+    Conservative,
+}
+
+/// Flag that selects workarounds for the controller chip on the display
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Controller {
+    /// Hitachi HD44780 or a faithful compatible (default)
+    Hd44780,
+
+    /// Sunplus SPLC780D, which needs a repeated function set and a longer clear
+    Splc780d,
+
+    /// Samsung KS0066 (and KS0066U), which needs a longer power-on wait, a
+    /// repeated function set and a longer clear
+    Ks0066,
+
+    /// Winstar WS0010 OLED controller, which has a slow clear and supports a graphic
+    /// mode (see [graphic_mode][LcdDisplay::graphic_mode])
+    Ws0010,
+
+    /// Sitronix ST7036, used on COG modules like the DOGM series, which supports
+    /// double-height rows (see [set_double_height][LcdDisplay::set_double_height]) and
+    /// icons (see [set_icon][LcdDisplay::set_icon])
+    St7036,
+
+    /// Sunplus SPLC792A (and ST7032 compatible controllers), which supports icons
+    /// (see [set_icon][LcdDisplay::set_icon])
+    Splc792,
+
+    /// Samsung KS0073, which has a native four-line mode that is used instead of
+    /// splitting two lines in half when [Lines::FourLines] is set
+    Ks0073,
+}
+
+impl Controller {
+    /// Number of times the function set is repeated after initialization
+    fn extra_function_sets(self) -> u8 {
+        match self {
+            Controller::Hd44780
+            | Controller::Ws0010
+            | Controller::St7036
+            | Controller::Splc792
+            | Controller::Ks0073 => 0,
+            Controller::Splc780d | Controller::Ks0066 => 1,
+        }
+    }
+
+    /// Minimum delay after clear and home, in microseconds
+    fn clear_delay(self) -> u32 {
+        match self {
+            Controller::Hd44780 | Controller::St7036 | Controller::Splc792 | Controller::Ks0073 => {
+                0
+            }
+            Controller::Splc780d => 5000,
+            Controller::Ks0066 => 4000,
+            Controller::Ws0010 => 6500,
+        }
+    }
+
+    /// Delay before initialization to let the controller power up, in microseconds
+    fn power_on_delay(self) -> u32 {
+        match self {
+            Controller::Hd44780
+            | Controller::Splc780d
+            | Controller::Ws0010
+            | Controller::St7036
+            | Controller::Splc792
+            | Controller::Ks0073 => 50000,
+            Controller::Ks0066 => 100000,
+        }
+    }
+
+    /// Whether the controller has ICON RAM
+    fn has_icons(self) -> bool {
+        matches!(self, Controller::St7036 | Controller::Splc792)
+    }
+
+    /// Get the instruction table a function set command selects, where 0 is the
+    /// standard HD44780 table. Only commands from table 0 can be read as HD44780
+    /// instructions, since the others reuse the same codes.
+    fn instruction_table(self, function: u8) -> u8 {
+        match self {
+            Controller::St7036 | Controller::Splc792 => function & 0x03,
+            Controller::Ks0073 => function & Ks0073::Extended as u8,
+            _ => 0,
+        }
+    }
+
+    /// Whether the function set bit that marks four-line mode selects an extended
+    /// setting on the controller, so it has to be left out
+    fn extends_function_set(self) -> bool {
+        matches!(
+            self,
+            Controller::St7036 | Controller::Splc792 | Controller::Ks0073
+        )
+    }
+}
+
+/// Flag that controls how text wraps at the end of a row
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Wrap {
+    /// Don't wrap, text runs on into off-screen memory (default)
+    Off,
+
+    /// Wrap to the next row at the last column, breaking words if needed
+    Char,
+
+    /// Wrap to the next row at the last space before the last column
+    Word,
+}
+
+/// A byte sent to the display, passed to the trace hook set by
+/// [with_trace][LcdDisplay::with_trace]
+#[cfg(feature = "trace")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Transfer {
+    /// An instruction byte (RS low)
+    Command(u8),
+
+    /// A data byte written to DDRAM or CGRAM (RS high)
+    Data(u8),
+}
+
+/// What switches the supply of the display (see [with_power_pin][LcdDisplay::with_power_pin])
+enum Power<T> {
+    /// A pin of the same type as the bus pins
+    Pin(T),
+    /// A function that switches the supply on (true) or off, and reports success
+    Switch(fn(bool) -> bool),
+}
+
+/// Backlight blinks scheduled by [notify][LcdDisplay::notify]
+#[derive(Clone, Copy)]
+struct Notification {
+    /// Backlight changes left, two for each blink
+    toggles: u16,
+    /// Time between backlight changes, half of the blink period
+    half: u32,
+    /// When the backlight last changed, or None before the first tick
+    since: Option<u32>,
+    /// The backlight state to return to
+    restore: bool,
+}
+
+/// Timestamps kept by the timing audit (see [with_timing_audit][LcdDisplay::with_timing_audit])
+#[cfg(feature = "timing-audit")]
+#[derive(Clone, Copy)]
+struct Audit {
+    /// Free-running clock in nanoseconds
+    clock: fn() -> u32,
+    /// When the enable pin last went high
+    rose: u32,
+    /// When the last instruction was sent
+    sent: u32,
+    /// How long the last instruction takes to execute
+    execute: u32,
+}
+
+/// Minimum width of the enable pulse, in nanoseconds (PWEH)
+#[cfg(feature = "timing-audit")]
+const AUDIT_PULSE: u32 = 450;
+
+/// Execution time of clear and home, in nanoseconds
+#[cfg(feature = "timing-audit")]
+const AUDIT_CLEAR: u32 = 1_520_000;
+
+/// Execution time of other instructions and data writes, in nanoseconds
+#[cfg(feature = "timing-audit")]
+const AUDIT_EXECUTE: u32 = 37_000;
+
+/// Plain-data configuration for an [LcdDisplay]
+///
+/// This holds the same settings as the builder functions, so a configuration can be
+/// computed, stored or loaded at runtime and applied with [from_config][LcdDisplay::from_config]
+/// or [with_config][LcdDisplay::with_config]. The default matches the defaults of [LcdDisplay::new].
+///
+/// With the `serde` feature enabled, the configuration (along with [Settings] and the
+/// setting flags) can be serialized with any serde format, like
+/// [postcard](https://crates.io/crates/postcard), to persist it in flash or EEPROM.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LcdConfig {
+    /// Bus mode, which must match the data pins given to the display
+    pub mode: Mode,
+
+    /// Number of lines
+    pub lines: Lines,
+
+    /// Character size
+    pub size: Size,
+
+    /// Display on or off
+    pub display: Display,
+
+    /// Cursor on or off
+    pub cursor: Cursor,
+
+    /// Cursor blink on or off
+    pub blink: Blink,
+
+    /// Text direction
+    pub layout: Layout,
+
+    /// Autoscroll on or off
+    pub autoscroll: AutoScroll,
+
+    /// Number of columns
+    pub cols: u8,
+}
+
+impl Default for LcdConfig {
+    fn default() -> Self {
+        Self {
+            mode: Mode::FourBits,
+            lines: Lines::OneLine,
+            size: Size::Dots5x8,
+            display: Display::On,
+            cursor: Cursor::Off,
+            blink: Blink::Off,
+            layout: Layout::LeftToRight,
+            autoscroll: AutoScroll::Off,
+            cols: DEFAULT_COLS,
+        }
+    }
+}
+
+/// The function set state, returned by [function_set][LcdDisplay::function_set]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FunctionSet {
+    /// Bus mode
+    pub bus: Mode,
+
+    /// Number of lines
+    pub lines: Lines,
+
+    /// Character size
+    pub font: Size,
+}
+
+/// The display control state, returned by [display_control][LcdDisplay::display_control]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DisplayControl {
+    /// Display on or off
+    pub display: Display,
+
+    /// Cursor on or off
+    pub cursor: Cursor,
+
+    /// Cursor blink on or off
+    pub blink: Blink,
+}
+
+/// The entry mode state, returned by [entry_mode][LcdDisplay::entry_mode]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EntryMode {
+    /// Text direction
+    pub layout: Layout,
+
+    /// Autoscroll on or off
+    pub autoscroll: AutoScroll,
+}
+
+/// Snapshot of the display settings, taken by [settings][LcdDisplay::settings] and
+/// re-applied by [restore][LcdDisplay::restore]
+///
+/// Settings can be converted to and from bytes with [to_bytes][Settings::to_bytes] and
+/// [from_bytes][Settings::from_bytes] so they can be persisted (for example to EEPROM)
+/// across resets. Bits that don't belong to an instruction are dropped when settings
+/// are restored, so corrupted settings can't send a different instruction.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Settings {
+    display_func: u8,
+    display_ctrl: u8,
+    display_mode: u8,
+    backlight: bool,
+}
+
+impl Settings {
+    /// Build settings from the structured state, for example to describe a UI mode that
+    /// is switched to with [apply][LcdDisplay::apply]. The bus mode is ignored when the
+    /// settings are applied, and like [set_size][LcdDisplay::set_size] the font is only
+    /// used with one line.
+    pub fn new(
+        function: FunctionSet,
+        control: DisplayControl,
+        entry: EntryMode,
+        backlight: Backlight,
+    ) -> Self {
+        // the size bit doubles as the four-line marker (see Lines::FourLines)
+        let mut display_func = function.bus as u8 | function.lines as u8;
+        if function.lines == Lines::OneLine {
+            display_func |= function.font as u8;
+        }
+        Self {
+            display_func,
+            display_ctrl: control.display as u8 | control.cursor as u8 | control.blink as u8,
+            display_mode: entry.layout as u8 | entry.autoscroll as u8,
+            backlight: backlight == Backlight::On,
+        }
+    }
+
+    /// Convert the settings to bytes for storage, with a marker and a checksum
+    pub fn to_bytes(&self) -> [u8; SETTINGS_SIZE] {
+        let mut bytes = [
+            SETTINGS_MAGIC,
+            self.display_func,
+            self.display_ctrl,
+            self.display_mode,
+            self.backlight as u8,
+            0,
+        ];
+        bytes[SETTINGS_SIZE - 1] = checksum(&bytes[..SETTINGS_SIZE - 1]);
+        bytes
+    }
+
+    /// Convert bytes created by [to_bytes][Settings::to_bytes] back into settings, or
+    /// None if they don't hold any (like erased or corrupted memory)
+    pub fn from_bytes(bytes: [u8; SETTINGS_SIZE]) -> Option<Self> {
+        let (body, sum) = bytes.split_at(SETTINGS_SIZE - 1);
+        if body[0] != SETTINGS_MAGIC || checksum(body) != sum[0] {
+            return None;
+        }
+
+        let settings = Self {
+            display_func: body[1],
+            display_ctrl: body[2],
+            display_mode: body[3],
+            backlight: body[4] != 0,
+        };
+        Some(settings.masked())
+    }
+
+    /// Drop the bits that don't belong to the function set, display control and entry
+    /// mode instructions.
+    fn masked(self) -> Self {
+        Self {
+            display_func: self.display_func & FUNCTION_BITS,
+            display_ctrl: self.display_ctrl & CONTROL_BITS,
+            display_mode: self.display_mode & ENTRY_BITS,
+            backlight: self.backlight,
+        }
+    }
+}
+
+/// How often an operation that can fail on a marginal connection is attempted, set with
+/// [with_retry_policy][LcdDisplay::with_retry_policy]
+///
+/// The policy is used when a pin or the bus of an I2C backpack reports an error while a
+/// command or character is sent, by [write_verified][LcdDisplay::write_verified] and
+/// [set_character_verified][LcdDisplay::set_character_verified] when a character doesn't
+/// read back correctly, and by [probe][LcdDisplay::probe] when no display answers.
+/// Waiting between attempts gives a noisy bus or a slow supply time to settle.
+///
+/// A failed transfer is retried as a whole: the four-bit bus is synchronized again and
+/// the address counter is pointed back at where the character belongs, so a glitch
+/// doesn't leave the display out of step. The port writer of a backpack doesn't retry
+/// on its own inside a transfer, so the attempts aren't multiplied.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RetryPolicy {
+    /// Number of times the operation is tried, including the first (0 is treated as 1)
+    pub attempts: u8,
+
+    /// Time to wait between attempts in microseconds
+    pub backoff_us: u32,
+}
+
+impl RetryPolicy {
+    /// Create a policy that tries `attempts` times, waiting `backoff_us` in between.
+    pub const fn new(attempts: u8, backoff_us: u32) -> Self {
+        Self {
+            attempts,
+            backoff_us,
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(1, 0)
+    }
+}
+
+/// Result of [benchmark][LcdDisplay::benchmark]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Benchmark {
+    /// Number of characters written per second
+    pub chars_per_second: u32,
+
+    /// Average time taken by a single command in microseconds, including its delay
+    pub command_us: u32,
+}
+
+/// Counters returned by [stats][LcdDisplay::stats]
+///
+/// The counters wrap around instead of overflowing. This is only available if the
+/// `stats` feature is enabled.
+#[cfg(feature = "stats")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stats {
+    /// Number of instruction bytes sent
+    pub commands: u32,
+
+    /// Number of data bytes written to DDRAM or CGRAM
+    pub data: u32,
+
+    /// Number of times a write or initialization was retried after a failed readback
+    /// or a pin error
+    pub retries: u32,
+
+    /// Number of times an error code was set
+    pub errors: u32,
+}
+
+/// Result of [self_test][LcdDisplay::self_test]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SelfTest {
+    /// The test pattern written to display memory (DDRAM) was read back intact
+    pub ddram: bool,
+
+    /// The test pattern written to character memory (CGRAM) was read back intact
+    pub cgram: bool,
+}
+
+impl SelfTest {
+    /// Check if every memory region passed
+    pub fn passed(&self) -> bool {
+        self.ddram && self.cgram
+    }
+}
+
 /// One of the most popular sizes for this kind of LCD is 16x2
 const DEFAULT_COLS: u8 = 16;
 
@@ -139,8 +686,88 @@ const DEFAULT_DISPLAY_FUNC: u8 = Mode::FourBits as u8 | Lines::OneLine as u8 | S
 const DEFAULT_DISPLAY_CTRL: u8 = Display::On as u8 | Cursor::Off as u8 | Blink::Off as u8;
 const DEFAULT_DISPLAY_MODE: u8 = Layout::LeftToRight as u8 | AutoScroll::Off as u8;
 
-const CMD_DELAY: u32 = 3500;
-const CHR_DELAY: u32 = 450;
+/// Each line of DDRAM is 40 characters long, so the display shift wraps at 40
+const DDRAM_COLS: i16 = 40;
+
+/// Longest word buffered for word wrapping, one more than fits on any row
+const WORD_LEN: usize = DDRAM_COLS as usize + 1;
+
+/// Known pattern written and read back by [LcdDisplay::self_test]
+const TEST_PATTERN: [u8; 8] = [0x55, 0xAA, 0x00, 0xFF, 0x0F, 0xF0, 0x33, 0xCC];
+
+/// Number of bytes in serialized [Settings]
+pub const SETTINGS_SIZE: usize = 6;
+
+/// Marks the start of serialized settings, so erased or unrelated memory isn't loaded
+const SETTINGS_MAGIC: u8 = b'S';
+
+/// Bits of the function set instruction that settings can change (bus, lines, size)
+const FUNCTION_BITS: u8 = 0x1C;
+
+/// Bits of the display control instruction (display, cursor, blink)
+const CONTROL_BITS: u8 = 0x07;
+
+/// Bits of the entry mode instruction (layout, autoscroll)
+const ENTRY_BITS: u8 = 0x03;
+
+/// Interval between reads of the busy flag, in microseconds
+const BUSY_POLL: u32 = 10;
+
+/// DDRAM addresses that [LcdDisplay::probe] expects to read back from the address counter
+const PROBE_ADDRESSES: [u8; 2] = [0x15, 0x4A];
+
+/// Time the display is left without power by [LcdDisplay::power_cycle], in microseconds
+const POWER_OFF_DELAY: u32 = 100_000;
+
+/// Number of characters and commands sent by [LcdDisplay::benchmark]
+const BENCH_CHARS: u32 = 32;
+const BENCH_COMMANDS: u32 = 8;
+
+/// Number of character codes shown on each page by [LcdDisplay::rom_page]
+const ROM_PAGE: u8 = 16;
+
+/// Delays (in microseconds) used when talking to the display
+#[derive(Clone, Copy)]
+struct Delays {
+    /// After most commands
+    command: u32,
+    /// After clear and home, which take much longer than other commands
+    clear: u32,
+    /// After each character is written
+    character: u32,
+    /// Width of the enable pulse
+    pulse: u32,
+    /// After the enable pulse, before the data pins change
+    hold: u32,
+}
+
+impl Delays {
+    const fn from_timing(timing: Timing) -> Self {
+        match timing {
+            Timing::Fast => Delays {
+                command: 50,
+                clear: 2000,
+                character: 50,
+                pulse: 1,
+                hold: 0,
+            },
+            Timing::Standard => Delays {
+                command: 3500,
+                clear: 3500,
+                character: 450,
+                pulse: 0,
+                hold: 0,
+            },
+            Timing::Conservative => Delays {
+                command: 5000,
+                clear: 10000,
+                character: 1000,
+                pulse: 10,
+                hold: 0,
+            },
+        }
+    }
+}
 
 const RS: u8 = 0;
 const EN: u8 = 1;
@@ -155,6 +782,55 @@ const D6: u8 = 9;
 const D7: u8 = 10;
 const A: u8 = 11;
 
+/// Per-display state that has to be swapped out when several displays share a bus
+///
+/// Everything that describes one display (its settings, cursor, memory and controller)
+/// is kept here, and [LcdDisplay] holds the context of the selected display. The pins, delays, retries and hooks belong to the shared bus, and the
+/// backlight state to the shared backlight pin, so they stay with the [LcdDisplay].
+#[derive(Clone)]
+pub(crate) struct Context {
+    display_func: u8,
+    display_mode: u8,
+    display_ctrl: u8,
+    offsets: [u8; 4],
+    font: Size,
+    cols: u8,
+    col: u8,
+    row: u8,
+    wrap: Wrap,
+    shift: i8,
+    sleeping: Option<Settings>,
+    transaction: u8,
+    hidden: u8,
+    screensaver: u32,
+    last_activity: u32,
+    activity: bool,
+    blanked: bool,
+    refresh_interval: u32,
+    last_refresh: u32,
+    cgram: Option<u8>,
+    table: u8,
+    characters: u8,
+    controller: Controller,
+    double_height: DoubleHeight,
+    icons: bool,
+    booster: bool,
+    contrast: Option<u8>,
+    rom: Rom,
+    unmappable: Unmappable,
+    #[cfg(feature = "nb")]
+    pending: Option<u8>,
+    #[cfg(feature = "nb")]
+    ready_at: Option<u32>,
+}
+
+/// Writes the level of every pin (bit `i` for the pin at index `i`) in a single
+/// transfer, for pins that share a port like those on an I2C expander
+pub(crate) type PortWriter<T> = fn(&mut [Option<T>; 12], u16) -> bool;
+
+/// Stores a custom character and checks that it arrived intact
+type CharacterCheck<T, D> = fn(&mut LcdDisplay<T, D>, u8, [u8; 8]) -> bool;
+
 /// The LCD display
 ///
 /// Methods called on this struct will fail silently if the system or screen is
@@ -165,10 +841,36 @@ where
     D: DelayNs + Sized,
 {
     pins: [Option<T>; 12],
-    display_func: u8,
-    display_mode: u8,
-    display_ctrl: u8,
-    offsets: [u8; 4],
+    power: Option<Power<T>>,
+    verify: Option<fn(&mut Self, u8) -> bool>,
+    verify_attempts: u8,
+    busy: Option<fn(&mut Self) -> bool>,
+    busy_timeout: u32,
+    probe: Option<fn(&mut Self) -> bool>,
+    direction: Option<fn(&mut Self, bool)>,
+    character_check: Option<CharacterCheck<T, D>>,
+    keep_position: bool,
+    port: Option<PortWriter<T>>,
+    levels: u16,
+    inverted: u16,
+    data_order: [u8; 4],
+    context: Context,
+    backlight: bool,
+    backlight_timeout: u32,
+    dimmed: bool,
+    notification: Option<Notification>,
+    retry: RetryPolicy,
+    watching: bool,
+    fault: Option<Error>,
+    on_error: Option<fn(Error)>,
+    yielder: YieldHook,
+    #[cfg(feature = "stats")]
+    stats: Stats,
+    #[cfg(feature = "trace")]
+    trace: Option<fn(Transfer)>,
+    #[cfg(feature = "timing-audit")]
+    audit: Option<Audit>,
+    delays: Delays,
     delay: D,
     code: Error,
 }
@@ -218,21 +920,180 @@ where
                 None,
                 None,
             ],
-            display_func: DEFAULT_DISPLAY_FUNC,
-            display_mode: DEFAULT_DISPLAY_MODE,
-            display_ctrl: DEFAULT_DISPLAY_CTRL,
-            offsets: [0x00, 0x40, 0x00 + DEFAULT_COLS, 0x40 + DEFAULT_COLS],
+            power: None,
+            verify: None,
+            verify_attempts: 0,
+            busy: None,
+            busy_timeout: 10_000,
+            probe: None,
+            direction: None,
+            character_check: None,
+            keep_position: true,
+            port: None,
+            levels: 0,
+            inverted: 0,
+            data_order: [0, 1, 2, 3],
+            context: Context {
+                display_func: DEFAULT_DISPLAY_FUNC,
+                display_mode: DEFAULT_DISPLAY_MODE,
+                display_ctrl: DEFAULT_DISPLAY_CTRL,
+                offsets: [0x00, 0x40, 0x00 + DEFAULT_COLS, 0x40 + DEFAULT_COLS],
+                font: Size::Dots5x8,
+                cols: DEFAULT_COLS,
+                col: 0,
+                row: 0,
+                wrap: Wrap::Off,
+                shift: 0,
+                sleeping: None,
+                transaction: 0,
+                hidden: 0,
+                screensaver: 0,
+                last_activity: 0,
+                activity: true,
+                blanked: false,
+                refresh_interval: 0,
+                last_refresh: 0,
+                cgram: None,
+                table: 0,
+                characters: 0,
+                controller: Controller::Hd44780,
+                double_height: DoubleHeight::Off,
+                icons: false,
+                booster: false,
+                contrast: None,
+                rom: Rom::A00,
+                unmappable: Unmappable::Replace(b'?'),
+                #[cfg(feature = "nb")]
+                pending: None,
+                #[cfg(feature = "nb")]
+                ready_at: None,
+            },
+            backlight: true,
+            backlight_timeout: 0,
+            dimmed: false,
+            notification: None,
+            retry: RetryPolicy::default(),
+            watching: false,
+            fault: None,
+            on_error: None,
+            yielder: YieldHook::new(),
+            #[cfg(feature = "stats")]
+            stats: Stats {
+                commands: 0,
+                data: 0,
+                retries: 0,
+                errors: 0,
+            },
+            #[cfg(feature = "trace")]
+            trace: None,
+            #[cfg(feature = "timing-audit")]
+            audit: None,
+            delays: Delays::from_timing(Timing::Standard),
             delay,
             code: Error::None,
         }
     }
 
-    /// Set amount of columns this lcd has
+    /// Create a new instance of the LcdDisplay with settings from an [LcdConfig]. The data
+    /// pins still have to be set with [with_half_bus][LcdDisplay::with_half_bus] or
+    /// [with_full_bus][LcdDisplay::with_full_bus] to match the configured bus mode.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let config = LcdConfig {
+    ///     lines: Lines::TwoLines,
+    ///     cursor: Cursor::On,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::from_config(rs, en, delay, config)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .build();
+    /// ```
+    pub fn from_config(rs: T, en: T, delay: D, config: LcdConfig) -> Self {
+        Self::new(rs, en, delay).with_config(config)
+    }
+
+    /// Apply every setting in an [LcdConfig]. (See [from_config][LcdDisplay::from_config])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_config(config)
+    ///     .build();
+    /// ```
+    pub fn with_config(mut self, config: LcdConfig) -> Self {
+        match config.mode {
+            Mode::EightBits => self.context.display_func |= Mode::EightBits as u8,
+            Mode::FourBits => self.context.display_func &= !(Mode::EightBits as u8),
+        }
+
+        // clear the lines bits first, because with_lines only ever adds them
+        self.context.display_func &= !(Lines::FourLines as u8);
+
+        self.with_lines(config.lines)
+            .with_size(config.size)
+            .with_display(config.display)
+            .with_cursor(config.cursor)
+            .with_blink(config.blink)
+            .with_layout(config.layout)
+            .with_autoscroll(config.autoscroll)
+            .with_cols(config.cols)
+    }
+
+    /// Set amount of columns this lcd has. (Default is 16)
+    ///
+    /// Each line of DDRAM holds 40 characters, so values are clamped to 1-40. Four-line
+    /// displays split each DDRAM line into two rows and can only have up to 20 columns,
+    /// which is checked by [build][LcdDisplay::build] (see [Error::InvalidCols]).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_lines(Lines::TwoLines)
+    ///     .with_cols(40)
+    ///     .build();
+    /// ```
     pub fn with_cols(mut self, mut cols: u8) -> Self {
-        cols = cols.clamp(0, 31);
+        cols = cols.clamp(1, DDRAM_COLS as u8);
         // First two bytes skipped because they are always the same
-        self.offsets[2] = 0x00 + cols;
-        self.offsets[3] = 0x40 + cols;
+        self.context.offsets[2] = 0x00 + cols;
+        self.context.offsets[3] = 0x40 + cols;
+        self.context.cols = cols;
+        self
+    }
+
+    /// Set the DDRAM address at the start of each row, for modules whose rows aren't
+    /// laid out like the standard ones. (Default follows [with_cols][LcdDisplay::with_cols])
+    ///
+    /// The default layout already covers the common sizes: 16x4 modules use 0x00, 0x40,
+    /// 0x10 and 0x50 and 20x4 modules use 0x00, 0x40, 0x14 and 0x54, which is what
+    /// `with_cols(16)` and `with_cols(20)` produce. Call this after `with_cols`, which
+    /// resets the offsets of the last two rows. A [Controller::Ks0073] in four-line mode
+    /// always uses its own layout (0x00, 0x20, 0x40 and 0x60).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_lines(Lines::FourLines)
+    ///     .with_cols(16)
+    ///     .with_row_offsets([0x00, 0x40, 0x10, 0x50])
+    ///     .build();
+    /// ```
+    pub fn with_row_offsets(mut self, offsets: [u8; 4]) -> Self {
+        for (offset, value) in self.context.offsets.iter_mut().zip(offsets) {
+            *offset = value & 0x7F;
+        }
         self
     }
 
@@ -252,7 +1113,7 @@ where
     /// ```
     pub fn with_half_bus(mut self, d4: T, d5: T, d6: T, d7: T) -> Self {
         // set to four-bit bus mode and assign pins
-        self.display_func &= !(Mode::EightBits as u8);
+        self.context.display_func &= !(Mode::EightBits as u8);
         self.pins[D4 as usize] = Some(d4);
         self.pins[D5 as usize] = Some(d5);
         self.pins[D6 as usize] = Some(d6);
@@ -277,7 +1138,7 @@ where
     #[allow(clippy::too_many_arguments)]
     pub fn with_full_bus(mut self, d0: T, d1: T, d2: T, d3: T, d4: T, d5: T, d6: T, d7: T) -> Self {
         // set to eight-bit bus mode and assign pins
-        self.display_func |= Mode::EightBits as u8;
+        self.context.display_func |= Mode::EightBits as u8;
         self.pins[D0 as usize] = Some(d0);
         self.pins[D1 as usize] = Some(d1);
         self.pins[D2 as usize] = Some(d2);
@@ -318,9 +1179,11 @@ where
     ///     .build();
     /// ```
     pub fn with_size(mut self, value: Size) -> Self {
-        match value {
-            Size::Dots5x10 => self.display_func |= Size::Dots5x10 as u8,
-            Size::Dots5x8 => self.display_func &= !(Size::Dots5x10 as u8),
+        self.context.font = value;
+        // with more lines the bit marks four-line mode instead
+        if self.lines() == Lines::OneLine {
+            self.context.display_func &= !(Size::Dots5x10 as u8);
+            self.context.display_func |= value as u8;
         }
         self
     }
@@ -337,10 +1200,11 @@ where
     ///     .build();
     /// ```
     pub fn with_lines(mut self, value: Lines) -> Self {
+        self.context.display_func &= !(Lines::FourLines as u8);
         match value {
-            Lines::FourLines => self.display_func |= Lines::FourLines as u8,
-            Lines::TwoLines => self.display_func |= Lines::TwoLines as u8,
-            Lines::OneLine => self.display_func &= !(Lines::TwoLines as u8),
+            Lines::FourLines => self.context.display_func |= Lines::FourLines as u8,
+            Lines::TwoLines => self.context.display_func |= Lines::TwoLines as u8,
+            Lines::OneLine => self.context.display_func |= self.context.font as u8,
         }
         self
     }
@@ -358,8 +1222,8 @@ where
     /// ```
     pub fn with_layout(mut self, value: Layout) -> Self {
         match value {
-            Layout::LeftToRight => self.display_mode |= Layout::LeftToRight as u8,
-            Layout::RightToLeft => self.display_mode &= !(Layout::LeftToRight as u8),
+            Layout::LeftToRight => self.context.display_mode |= Layout::LeftToRight as u8,
+            Layout::RightToLeft => self.context.display_mode &= !(Layout::LeftToRight as u8),
         }
         self
     }
@@ -377,8 +1241,8 @@ where
     /// ```
     pub fn with_display(mut self, value: Display) -> Self {
         match value {
-            Display::On => self.display_ctrl |= Display::On as u8,
-            Display::Off => self.display_ctrl &= !(Display::On as u8),
+            Display::On => self.context.display_ctrl |= Display::On as u8,
+            Display::Off => self.context.display_ctrl &= !(Display::On as u8),
         }
         self
     }
@@ -396,8 +1260,8 @@ where
     /// ```
     pub fn with_cursor(mut self, value: Cursor) -> Self {
         match value {
-            Cursor::On => self.display_ctrl |= Cursor::On as u8,
-            Cursor::Off => self.display_ctrl &= !(Cursor::On as u8),
+            Cursor::On => self.context.display_ctrl |= Cursor::On as u8,
+            Cursor::Off => self.context.display_ctrl &= !(Cursor::On as u8),
         }
         self
     }
@@ -415,19 +1279,43 @@ where
     /// ```
     pub fn with_blink(mut self, value: Blink) -> Self {
         match value {
-            Blink::On => self.display_ctrl |= Blink::On as u8,
-            Blink::Off => self.display_ctrl &= !(Blink::On as u8),
+            Blink::On => self.context.display_ctrl |= Blink::On as u8,
+            Blink::Off => self.context.display_ctrl &= !(Blink::On as u8),
         }
         self
     }
 
+    /// Set the cursor and blink state together. (Default is CursorStyle::Hidden)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_cursor_style(CursorStyle::Underline)
+    ///     .build();
+    /// ```
+    pub fn with_cursor_style(mut self, value: CursorStyle) -> Self {
+        self.context.display_ctrl &= !(CursorStyle::UnderlineAndBlinkingBlock as u8);
+        self.context.display_ctrl |= value as u8;
+        self
+    }
+
     /// Set a pin for controlling backlight state
     pub fn with_backlight(mut self, backlight_pin: T) -> Self {
         self.pins[A as usize] = Some(backlight_pin);
         self
     }
 
-    /// Set autoscroll on or off. (Default is AutoScroll::Off)
+    /// Set a pin that switches the power supply (VCC) of the display through a transistor,
+    /// high for on. This makes a hard reset possible with [power_cycle][LcdDisplay::power_cycle],
+    /// which is the only thing that recovers some wedged clone controllers, and
+    /// [reliable_init_verified][LcdDisplay::reliable_init_verified] uses it between attempts.
+    /// If the pin reports an error the error code is set to [Error::PowerFailed].
+    ///
+    /// The pin has to be the same type as the bus pins. For a pin of another type, or a
+    /// supply switched some other way, use [with_power_switch][LcdDisplay::with_power_switch].
     ///
     /// # Examples
     ///
@@ -435,801 +1323,4590 @@ where
     /// ...
     /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
     ///     .with_half_bus(d4, d5, d6, d7)
-    ///     .with_autoscroll(AutoScroll::Off)
+    ///     .with_power_pin(power)
     ///     .build();
     /// ```
-    pub fn with_autoscroll(mut self, value: AutoScroll) -> Self {
-        match value {
-            AutoScroll::On => self.display_mode |= AutoScroll::On as u8,
-            AutoScroll::Off => self.display_mode &= !(AutoScroll::On as u8),
-        }
+    pub fn with_power_pin(mut self, power_pin: T) -> Self {
+        self.power = Some(Power::Pin(power_pin));
         self
     }
 
-    /// Increase reliability of initialization of LCD.
-    ///
-    /// Some users experience unreliable initialization of the LCD, where
-    /// the LCD sometimes is unable to display symbols after running
-    /// `.build()`. This method toggles the LCD off and on with some
-    /// delay in between, 3 times. A higher `delay_toggle` tends to make
-    /// this method more reliable, and a value of `10 000` is recommended.
-    /// Note that this method should be run as close as possible to
-    /// `.build()`.
+    /// Set a function that switches the power supply of the display, called with true to
+    /// turn it on and false to turn it off. It returns false if switching failed, which
+    /// sets the error code to [Error::PowerFailed]. This works like
+    /// [with_power_pin][LcdDisplay::with_power_pin], for supplies switched by a pin of a
+    /// different type, a port expander or a power management chip.
     ///
     /// # Examples
     ///
     /// ```
+    /// static POWER: Mutex<RefCell<Option<PowerPin>>> = Mutex::new(RefCell::new(None));
+    ///
+    /// fn switch_power(on: bool) -> bool {
+    ///     critical_section::with(|cs| match POWER.borrow_ref_mut(cs).as_mut() {
+    ///         Some(pin) => pin.set_state(on.into()).is_ok(),
+    ///         None => false,
+    ///     })
+    /// }
+    ///
     /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
     ///     .with_half_bus(d4, d5, d6, d7)
-    ///     .with_reliable_init(10000)
+    ///     .with_power_switch(switch_power)
     ///     .build();
     /// ```
-    pub fn with_reliable_init(mut self, delay_toggle: u32) -> Self {
-        if self.display_ctrl == Display::On as u8 {
-            for _ in 0..3 {
-                self.delay.delay_us(delay_toggle);
-                self.display_off();
-                self.delay.delay_us(delay_toggle);
-                self.display_on();
-            }
-        } else {
-            for _ in 0..3 {
-                self.delay.delay_us(delay_toggle);
-                self.display_on();
-                self.delay.delay_us(delay_toggle);
-                self.display_off();
-            }
-        }
-
+    pub fn with_power_switch(mut self, switch: fn(bool) -> bool) -> Self {
+        self.power = Some(Power::Switch(switch));
         self
     }
 
-    /// Finish construction of the LcdDisplay and initialized the
-    /// display to the provided settings.
+    /// Set how long to wait for the busy flag to clear before giving up, in microseconds.
+    /// When the flag doesn't clear in time (for example because the display was
+    /// disconnected), the error code is set to [Error::Timeout] and the display goes
+    /// back to fixed delays. Only used with [with_busy_flag][LcdDisplay::with_busy_flag].
+    /// (Default is 10000)
     ///
     /// # Examples
     ///
     /// ```
-    /// use ag_lcd::{Display, Blink, Cursor, LcdDisplay};
-    ///
-    /// let peripherals = arduino_hal::Peripherals::take().unwrap();
-    /// let pins = arduino_hal::pins!(peripherals);
-    /// let delay = arduino_hal::Delay::new();
-    ///
-    /// let rs = pins.d12.into_output().downgrade();
-    /// let rw = pins.d11.into_output().downgrade();
-    /// let en = pins.d10.into_output().downgrade();
-    ///
-    /// // left-side names refer to lcd pinout (e.g. 'd4' = D4 on lcd)
-    /// let d4 = pins.d5.into_output().downgrade();
-    /// let d5 = pins.d4.into_output().downgrade();
-    /// let d6 = pins.d3.into_output().downgrade();
-    /// let d7 = pins.d2.into_output().downgrade();
-    ///
+    /// ...
     /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
     ///     .with_half_bus(d4, d5, d6, d7)
-    ///     .with_display(Display::On)
-    ///     .with_blink(Blink::On)
-    ///     .with_cursor(Cursor::On)
-    ///     .with_rw(rw) // optional (set lcd pin to GND if not provided)
+    ///     .with_rw(rw)
+    ///     .with_busy_flag()
+    ///     .with_busy_timeout(5000)
     ///     .build();
-    ///
-    /// lcd.print("Test message!");
     /// ```
-    pub fn build(mut self) -> Self {
-        self.delay.delay_us(50000);
-
-        self.set(RS, false);
-        self.set(EN, false);
-
-        if self.exists(RW) {
-            self.set(RW, false);
-        }
-
-        match self.mode() {
-            Mode::FourBits => {
-                // display function is four bit
-                self.update(0x03);
-                self.delay.delay_us(4500);
-
-                self.update(0x03);
-                self.delay.delay_us(4500);
-
-                self.update(0x03);
-                self.delay.delay_us(150);
-
-                self.update(0x02);
-            }
-            Mode::EightBits => {
-                // display function is eight bit
-                self.command(Command::SetDisplayFunc as u8 | self.display_func);
-                self.delay.delay_us(4500);
-
-                self.command(Command::SetDisplayFunc as u8 | self.display_func);
-                self.delay.delay_us(150);
-
-                self.command(Command::SetDisplayFunc as u8 | self.display_func);
-            }
-        }
-
-        self.command(Command::SetDisplayFunc as u8 | self.display_func);
-        self.delay.delay_us(CMD_DELAY);
-
-        self.command(Command::SetDisplayCtrl as u8 | self.display_ctrl);
-        self.delay.delay_us(CMD_DELAY);
-
-        self.command(Command::SetDisplayMode as u8 | self.display_mode);
-        self.delay.delay_us(CMD_DELAY);
-
-        self.clear();
-        self.home();
-
-        // set an error code display is misconfigured
-        self.validate();
+    pub fn with_busy_timeout<M: Microseconds>(mut self, timeout: M) -> Self {
+        self.busy_timeout = timeout.to_micros();
         self
     }
 
-    /// Set the position of the cursor.
+    /// Invert the enable pin, for adapter boards that drive EN through an inverting
+    /// transistor. (Default is not inverted)
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut lcd: LcdDisplay<_,_> = ...;
-    ///
-    /// let row = 0;
-    /// let col = 2;
-    ///
-    /// lcd.set_position(col,row);
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_inverted_en()
+    ///     .build();
     /// ```
-    pub fn set_position(&mut self, col: u8, mut row: u8) {
-        let max_lines = 4;
-
-        let num_lines = match self.lines() {
-            Lines::FourLines => 4,
-            Lines::TwoLines => 2,
-            Lines::OneLine => 1,
-        };
-
-        let mut pos = col;
-
-        if row >= max_lines {
-            row = max_lines.saturating_sub(1);
-        }
-
-        if row >= num_lines {
-            row = num_lines.saturating_sub(1);
-        }
-
-        pos += self.offsets[row as usize];
-        self.command(Command::SetDDRAMAddr as u8 | pos);
-        self.delay.delay_us(CMD_DELAY);
+    pub fn with_inverted_en(mut self) -> Self {
+        self.inverted |= 1 << EN;
+        self
     }
 
-    /// Scroll the display right or left.
+    /// Invert the register select pin, for adapter boards that drive RS through an
+    /// inverting transistor. (Default is not inverted)
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut lcd: LcdDisplay<_,_> = ...;
-    ///
-    /// let direction = Scroll::Left;
-    /// let distance = 2;
-    ///
-    /// lcd.set_scroll(direction,distance);
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_inverted_rs()
+    ///     .build();
     /// ```
-    pub fn set_scroll(&mut self, direction: Scroll, distance: u8) {
-        let command = Command::CursorShift as u8 | Move::Display as u8 | direction as u8;
-        for _ in 0..distance {
-            self.command(command);
-            self.delay.delay_us(CMD_DELAY);
-        }
+    pub fn with_inverted_rs(mut self) -> Self {
+        self.inverted |= 1 << RS;
+        self
     }
 
-    /// Set the text direction layout.
+    /// Set which bit of each nibble is sent on each data line, for adapters and boards
+    /// that were routed with the data lines swapped. `order[n]` is the bit that goes
+    /// out on D4+n (and on D0+n in eight-bit mode), so `[3, 2, 1, 0]` reverses the
+    /// nibble. Reads are mapped back the same way. If `order` isn't a permutation of
+    /// 0-3 the error code is set to [Error::InvalidMode] and the order is not changed.
+    /// (Default is `[0, 1, 2, 3]`)
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut lcd: LcdDisplay<_,_> = ...;
-    ///
-    /// lcd.set_layout(Layout::LeftToRight);
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new_pcf8574(&mut i2c_expander, delay)
+    ///     .with_data_order([3, 2, 1, 0])
+    ///     .build();
     /// ```
-    pub fn set_layout(&mut self, layout: Layout) {
-        match layout {
-            Layout::LeftToRight => self.display_mode |= Layout::LeftToRight as u8,
-            Layout::RightToLeft => self.display_mode &= !(Layout::LeftToRight as u8),
+    pub fn with_data_order(mut self, order: [u8; 4]) -> Self {
+        match (0..4).all(|bit| order.contains(&bit)) {
+            true => self.data_order = order,
+            false => self.fail(Error::InvalidMode),
         }
-        self.command(Command::SetDisplayMode as u8 | self.display_mode);
-        self.delay.delay_us(CMD_DELAY);
+        self
     }
 
-    /// Turn the display on or off.
+    /// Write all pins at once with `port` instead of one at a time. Pin changes are
+    /// collected and only written when the enable pin changes, with the backlight pin
+    /// always set to the cached backlight state.
+    #[cfg_attr(not(feature = "i2c"), allow(dead_code))]
+    pub(crate) fn with_port(mut self, port: PortWriter<T>) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Set autoscroll on or off. (Default is AutoScroll::Off)
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut lcd: LcdDisplay<_,_> = ...;
-    ///
-    /// lcd.set_display(Display::Off);
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_autoscroll(AutoScroll::Off)
+    ///     .build();
     /// ```
-    pub fn set_display(&mut self, display: Display) {
-        match display {
-            Display::On => self.display_ctrl |= Display::On as u8,
-            Display::Off => self.display_ctrl &= !(Display::On as u8),
+    pub fn with_autoscroll(mut self, value: AutoScroll) -> Self {
+        match value {
+            AutoScroll::On => self.context.display_mode |= AutoScroll::On as u8,
+            AutoScroll::Off => self.context.display_mode &= !(AutoScroll::On as u8),
         }
-        self.command(Command::SetDisplayCtrl as u8 | self.display_ctrl);
-        self.delay.delay_us(CMD_DELAY);
+        self
     }
 
-    /// Turn the cursor on or off.
+    /// Set how text wraps when it reaches the end of a row. (Default is Wrap::Off)
+    ///
+    /// Wrapping only applies to [Layout::LeftToRight] text written through
+    /// [print][LcdDisplay::print] and [write][LcdDisplay::write].
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut lcd: LcdDisplay<_,_> = ...;
-    ///
-    /// lcd.set_cursor(Cursor::On);
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_lines(Lines::FourLines)
+    ///     .with_cols(20)
+    ///     .with_wrap(Wrap::Word)
+    ///     .build();
     /// ```
-    pub fn set_cursor(&mut self, cursor: Cursor) {
-        match cursor {
-            Cursor::On => self.display_ctrl |= Cursor::On as u8,
-            Cursor::Off => self.display_ctrl &= !(Cursor::On as u8),
-        }
-        self.command(Command::SetDisplayCtrl as u8 | self.display_ctrl);
-        self.delay.delay_us(CMD_DELAY);
+    pub fn with_wrap(mut self, value: Wrap) -> Self {
+        self.context.wrap = value;
+        self
     }
 
-    /// Make the background of the cursor blink or stop blinking.
+    /// Point the address counter back at the cursor position after a custom character
+    /// is stored. (Default is true)
+    ///
+    /// Storing a character leaves the address counter in character memory, so without
+    /// this the next [print][LcdDisplay::print] would overwrite the glyphs instead of
+    /// showing text. Turning it off saves one instruction per character for code that
+    /// always sets the position afterwards.
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut lcd: LcdDisplay<_,_> = ...;
-    ///
-    /// lcd.set_blink(Blink::On);
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_keep_position(false)
+    ///     .build();
     /// ```
-    pub fn set_blink(&mut self, blink: Blink) {
-        match blink {
-            Blink::On => self.display_ctrl |= Blink::On as u8,
-            Blink::Off => self.display_ctrl &= !(Blink::On as u8),
-        }
-        self.command(Command::SetDisplayCtrl as u8 | self.display_ctrl);
-        self.delay.delay_us(CMD_DELAY);
-    }
-
-    /// Enable or disable LCD backlight
-    pub fn set_backlight(&mut self, backlight: Backlight) {
-        match backlight {
-            Backlight::On => self.backlight_on(),
-            Backlight::Off => self.backlight_off(),
-        }
+    pub fn with_keep_position(mut self, value: bool) -> Self {
+        self.keep_position = value;
+        self
     }
 
-    /// Turn auto scroll on or off.
+    /// Set the delays used when talking to the display from a named profile. (Default is Timing::Standard)
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut lcd: LcdDisplay<_,_> = ...;
-    ///
-    /// lcd.set_autoscroll(AutoScroll::On);
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_timing(Timing::Conservative)
+    ///     .build();
     /// ```
-    pub fn set_autoscroll(&mut self, scroll: AutoScroll) {
-        match scroll {
-            AutoScroll::On => self.display_mode |= AutoScroll::On as u8,
-            AutoScroll::Off => self.display_mode &= !(AutoScroll::On as u8),
-        }
-        self.command(Command::SetDisplayMode as u8 | self.display_mode);
-        self.delay.delay_us(CMD_DELAY);
+    pub fn with_timing(mut self, value: Timing) -> Self {
+        self.delays = Delays::from_timing(value);
+        self
     }
 
-    /// Add a new character map to the LCD memory (CGRAM) at a particular location.
-    /// There are eight locations available at positions 0-7, and location values
-    /// outside of this range will be bitwise masked to fall within the range, possibly
-    /// overwriting an existing custom character.
+    /// Set the delays used when talking to the display individually, instead of from
+    /// a profile: after most commands, after clear and home, and after each character.
+    /// The enable pulse width and hold time are kept. Plain `u32` values are in
+    /// microseconds; with the `fugit` feature `fugit` durations can be used.
+    /// (See [Microseconds])
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut lcd: LcdDisplay<_,_> = ...;
-    ///
-    /// // set a sideways smiley face in CGRAM at location 0.
-    /// lcd.set_character(0u8,[
-    ///     0b00110,
-    ///     0b00001,
-    ///     0b11001,
-    ///     0b00001,
-    ///     0b00001,
-    ///     0b11001,
-    ///     0b00001,
-    ///     0b00110
-    /// ]);
-    ///
-    /// // write the character code for the custom character.
-    /// lcd.home();
-    /// lcd.write(0u8);
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_delays(50.micros(), 2.millis(), 50.micros())
+    ///     .build();
     /// ```
-    pub fn set_character(&mut self, mut location: u8, map: [u8; 8]) {
-        location &= 0x7; // limit to locations 0-7
-        self.command(Command::SetCGramAddr as u8 | (location << 3));
-        for ch in map.iter() {
-            self.write(*ch);
-        }
+    pub fn with_delays<C, L, H>(mut self, command: C, clear: L, character: H) -> Self
+    where
+        C: Microseconds,
+        L: Microseconds,
+        H: Microseconds,
+    {
+        self.set_delays(command, clear, character);
+        self
     }
 
-    /// Clear the display.
+    /// Set how long the enable pin is held high for each transfer, in microseconds, for
+    /// long ribbon cables or level shifters that slow down the edges. Zero keeps the pulse
+    /// as short as the pins allow. [with_timing][LcdDisplay::with_timing] replaces this,
+    /// so call it afterwards. (Default is 0)
+    ///
+    /// Interrupts can still stretch the time between setting the data lines and the pulse.
+    /// With the `critical-pulse` feature each transfer (the data lines and the enable
+    /// pulse) runs inside a `critical_section`, so the pulses are the same width every time.
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut lcd: LcdDisplay<_,_> = ...;
-    /// lcd.clear();
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_pulse_width_us(5)
+    ///     .build();
     /// ```
-    pub fn clear(&mut self) {
-        self.command(Command::ClearDisplay as u8);
-        self.delay.delay_us(CMD_DELAY);
+    pub fn with_pulse_width_us<M: Microseconds>(mut self, value: M) -> Self {
+        self.set_pulse_width_us(value);
+        self
     }
 
-    /// Move the cursor to the home position.
+    /// Set how long the data pins are held after the enable pin falls, in microseconds,
+    /// before they change for the next transfer. [with_timing][LcdDisplay::with_timing]
+    /// replaces this, so call it afterwards. (Default is 0)
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut lcd: LcdDisplay<_,_> = ...;
-    /// lcd.home(); // cursor should be top-left
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_pulse_width_us(5)
+    ///     .with_hold_time_us(2)
+    ///     .build();
     /// ```
-    pub fn home(&mut self) {
-        self.command(Command::ReturnHome as u8);
-        self.delay.delay_us(CMD_DELAY);
+    pub fn with_hold_time_us<M: Microseconds>(mut self, value: M) -> Self {
+        self.set_hold_time_us(value);
+        self
     }
 
-    /// Scroll the display to the right. (See [set_scroll][LcdDisplay::set_scroll])
+    /// Set the controller chip on the display, to enable workarounds for clones that
+    /// need different initialization. (Default is Controller::Hd44780)
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut lcd: LcdDisplay<_,_> = ...;
-    /// lcd.scroll_right(2); // display scrolls 2 positions to the right.
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_controller(Controller::Ks0066)
+    ///     .build();
     /// ```
-    pub fn scroll_right(&mut self, value: u8) {
-        self.set_scroll(Scroll::Right, value);
+    pub fn with_controller(mut self, value: Controller) -> Self {
+        self.context.controller = value;
+        self
     }
 
-    /// Scroll the display to the left. (See [set_scroll][LcdDisplay::set_scroll])
+    /// Set the rows that use a double-height font, which only works with
+    /// [Controller::St7036]. The controller can be set before or after this, and the
+    /// setting is dropped during [build][LcdDisplay::build] if it isn't an ST7036.
+    /// (Default is DoubleHeight::Off)
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut lcd: LcdDisplay<_,_> = ...;
-    /// lcd.scroll_left(2); // display scrolls 2 positions to the left.
-    /// ```
-    pub fn scroll_left(&mut self, value: u8) {
-        self.set_scroll(Scroll::Left, value);
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_controller(Controller::St7036)
+    ///     .with_double_height(DoubleHeight::Top)
+    ///     .build();
+    /// ```
+    pub fn with_double_height(mut self, rows: DoubleHeight) -> Self {
+        self.context.double_height = rows;
+        self
     }
 
-    /// Set the text direction layout left-to-right. (See [set_layout][LcdDisplay::set_layout])
+    /// Turn the voltage booster of an ST7036 or SPLC792 on or off, which is needed by
+    /// modules running from 3.3V. (Default is off)
+    ///
+    /// The booster shares an instruction with the contrast and icon settings, so it is
+    /// only sent when one of those is set (see [set_contrast][LcdDisplay::set_contrast]).
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut lcd: LcdDisplay<_,_> = ...;
-    /// lcd.layout_left_to_right();
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_controller(Controller::St7036)
+    ///     .with_booster(true)
+    ///     .with_contrast(40)
+    ///     .build();
     /// ```
-    pub fn layout_left_to_right(&mut self) {
-        self.set_layout(Layout::LeftToRight);
+    pub fn with_booster(mut self, value: bool) -> Self {
+        self.context.booster = value;
+        self
     }
 
-    /// Set the text direction layout right-to-left. (See [set_layout][LcdDisplay::set_layout])
+    /// Set the contrast (0-63) sent during initialization of an ST7036 or SPLC792. The
+    /// controller can be set before or after this, and the setting is dropped during
+    /// [build][LcdDisplay::build] for other controllers.
+    /// (See [set_contrast][LcdDisplay::set_contrast])
+    pub fn with_contrast(mut self, value: u8) -> Self {
+        self.context.contrast = Some(value.min(63));
+        self
+    }
+
+    /// Set the character ROM fitted to the display, used by [print_utf8][LcdDisplay::print_utf8]
+    /// to find the code for each character. (Default is Rom::A00)
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut lcd: LcdDisplay<_,_> = ...;
-    /// lcd.layout_right_to_left();
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_rom(Rom::A02)
+    ///     .build();
     /// ```
-    pub fn layout_right_to_left(&mut self) {
-        self.set_layout(Layout::RightToLeft);
+    pub fn with_rom(mut self, value: Rom) -> Self {
+        self.context.rom = value;
+        self
     }
 
-    /// Turn the display on. (See [set_display][LcdDisplay::set_display])
+    /// Set what [print_utf8][LcdDisplay::print_utf8] does with characters that aren't in
+    /// the character ROM. (Default is Unmappable::Replace(b'?'))
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut lcd: LcdDisplay<_,_> = ...;
-    /// lcd.display_on();
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_unmappable(Unmappable::Skip)
+    ///     .build();
     /// ```
-    pub fn display_on(&mut self) {
-        self.set_display(Display::On);
+    pub fn with_unmappable(mut self, value: Unmappable) -> Self {
+        self.context.unmappable = value;
+        self
     }
 
-    /// Turn the display off. (See [set_display][LcdDisplay::set_display])
+    /// Set how often [maintain][LcdDisplay::maintain] re-sends the display settings, in the
+    /// units of the timestamps passed to it. A value of 0 disables refreshing. (Default is 0)
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut lcd: LcdDisplay<_,_> = ...;
-    /// lcd.display_off();
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_refresh_interval(60_000) // once a minute with millisecond timestamps
+    ///     .build();
     /// ```
-    pub fn display_off(&mut self) {
-        self.set_display(Display::Off);
+    pub fn with_refresh_interval(mut self, interval: u32) -> Self {
+        self.context.refresh_interval = interval;
+        self
     }
 
-    /// Turn the cursor on. (See [set_cursor][LcdDisplay::set_cursor])
+    /// Turn the display off after nothing has been written for `idle`, in the units of
+    /// the timestamps passed to [tick][LcdDisplay::tick]. A value of 0 disables the
+    /// screensaver. (Default is 0)
+    ///
+    /// The display memory is kept while the display is off, and the next write (or a
+    /// call to [wake][LcdDisplay::wake]) turns it back on with the same content.
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut lcd: LcdDisplay<_,_> = ...;
-    /// lcd.cursor_on();
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_screensaver(300_000) // five minutes with millisecond timestamps
+    ///     .build();
     /// ```
-    pub fn cursor_on(&mut self) {
-        self.set_cursor(Cursor::On);
+    pub fn with_screensaver(mut self, idle: u32) -> Self {
+        self.context.screensaver = idle;
+        self
     }
 
-    /// Turn the cursor off. (See [set_cursor][LcdDisplay::set_cursor])
+    /// Turn the backlight off after nothing has been written for `idle`, in the units of
+    /// the timestamps passed to [tick][LcdDisplay::tick], while leaving the text on the
+    /// display. A value of 0 disables the timeout. (Default is 0)
+    ///
+    /// The next write (or a call to [wake][LcdDisplay::wake]) turns the backlight back on.
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut lcd: LcdDisplay<_,_> = ...;
-    /// lcd.cursor_off();
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_backlight(bl)
+    ///     .with_backlight_timeout(10_000) // ten seconds with millisecond timestamps
+    ///     .build();
     /// ```
-    pub fn cursor_off(&mut self) {
-        self.set_cursor(Cursor::Off);
+    pub fn with_backlight_timeout(mut self, idle: u32) -> Self {
+        self.backlight_timeout = idle;
+        self
     }
 
-    /// Set the background of the cursor to blink. (See [set_blink][LcdDisplay::set_blink])
+    /// Set a hook that is called when the error code changes from [Error::None] to an
+    /// error, so a failure can light an LED, log a line or start a recovery routine
+    /// right away. It is called again for the next error after
+    /// [clear_error][LcdDisplay::clear_error]. Set it before [build][LcdDisplay::build]
+    /// to catch configuration errors.
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut lcd: LcdDisplay<_,_> = ...;
-    /// lcd.blink_on();
+    /// fn failed(code: Error) {
+    ///     // turn on the error LED
+    /// }
+    ///
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_on_error(failed)
+    ///     .build();
     /// ```
-    pub fn blink_on(&mut self) {
-        self.set_blink(Blink::On);
+    pub fn with_on_error(mut self, hook: fn(Error)) -> Self {
+        self.on_error = Some(hook);
+        self
     }
 
-    /// Set the background of the cursor to stop blinking. (See [set_blink][LcdDisplay::set_blink])
+    /// Set a hook that is called at least every `interval` during the driver's blocking
+    /// delays, so a watchdog can be fed or a quick poll run while the display waits (the
+    /// 50ms power-on delay, 1.5ms clears, busy flag polling). The interval counts time
+    /// spent across consecutive delays, and the hook is never called while an enable
+    /// pulse is being sent. Set it before [build][LcdDisplay::build] to cover the
+    /// initialization sequence. (Default interval is 1ms)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// fn feed() {
+    ///     // reset the watchdog timer
+    /// }
+    ///
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_yield_hook(feed, 1000)
+    ///     .build();
+    /// ```
+    pub fn with_yield_hook<M: Microseconds>(mut self, hook: fn(), interval: M) -> Self {
+        self.set_yield_hook(Some(hook), interval);
+        self
+    }
+
+    /// Set a hook that is called with every command and data byte sent to the display,
+    /// so the exact byte stream can be mirrored to a serial port or RTT while debugging.
+    ///
+    /// The four-bit synchronization nibbles sent during initialization are not traced.
+    /// This method is only available if the `trace` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// fn trace(transfer: Transfer) {
+    ///     // write to a global serial port
+    /// }
+    ///
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_trace(trace)
+    ///     .build();
+    /// ```
+    #[cfg(feature = "trace")]
+    pub fn with_trace(mut self, hook: fn(Transfer)) -> Self {
+        self.trace = Some(hook);
+        self
+    }
+
+    /// Check every transfer against the datasheet minimums, using `clock` to timestamp
+    /// them. The enable pulse has to be at least 450 ns wide, and each instruction has to
+    /// be sent at least 1.52 ms after a clear or home and 37 µs after anything else
+    /// (unless the [busy flag][LcdDisplay::with_busy_flag] is polled instead). A violation
+    /// panics through `debug_assert!`, so the checks only run in debug builds.
+    ///
+    /// `clock` returns a free-running time in nanoseconds, which is allowed to wrap. Its
+    /// resolution should be well below 450 ns, or short enable pulses are reported even
+    /// if they are wide enough. This method is only available if the `timing-audit`
+    /// feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// fn now() -> u32 {
+    ///     // cycle counter at 64 MHz
+    ///     DWT::cycle_count().wrapping_mul(1000) / 64
+    /// }
+    ///
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_timing_audit(now)
+    ///     .build();
+    /// ```
+    #[cfg(feature = "timing-audit")]
+    pub fn with_timing_audit(mut self, clock: fn() -> u32) -> Self {
+        self.audit = Some(Audit {
+            clock,
+            rose: 0,
+            sent: 0,
+            execute: 0,
+        });
+        self
+    }
+
+    /// Set how many times [write_verified][LcdDisplay::write_verified] re-sends a character
+    /// that didn't read back correctly. This sets the attempts of the
+    /// [retry policy][LcdDisplay::with_retry_policy] to `retries + 1`. (Default is 0)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_rw(rw)
+    ///     .with_write_retries(5)
+    ///     .build();
+    /// ```
+    pub fn with_write_retries(mut self, retries: u8) -> Self {
+        self.retry.attempts = retries.saturating_add(1);
+        self
+    }
+
+    /// Set how many times commands and characters that hit a pin or bus error, verified
+    /// writes and [probe][LcdDisplay::probe] are tried before giving up, and how long to
+    /// wait between attempts. The error is only recorded if the last attempt fails too.
+    /// (See [RetryPolicy], default is a single attempt)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new_pcf8574(&mut i2c_expander, delay)
+    ///     .with_retry_policy(RetryPolicy::new(6, 500))
+    ///     .build();
+    /// ```
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Increase reliability of initialization of LCD.
+    ///
+    /// Some users experience unreliable initialization of the LCD, where
+    /// the LCD sometimes is unable to display symbols after running
+    /// `.build()`. This method toggles the LCD off and on with some
+    /// delay in between, 3 times. A higher `delay_toggle` tends to make
+    /// this method more reliable, and a value of `10 000` is recommended.
+    /// Note that this method should be run as close as possible to
+    /// `.build()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_reliable_init(10000)
+    ///     .build();
+    /// ```
+    pub fn with_reliable_init<M: Microseconds>(mut self, delay_toggle: M) -> Self {
+        let delay_toggle = delay_toggle.to_micros();
+        if self.context.display_ctrl == Display::On as u8 {
+            for _ in 0..3 {
+                self.pause(delay_toggle);
+                self.display_off();
+                self.pause(delay_toggle);
+                self.display_on();
+            }
+        } else {
+            for _ in 0..3 {
+                self.pause(delay_toggle);
+                self.display_on();
+                self.pause(delay_toggle);
+                self.display_off();
+            }
+        }
+
+        self
+    }
+
+    /// Finish construction of the LcdDisplay and initialized the
+    /// display to the provided settings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ag_lcd::{Display, Blink, Cursor, LcdDisplay};
+    ///
+    /// let peripherals = arduino_hal::Peripherals::take().unwrap();
+    /// let pins = arduino_hal::pins!(peripherals);
+    /// let delay = arduino_hal::Delay::new();
+    ///
+    /// let rs = pins.d12.into_output().downgrade();
+    /// let rw = pins.d11.into_output().downgrade();
+    /// let en = pins.d10.into_output().downgrade();
+    ///
+    /// // left-side names refer to lcd pinout (e.g. 'd4' = D4 on lcd)
+    /// let d4 = pins.d5.into_output().downgrade();
+    /// let d5 = pins.d4.into_output().downgrade();
+    /// let d6 = pins.d3.into_output().downgrade();
+    /// let d7 = pins.d2.into_output().downgrade();
+    ///
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_display(Display::On)
+    ///     .with_blink(Blink::On)
+    ///     .with_cursor(Cursor::On)
+    ///     .with_rw(rw) // optional (set lcd pin to GND if not provided)
+    ///     .build();
+    ///
+    /// lcd.print("Test message!");
+    /// ```
+    pub fn build(mut self) -> Self {
+        if self.power.is_some() {
+            self.switch_power(true);
+        }
+        self.reinit();
+
+        // set an error code display is misconfigured
+        self.validate();
+
+        if let Some(probe) = self.probe {
+            probe(&mut self);
+        }
+        self
+    }
+
+    /// Run the full initialization sequence again, resetting the display to the current
+    /// settings. This clears the display and moves the cursor to the home position. If
+    /// [with_verified_init][LcdDisplay::with_verified_init] is set, the display is checked
+    /// afterwards and power cycled if it doesn't respond.
     ///
     /// # Examples
     ///
     /// ```
     /// let mut lcd: LcdDisplay<_,_> = ...;
-    /// lcd.blink_off();
+    /// lcd.reinit();
     /// ```
-    pub fn blink_off(&mut self) {
-        self.set_blink(Blink::Off);
+    pub fn reinit(&mut self) {
+        match self.verify {
+            Some(verify) => {
+                verify(self, self.verify_attempts);
+            }
+            None => self.initialize(),
+        }
     }
 
-    /// Turn backlight on
-    pub fn backlight_on(&mut self) {
-        if let Some(backlight_pin) = &mut self.pins[A as usize] {
-            let _ = backlight_pin.set_high();
+    /// Wait for the display to power up and run the initialization sequence once.
+    fn initialize(&mut self) {
+        self.pause(self.context.controller.power_on_delay());
+        self.init();
+    }
+
+    /// Switch the supply of the display on or off with the power pin or switch.
+    fn switch_power(&mut self, on: bool) {
+        let switched = match self.power.as_mut() {
+            Some(Power::Pin(pin)) => match on {
+                true => pin.set_high().is_ok(),
+                false => pin.set_low().is_ok(),
+            },
+            Some(Power::Switch(switch)) => switch(on),
+            None => true,
+        };
+        if !switched {
+            self.fail(Error::PowerFailed);
         }
     }
 
-    /// Turn backlight off
-    pub fn backlight_off(&mut self) {
-        if let Some(backlight_pin) = &mut self.pins[A as usize] {
-            let _ = backlight_pin.set_low();
+    /// Run the full initialization sequence `attempts` times, for displays that sometimes
+    /// stay blank after a reset. Unlike [with_reliable_init][LcdDisplay::with_reliable_init]
+    /// this can be called at any time after [build][LcdDisplay::build]. If the data pins
+    /// can be read, [reliable_init_verified][LcdDisplay::reliable_init_verified] stops as
+    /// soon as the display responds instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.reliable_init(3);
+    /// ```
+    pub fn reliable_init(&mut self, attempts: u8) {
+        for _ in 0..attempts.max(1) {
+            self.initialize();
         }
     }
 
-    /// Turn autoscroll on. (See [set_autoscroll][LcdDisplay::set_autoscroll])
+    /// Switch the display off with the [power pin][LcdDisplay::with_power_pin], wait, then
+    /// switch it back on and run the full initialization sequence. The bus pins are held
+    /// low while the power is off, so the display isn't powered through them. Does nothing
+    /// if there is no power pin or switch.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.power_cycle();
+    /// ```
+    pub fn power_cycle(&mut self) {
+        if self.power.is_none() {
+            return;
+        }
+
+        for index in [RS, RW, D0, D1, D2, D3, D4, D5, D6, D7, EN] {
+            if self.exists(index) {
+                self.set(index, false);
+            }
+        }
+
+        self.switch_power(false);
+        self.pause(POWER_OFF_DELAY);
+        self.switch_power(true);
+        self.initialize();
+    }
+
+    /// Re-send the bus width, function, control and entry mode settings without clearing
+    /// the display, then restore the cursor position.
+    ///
+    /// This heals a controller that lost its configuration (or four-bit nibble alignment)
+    /// because of electrical noise or a short power dip, while keeping the text on screen.
+    /// See [with_refresh_interval][LcdDisplay::with_refresh_interval] to do this periodically.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.refresh();
+    /// ```
+    pub fn refresh(&mut self) {
+        self.reconfigure();
+    }
+
+    /// Periodically [refresh][LcdDisplay::refresh] the display from the main loop. `now` is
+    /// a timestamp in the same (wrapping) units as the interval given to
+    /// [with_refresh_interval][LcdDisplay::with_refresh_interval]. Does nothing if no interval
+    /// has been set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_refresh_interval(60_000)
+    ///     .build();
+    ///
+    /// loop {
+    ///     lcd.maintain(millis());
+    /// }
+    /// ```
+    pub fn maintain(&mut self, now: u32) {
+        if self.context.refresh_interval == 0 {
+            return;
+        }
+
+        if now.wrapping_sub(self.context.last_refresh) >= self.context.refresh_interval {
+            self.context.last_refresh = now;
+            self.refresh();
+        }
+    }
+
+    /// Advance the inactivity timer from the main loop and turn the display off once
+    /// the [screensaver][LcdDisplay::with_screensaver] timeout has passed. `now` is a
+    /// timestamp in the same (wrapping) units as the timeout. This also drives the
+    /// backlight blinks started by [notify][LcdDisplay::notify].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// loop {
+    ///     lcd.tick(millis());
+    /// }
+    /// ```
+    pub fn tick(&mut self, now: u32) {
+        if self.context.activity {
+            self.context.activity = false;
+            self.context.last_activity = now;
+        }
+
+        self.tick_notification(now);
+
+        let idle = now.wrapping_sub(self.context.last_activity);
+        if self.context.screensaver > 0
+            && idle >= self.context.screensaver
+            && !self.context.blanked
+            && self.context.sleeping.is_none()
+            && self.display() == Display::On
+        {
+            self.context.blanked = true;
+            self.set_display(Display::Off);
+        }
+
+        if self.backlight_timeout > 0
+            && idle >= self.backlight_timeout
+            && !self.dimmed
+            && self.backlight
+            && self.notification.is_none()
+        {
+            self.dimmed = true;
+            self.backlight_off();
+        }
+    }
+
+    /// Blink the backlight `blinks` times to draw attention to the display, without
+    /// blocking. Each blink turns the backlight off (or on, if it's off) for half of
+    /// `period` and back for the other half, driven by [tick][LcdDisplay::tick] in the
+    /// same (wrapping) units. Calling this again replaces the current notification, and
+    /// zero blinks cancels it. The backlight is left the way it was before.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.notify(3, 500);
+    ///
+    /// loop {
+    ///     lcd.tick(millis());
+    ///     // ... keep updating the display
+    /// }
+    /// ```
+    pub fn notify(&mut self, blinks: u8, period: u32) {
+        self.cancel_notification();
+
+        if blinks > 0 {
+            self.notification = Some(Notification {
+                toggles: blinks as u16 * 2,
+                half: period / 2,
+                since: None,
+                restore: self.backlight,
+            });
+        }
+    }
+
+    /// Check if backlight blinks from [notify][LcdDisplay::notify] are still running.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.notify(3, 500);
+    /// while lcd.notifying() {
+    ///     lcd.tick(millis());
+    /// }
+    /// ```
+    pub fn notifying(&self) -> bool {
+        self.notification.is_some()
+    }
+
+    /// Stop blinking the backlight and put it back the way it was before
+    /// [notify][LcdDisplay::notify] was called.
+    fn cancel_notification(&mut self) {
+        if let Some(notification) = self.notification.take() {
+            if self.backlight != notification.restore {
+                self.toggle_backlight();
+            }
+        }
+    }
+
+    /// Change the backlight if the current blink phase is over.
+    fn tick_notification(&mut self, now: u32) {
+        let Some(mut notification) = self.notification else {
+            return;
+        };
+
+        let due = match notification.since {
+            Some(since) => now.wrapping_sub(since) >= notification.half,
+            None => true,
+        };
+
+        if due {
+            self.toggle_backlight();
+            notification.toggles -= 1;
+            notification.since = Some(now);
+        }
+
+        self.notification = (notification.toggles > 0).then_some(notification);
+    }
+
+    /// Turn the backlight on if it's off, or off if it's on.
+    fn toggle_backlight(&mut self) {
+        match self.backlight {
+            true => self.backlight_off(),
+            false => self.backlight_on(),
+        }
+    }
+
+    /// Run `f` without its writes counting as activity, so animations don't wake the
+    /// display or keep the screensaver and backlight timeout from running.
+    pub(crate) fn passive<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        let saved = (self.context.activity, self.context.blanked, self.dimmed);
+        self.context.blanked = false;
+        self.dimmed = false;
+
+        let result = f(self);
+        (self.context.activity, self.context.blanked, self.dimmed) = saved;
+        result
+    }
+
+    /// Set the screensaver timeout. (See [with_screensaver][LcdDisplay::with_screensaver])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.set_screensaver(0); // disable
+    /// ```
+    pub fn set_screensaver(&mut self, idle: u32) {
+        self.context.screensaver = idle;
+    }
+
+    /// Set the backlight timeout. (See [with_backlight_timeout][LcdDisplay::with_backlight_timeout])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.set_backlight_timeout(30_000);
+    /// ```
+    pub fn set_backlight_timeout(&mut self, idle: u32) {
+        self.backlight_timeout = idle;
+    }
+
+    /// Set the position of the cursor. A row or column outside of display memory sets
+    /// the error code to [Error::OutOfBounds] and moves the cursor to the last one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// let row = 0;
+    /// let col = 2;
+    ///
+    /// lcd.set_position(col,row);
+    /// ```
+    pub fn set_position(&mut self, mut col: u8, mut row: u8) {
+        let num_lines = self.rows();
+
+        if row >= num_lines {
+            row = num_lines.saturating_sub(1);
+            self.fail(Error::OutOfBounds);
+        }
+
+        let length = self.row_length();
+        if col as i16 >= length {
+            col = (length - 1) as u8;
+            self.fail(Error::OutOfBounds);
+        }
+
+        let pos = self.row_offsets()[row as usize].wrapping_add(col) & 0x7F;
+        self.command(Command::SetDDRAMAddr as u8 | pos);
+        self.wait(self.delays.command);
+
+        self.context.col = col;
+        self.context.row = row;
+    }
+
+    /// Scroll the display right or left.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// let direction = Scroll::Left;
+    /// let distance = 2;
+    ///
+    /// lcd.set_scroll(direction,distance);
+    /// ```
+    pub fn set_scroll(&mut self, direction: Scroll, distance: u8) {
+        let delta = match direction {
+            Scroll::Right => distance as i16,
+            Scroll::Left => -(distance as i16),
+        };
+
+        let command = Command::CursorShift as u8 | Move::Display as u8 | direction as u8;
+        for _ in 0..distance {
+            self.command(command);
+            self.wait(self.delays.command);
+        }
+
+        self.shift_by(delta);
+    }
+
+    /// Scroll the display back to its original position without moving the cursor
+    /// or clearing the display. (See [scroll_offset][LcdDisplay::scroll_offset])
+    ///
+    /// Unlike [home][LcdDisplay::home], the address counter is left unchanged, so the
+    /// next character is written wherever the cursor currently is. The display wraps
+    /// every 40 columns, so it is scrolled whichever way around is shorter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.scroll_left(3);
+    /// lcd.scroll_reset(); // display is back at the origin
+    /// ```
+    pub fn scroll_reset(&mut self) {
+        let offset = self.context.shift as i16;
+        let around = DDRAM_COLS - offset.abs();
+
+        match offset.abs() > around {
+            true if offset > 0 => self.set_scroll(Scroll::Right, around as u8),
+            true => self.set_scroll(Scroll::Left, around as u8),
+            false if offset > 0 => self.set_scroll(Scroll::Left, offset as u8),
+            false => self.set_scroll(Scroll::Right, offset.unsigned_abs() as u8),
+        }
+    }
+
+    /// Change the number of lines. (See [with_lines][LcdDisplay::with_lines])
+    ///
+    /// The controller only accepts a new function set as part of the initialization
+    /// sequence, so the bus synchronization is sent again before it, followed by the
+    /// display control and entry mode. Display memory and the cursor position are kept
+    /// (the row is limited to the new number of rows), but text may show up on different
+    /// rows. The 5x10 character size shares a bit with four-line mode, so it is only
+    /// sent with one line and comes back when the display is set to one line again.
+    /// A [Controller::Ks0073] leaving four-line mode goes back to the row offsets set by
+    /// [with_cols][LcdDisplay::with_cols] or [with_row_offsets][LcdDisplay::with_row_offsets].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.set_lines(Lines::TwoLines);
+    /// lcd.clear();
+    /// ```
+    pub fn set_lines(&mut self, lines: Lines) {
+        let mut function = (self.context.display_func & !(Lines::FourLines as u8)) | lines as u8;
+        if lines == Lines::OneLine && self.context.font == Size::Dots5x10 {
+            function |= Size::Dots5x10 as u8;
+        }
+        self.set_function(function);
+    }
+
+    /// Change the character size. (See [with_size][LcdDisplay::with_size])
+    ///
+    /// The 5x10 size can only be used on one line, and controllers ignore the size
+    /// with two or four lines, so nothing is sent unless the display has one line.
+    /// Otherwise the display is reconfigured like [set_lines][LcdDisplay::set_lines].
+    /// The size is kept either way, and used when the display is set to one line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.set_size(Size::Dots5x10);
+    /// ```
+    pub fn set_size(&mut self, size: Size) {
+        self.context.font = size;
+        if self.lines() != Lines::OneLine {
+            return;
+        }
+        match size {
+            Size::Dots5x10 => self.context.display_func |= Size::Dots5x10 as u8,
+            Size::Dots5x8 => self.context.display_func &= !(Size::Dots5x10 as u8),
+        }
+        self.reconfigure();
+    }
+
+    /// Set the text direction layout.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.set_layout(Layout::LeftToRight);
+    /// ```
+    pub fn set_layout(&mut self, layout: Layout) {
+        match layout {
+            Layout::LeftToRight => self.context.display_mode |= Layout::LeftToRight as u8,
+            Layout::RightToLeft => self.context.display_mode &= !(Layout::LeftToRight as u8),
+        }
+        self.command(Command::SetDisplayMode as u8 | self.context.display_mode);
+        self.wait(self.delays.command);
+    }
+
+    /// Turn the display on or off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.set_display(Display::Off);
+    /// ```
+    pub fn set_display(&mut self, display: Display) {
+        match display {
+            Display::On => self.context.display_ctrl |= Display::On as u8,
+            Display::Off => self.context.display_ctrl &= !(Display::On as u8),
+        }
+        self.command(Command::SetDisplayCtrl as u8 | self.context.display_ctrl);
+        self.wait(self.delays.command);
+    }
+
+    /// Turn the cursor on or off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.set_cursor(Cursor::On);
+    /// ```
+    pub fn set_cursor(&mut self, cursor: Cursor) {
+        match cursor {
+            Cursor::On => self.context.display_ctrl |= Cursor::On as u8,
+            Cursor::Off => self.context.display_ctrl &= !(Cursor::On as u8),
+        }
+        self.command(Command::SetDisplayCtrl as u8 | self.context.display_ctrl);
+        self.wait(self.delays.command);
+    }
+
+    /// Make the background of the cursor blink or stop blinking.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.set_blink(Blink::On);
+    /// ```
+    pub fn set_blink(&mut self, blink: Blink) {
+        match blink {
+            Blink::On => self.context.display_ctrl |= Blink::On as u8,
+            Blink::Off => self.context.display_ctrl &= !(Blink::On as u8),
+        }
+        self.command(Command::SetDisplayCtrl as u8 | self.context.display_ctrl);
+        self.wait(self.delays.command);
+    }
+
+    /// Set the cursor and blink state together, replacing separate calls to
+    /// [set_cursor][LcdDisplay::set_cursor] and [set_blink][LcdDisplay::set_blink].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.set_cursor_style(CursorStyle::BlinkingBlock);
+    /// ```
+    pub fn set_cursor_style(&mut self, style: CursorStyle) {
+        self.context.display_ctrl &= !(CursorStyle::UnderlineAndBlinkingBlock as u8);
+        self.context.display_ctrl |= style as u8;
+        self.command(Command::SetDisplayCtrl as u8 | self.context.display_ctrl);
+        self.wait(self.delays.command);
+    }
+
+    /// Set or remove the hook that is called with every byte sent to the display.
+    /// (See [with_trace][LcdDisplay::with_trace])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.set_trace(None);
+    /// ```
+    #[cfg(feature = "trace")]
+    pub fn set_trace(&mut self, hook: Option<fn(Transfer)>) {
+        self.trace = hook;
+    }
+
+    /// Set the delays used when talking to the display from a named profile.
+    /// (See [with_timing][LcdDisplay::with_timing])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.set_timing(Timing::Fast);
+    /// ```
+    pub fn set_timing(&mut self, timing: Timing) {
+        self.delays = Delays::from_timing(timing);
+    }
+
+    /// Set the delays used when talking to the display individually.
+    /// (See [with_delays][LcdDisplay::with_delays])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.set_delays(50.micros(), 2.millis(), 50.micros());
+    /// ```
+    pub fn set_delays<C, L, H>(&mut self, command: C, clear: L, character: H)
+    where
+        C: Microseconds,
+        L: Microseconds,
+        H: Microseconds,
+    {
+        self.delays.command = command.to_micros();
+        self.delays.clear = clear.to_micros();
+        self.delays.character = character.to_micros();
+    }
+
+    /// Set how long the enable pin is held high for each transfer, in microseconds.
+    /// (See [with_pulse_width_us][LcdDisplay::with_pulse_width_us])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.set_pulse_width_us(5);
+    /// ```
+    pub fn set_pulse_width_us<M: Microseconds>(&mut self, value: M) {
+        self.delays.pulse = value.to_micros();
+    }
+
+    /// Set how long the data pins are held after the enable pin falls, in microseconds.
+    /// (See [with_hold_time_us][LcdDisplay::with_hold_time_us])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.set_hold_time_us(2);
+    /// ```
+    pub fn set_hold_time_us<M: Microseconds>(&mut self, value: M) {
+        self.delays.hold = value.to_micros();
+    }
+
+    /// Set how text wraps when it reaches the end of a row. (See [with_wrap][LcdDisplay::with_wrap])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.set_wrap(Wrap::Word);
+    /// ```
+    pub fn set_wrap(&mut self, wrap: Wrap) {
+        self.context.wrap = wrap;
+    }
+
+    /// Set whether the cursor position is restored after a custom character is stored.
+    /// (See [with_keep_position][LcdDisplay::with_keep_position])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.set_keep_position(false);
+    /// ```
+    pub fn set_keep_position(&mut self, value: bool) {
+        self.keep_position = value;
+    }
+
+    /// Enable or disable LCD backlight
+    pub fn set_backlight(&mut self, backlight: Backlight) {
+        match backlight {
+            Backlight::On => self.backlight_on(),
+            Backlight::Off => self.backlight_off(),
+        }
+    }
+
+    /// Turn auto scroll on or off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.set_autoscroll(AutoScroll::On);
+    /// ```
+    pub fn set_autoscroll(&mut self, scroll: AutoScroll) {
+        match scroll {
+            AutoScroll::On => self.context.display_mode |= AutoScroll::On as u8,
+            AutoScroll::Off => self.context.display_mode &= !(AutoScroll::On as u8),
+        }
+        self.command(Command::SetDisplayMode as u8 | self.context.display_mode);
+        self.wait(self.delays.command);
+    }
+
+    /// Add a new character map to the LCD memory (CGRAM) at a particular location.
+    /// There are eight locations available at positions 0-7. A location outside of this
+    /// range sets the error code to [Error::OutOfBounds], and is still bitwise masked to
+    /// fall within the range, possibly overwriting an existing custom character.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// // set a sideways smiley face in CGRAM at location 0.
+    /// lcd.set_character(0u8,[
+    ///     0b00110,
+    ///     0b00001,
+    ///     0b11001,
+    ///     0b00001,
+    ///     0b00001,
+    ///     0b11001,
+    ///     0b00001,
+    ///     0b00110
+    /// ]);
+    ///
+    /// // write the character code for the custom character.
+    /// lcd.home();
+    /// lcd.write(0u8);
+    /// ```
+    ///
+    /// Afterwards the address counter is pointed back at the cursor position, so text
+    /// can be printed straight away. (See [with_keep_position][LcdDisplay::with_keep_position])
+    pub fn set_character(&mut self, location: u8, map: [u8; 8]) {
+        if location > 7 {
+            self.fail(Error::OutOfBounds);
+        }
+
+        match self.character_check {
+            Some(check) => {
+                check(self, location & 0x7, map);
+            }
+            None => self.upload_character(location & 0x7, map),
+        }
+        self.return_to_ddram();
+    }
+
+    /// Store a custom character in the first CGRAM slot that hasn't been used since the
+    /// display was built, and return the slot. If all eight slots are in use the error
+    /// code is set to [Error::CgRamFull] and nothing is stored. Slots written with
+    /// [set_character][LcdDisplay::set_character] count as used, and can be given back
+    /// with [remove_character][LcdDisplay::remove_character].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// if let Some(slot) = lcd.add_character(heart) {
+    ///     lcd.write(slot);
+    /// }
+    /// ```
+    pub fn add_character(&mut self, map: [u8; 8]) -> Option<u8> {
+        match (0..8).find(|slot| self.context.characters & (1 << slot) == 0) {
+            Some(slot) => {
+                self.set_character(slot, map);
+                Some(slot)
+            }
+            None => {
+                self.fail(Error::CgRamFull);
+                None
+            }
+        }
+    }
+
+    /// Mark a CGRAM slot (0-7) as free, so [add_character][LcdDisplay::add_character]
+    /// can use it again. The character stays in memory until it is replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// let slot = lcd.add_character(heart).unwrap();
+    /// ...
+    /// lcd.remove_character(slot);
+    /// ```
+    pub fn remove_character(&mut self, location: u8) {
+        self.context.characters &= !(1 << (location & 0x7));
+    }
+
+    /// Point the address counter back at the cursor position in DDRAM, if it was left in
+    /// CGRAM and [with_keep_position][LcdDisplay::with_keep_position] is set.
+    fn return_to_ddram(&mut self) {
+        if self.keep_position && self.context.cgram.is_some() {
+            self.context.cgram = None;
+            let address = self.address();
+            self.command(address);
+            self.wait(self.delays.command);
+        }
+    }
+
+    /// Write a character map to CGRAM at `location` (0-7).
+    fn upload_character(&mut self, location: u8, map: [u8; 8]) {
+        self.context.characters |= 1 << location;
+        self.command(Command::SetCGramAddr as u8 | (location << 3));
+        for ch in map.iter() {
+            // writes to CGRAM never shift the display
+            self.data(*ch);
+        }
+    }
+
+    /// Clear the display.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.clear();
+    /// ```
+    pub fn clear(&mut self) {
+        self.command(Command::ClearDisplay as u8);
+        self.wait(self.clear_delay());
+        self.context.shift = 0;
+        self.context.col = 0;
+        self.context.row = 0;
+    }
+
+    /// Clear the visible area by writing spaces over it, and move the cursor to the
+    /// start of the first row.
+    ///
+    /// The clear instruction used by [clear][LcdDisplay::clear] blocks for around 1.5ms,
+    /// while this only takes one write per visible cell (about 0.7ms for 16x2), which
+    /// adds up in fast refresh loops. Text outside the visible area is left in place,
+    /// and the display stays scrolled by the current [scroll_offset][LcdDisplay::scroll_offset].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// loop {
+    ///     lcd.clear_fast();
+    ///     lcd.print(&reading);
+    /// }
+    /// ```
+    pub fn clear_fast(&mut self) {
+        // write left to right without shifting the display, whatever the entry mode
+        let mode = self.context.display_mode;
+        if mode != DEFAULT_DISPLAY_MODE {
+            self.command(Command::SetDisplayMode as u8 | DEFAULT_DISPLAY_MODE);
+            self.wait(self.delays.command);
+        }
+
+        // each row wraps within its own DDRAM line
+        let length = self.row_length();
+        let line = match self.native_four_lines() {
+            true => 0x60,
+            false => 0x40,
+        };
+
+        for row in 0..self.rows() {
+            let offset = self.row_offsets()[row as usize];
+            let first = (offset & !line) as i16 - self.context.shift as i16;
+            for col in 0..self.context.cols as i16 {
+                let column = (first + col).rem_euclid(length) as u8;
+                if col == 0 || column == 0 {
+                    self.command(Command::SetDDRAMAddr as u8 | (offset & line) | column);
+                    self.wait(self.delays.command);
+                }
+                self.data(b' ');
+            }
+        }
+
+        if mode != DEFAULT_DISPLAY_MODE {
+            self.command(Command::SetDisplayMode as u8 | mode);
+            self.wait(self.delays.command);
+        }
+        self.set_position(0, 0);
+    }
+
+    /// Move the cursor to the home position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.home(); // cursor should be top-left
+    /// ```
+    pub fn home(&mut self) {
+        self.command(Command::ReturnHome as u8);
+        self.wait(self.clear_delay());
+        self.context.shift = 0;
+        self.context.col = 0;
+        self.context.row = 0;
+    }
+
+    /// Scroll the display to the right. (See [set_scroll][LcdDisplay::set_scroll])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.scroll_right(2); // display scrolls 2 positions to the right.
+    /// ```
+    pub fn scroll_right(&mut self, value: u8) {
+        self.set_scroll(Scroll::Right, value);
+    }
+
+    /// Scroll the display to the left. (See [set_scroll][LcdDisplay::set_scroll])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.scroll_left(2); // display scrolls 2 positions to the left.
+    /// ```
+    pub fn scroll_left(&mut self, value: u8) {
+        self.set_scroll(Scroll::Left, value);
+    }
+
+    /// Set the text direction layout left-to-right. (See [set_layout][LcdDisplay::set_layout])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.layout_left_to_right();
+    /// ```
+    pub fn layout_left_to_right(&mut self) {
+        self.set_layout(Layout::LeftToRight);
+    }
+
+    /// Set the text direction layout right-to-left. (See [set_layout][LcdDisplay::set_layout])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.layout_right_to_left();
+    /// ```
+    pub fn layout_right_to_left(&mut self) {
+        self.set_layout(Layout::RightToLeft);
+    }
+
+    /// Turn the display on. (See [set_display][LcdDisplay::set_display])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.display_on();
+    /// ```
+    pub fn display_on(&mut self) {
+        self.set_display(Display::On);
+    }
+
+    /// Turn the display off. (See [set_display][LcdDisplay::set_display])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.display_off();
+    /// ```
+    pub fn display_off(&mut self) {
+        self.set_display(Display::Off);
+    }
+
+    /// Turn the cursor on. (See [set_cursor][LcdDisplay::set_cursor])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.cursor_on();
+    /// ```
+    pub fn cursor_on(&mut self) {
+        self.set_cursor(Cursor::On);
+    }
+
+    /// Turn the cursor off. (See [set_cursor][LcdDisplay::set_cursor])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.cursor_off();
+    /// ```
+    pub fn cursor_off(&mut self) {
+        self.set_cursor(Cursor::Off);
+    }
+
+    /// Set the background of the cursor to blink. (See [set_blink][LcdDisplay::set_blink])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.blink_on();
+    /// ```
+    pub fn blink_on(&mut self) {
+        self.set_blink(Blink::On);
+    }
+
+    /// Set the background of the cursor to stop blinking. (See [set_blink][LcdDisplay::set_blink])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.blink_off();
+    /// ```
+    pub fn blink_off(&mut self) {
+        self.set_blink(Blink::Off);
+    }
+
+    /// Turn backlight on
+    pub fn backlight_on(&mut self) {
+        self.backlight = true;
+        if self.port.is_some() {
+            self.flush();
+        } else if let Some(backlight_pin) = &mut self.pins[A as usize] {
+            let _ = backlight_pin.set_high();
+        }
+    }
+
+    /// Turn backlight off
+    pub fn backlight_off(&mut self) {
+        self.backlight = false;
+        if self.port.is_some() {
+            self.flush();
+        } else if let Some(backlight_pin) = &mut self.pins[A as usize] {
+            let _ = backlight_pin.set_low();
+        }
+    }
+
+    /// Turn the display, cursor, blink and backlight off to save power. The display
+    /// memory is kept, and [wake][LcdDisplay::wake] restores the previous state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.sleep();
+    /// // ...
+    /// lcd.wake();
+    /// ```
+    pub fn sleep(&mut self) {
+        if self.context.sleeping.is_some() {
+            return;
+        }
+
+        // remember the state from before the screensaver or backlight timeout, so waking
+        // up doesn't restore the blanked display or dimmed backlight
+        let mut settings = self.settings();
+        if self.context.blanked {
+            settings.display_ctrl |= Display::On as u8;
+        }
+        if self.dimmed {
+            settings.backlight = true;
+        }
+        (self.context.blanked, self.dimmed) = (false, false);
+
+        self.context.sleeping = Some(settings);
+        self.context.display_ctrl &= !(Display::On as u8 | Cursor::On as u8 | Blink::On as u8);
+        self.command(Command::SetDisplayCtrl as u8 | self.context.display_ctrl);
+        self.wait(self.delays.command);
+        self.backlight_off();
+    }
+
+    /// Restore the display, cursor, blink and backlight state from before
+    /// [sleep][LcdDisplay::sleep] was called, or turn the display and backlight back on
+    /// after the [screensaver][LcdDisplay::with_screensaver] or
+    /// [backlight timeout][LcdDisplay::with_backlight_timeout] turned them off. Does
+    /// nothing if the display isn't asleep.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.wake();
+    /// ```
+    pub fn wake(&mut self) {
+        self.context.activity = true;
+        self.undim();
+
+        if let Some(settings) = self.context.sleeping.take() {
+            self.context.display_ctrl = settings.display_ctrl;
+            self.command(Command::SetDisplayCtrl as u8 | self.context.display_ctrl);
+            self.wait(self.delays.command);
+
+            if settings.backlight {
+                self.backlight_on();
+            }
+        }
+    }
+
+    /// Turn the display and backlight back on if the screensaver or backlight timeout
+    /// turned them off, without waking the display from [sleep][LcdDisplay::sleep].
+    fn undim(&mut self) {
+        if self.context.blanked {
+            self.context.blanked = false;
+            self.set_display(Display::On);
+        }
+
+        if self.dimmed {
+            self.dimmed = false;
+            self.backlight_on();
+        }
+    }
+
+    /// Start a group of updates. The cursor and blink are hidden until the matching
+    /// [commit][LcdDisplay::commit], so the cursor doesn't visibly hop around the screen
+    /// while several fields are redrawn. Groups can be nested, and only the outermost
+    /// commit shows the cursor again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.begin();
+    /// lcd.print_at(6, 0, "21.5");
+    /// lcd.print_at(6, 1, "45");
+    /// lcd.set_position(0, 1);
+    /// lcd.commit();
+    /// ```
+    pub fn begin(&mut self) {
+        self.context.transaction = self.context.transaction.saturating_add(1);
+        if self.context.transaction > 1 {
+            return;
+        }
+
+        let mask = Cursor::On as u8 | Blink::On as u8;
+        self.context.hidden = self.context.display_ctrl & mask;
+        if self.context.hidden != 0 {
+            self.context.display_ctrl &= !mask;
+            self.command(Command::SetDisplayCtrl as u8 | self.context.display_ctrl);
+            self.wait(self.delays.command);
+        }
+    }
+
+    /// Finish a group of updates started with [begin][LcdDisplay::begin], showing the
+    /// cursor and blink again if they were on. Does nothing outside of a group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.commit();
+    /// ```
+    pub fn commit(&mut self) {
+        if self.context.transaction == 0 {
+            return;
+        }
+
+        self.context.transaction -= 1;
+        if self.context.transaction == 0 && self.context.hidden != 0 {
+            self.context.display_ctrl |= self.context.hidden;
+            self.context.hidden = 0;
+            self.command(Command::SetDisplayCtrl as u8 | self.context.display_ctrl);
+            self.wait(self.delays.command);
+        }
+    }
+
+    /// Check if a group of updates started with [begin][LcdDisplay::begin] is open.
+    pub fn in_transaction(&self) -> bool {
+        self.context.transaction > 0
+    }
+
+    /// Turn autoscroll on. (See [set_autoscroll][LcdDisplay::set_autoscroll])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.autoscroll_on();
+    /// ```
+    pub fn autoscroll_on(&mut self) {
+        self.set_autoscroll(AutoScroll::On);
+    }
+
+    /// Turn autoscroll off. (See [set_autoscroll][LcdDisplay::set_autoscroll])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.autoscroll_off();
+    /// ```
+    pub fn autoscroll_off(&mut self) {
+        self.set_autoscroll(AutoScroll::Off);
+    }
+
+    /// Get the current bus mode. (See [with_half_bus][LcdDisplay::with_half_bus] and [with_full_bus][LcdDisplay::with_full_bus])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// let mode = lcd.mode();
+    /// ```
+    pub fn mode(&self) -> Mode {
+        if (self.context.display_func & Mode::EightBits as u8) == 0 {
+            Mode::FourBits
+        } else {
+            Mode::EightBits
+        }
+    }
+
+    /// Get the current text direction layout. (See [set_layout][LcdDisplay::set_layout])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// let layout = lcd.layout();
+    /// ```
+    pub fn layout(&self) -> Layout {
+        if (self.context.display_mode & Layout::LeftToRight as u8) == 0 {
+            Layout::RightToLeft
+        } else {
+            Layout::LeftToRight
+        }
+    }
+
+    /// Get the current state of the display (on or off). (See [set_display][LcdDisplay::set_display])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// let display = lcd.display();
+    /// ```
+    pub fn display(&self) -> Display {
+        if (self.context.display_ctrl & Display::On as u8) == 0 {
+            Display::Off
+        } else {
+            Display::On
+        }
+    }
+
+    /// Get the current cursor state (on or off). (See [set_cursor][LcdDisplay::set_cursor])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// let cursor = lcd.cursor();
+    /// ```
+    pub fn cursor(&self) -> Cursor {
+        if (self.context.display_ctrl & Cursor::On as u8) == 0 {
+            Cursor::Off
+        } else {
+            Cursor::On
+        }
+    }
+
+    /// Get the current blink state (on or off). (See [set_blink][LcdDisplay::set_blink])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// let blink = lcd.blink();
+    /// ```
+    pub fn blink(&self) -> Blink {
+        if (self.context.display_ctrl & Blink::On as u8) == 0 {
+            Blink::Off
+        } else {
+            Blink::On
+        }
+    }
+
+    /// Get the current combined cursor and blink state. (See [set_cursor_style][LcdDisplay::set_cursor_style])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// let style = lcd.cursor_style();
+    /// ```
+    pub fn cursor_style(&self) -> CursorStyle {
+        match self.context.display_ctrl & CursorStyle::UnderlineAndBlinkingBlock as u8 {
+            0x01 => CursorStyle::BlinkingBlock,
+            0x02 => CursorStyle::Underline,
+            0x03 => CursorStyle::UnderlineAndBlinkingBlock,
+            _ => CursorStyle::Hidden,
+        }
+    }
+
+    /// Get the current autoscroll state (on or off). (See [set_autoscroll][LcdDisplay::set_autoscroll])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// let autoscroll = lcd.autoscroll();
+    /// ```
+    pub fn autoscroll(&self) -> AutoScroll {
+        if (self.context.display_mode & AutoScroll::On as u8) == 0 {
+            AutoScroll::Off
+        } else {
+            AutoScroll::On
+        }
+    }
+
+    /// Get the character size. (See [with_size][LcdDisplay::with_size])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// let size = lcd.size();
+    /// ```
+    pub fn size(&self) -> Size {
+        // the size bit doubles as the four-line marker (see Lines::FourLines)
+        if self.lines() == Lines::OneLine && (self.context.display_func & Size::Dots5x10 as u8) != 0
+        {
+            Size::Dots5x10
+        } else {
+            Size::Dots5x8
+        }
+    }
+
+    /// Get the number of lines. (See [with_lines][LcdDisplay::with_lines])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// let lines = lcd.lines();
+    /// ```
+    pub fn lines(&self) -> Lines {
+        let flag_bits: u8 = self.context.display_func & 0x0C;
+        if flag_bits == Lines::FourLines as u8 {
+            Lines::FourLines
+        } else if flag_bits == Lines::TwoLines as u8 {
+            Lines::TwoLines
+        } else {
+            Lines::OneLine
+        }
+    }
+
+    /// Get the bus mode, number of lines and character size together.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// let function = lcd.function_set();
+    ///
+    /// if function.lines == Lines::TwoLines {
+    ///     ...
+    /// }
+    /// ```
+    pub fn function_set(&self) -> FunctionSet {
+        FunctionSet {
+            bus: self.mode(),
+            lines: self.lines(),
+            font: self.size(),
+        }
+    }
+
+    /// Get the display, cursor and blink state together.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// let control = lcd.display_control();
+    ///
+    /// if control.cursor == Cursor::On || control.blink == Blink::On {
+    ///     ...
+    /// }
+    /// ```
+    pub fn display_control(&self) -> DisplayControl {
+        DisplayControl {
+            display: self.display(),
+            cursor: self.cursor(),
+            blink: self.blink(),
+        }
+    }
+
+    /// Get the text direction and autoscroll state together.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// let entry = lcd.entry_mode();
+    /// ```
+    pub fn entry_mode(&self) -> EntryMode {
+        EntryMode {
+            layout: self.layout(),
+            autoscroll: self.autoscroll(),
+        }
+    }
+
+    /// Get the current wrapping mode. (See [with_wrap][LcdDisplay::with_wrap])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// let wrap = lcd.wrap();
+    /// ```
+    pub fn wrap(&self) -> Wrap {
+        self.context.wrap
+    }
+
+    /// Check whether the cursor position is restored after a custom character is stored.
+    /// (See [with_keep_position][LcdDisplay::with_keep_position])
+    pub fn keep_position(&self) -> bool {
+        self.keep_position
+    }
+
+    /// Get the number of positions the display is currently shifted by. Positive values
+    /// mean the display has been scrolled to the right and negative values to the left.
+    ///
+    /// The offset is tracked across [set_scroll][LcdDisplay::set_scroll] and autoscrolling
+    /// writes, wraps at the 40 character length of a DDRAM line, and is reset by
+    /// [clear][LcdDisplay::clear], [home][LcdDisplay::home] and [scroll_reset][LcdDisplay::scroll_reset].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.scroll_left(2);
+    /// let offset = lcd.scroll_offset(); // -2
+    /// ```
+    pub fn scroll_offset(&self) -> i8 {
+        self.context.shift
+    }
+
+    /// Get the tracked cursor position as (column, row). The position follows
+    /// [set_position][LcdDisplay::set_position], [clear][LcdDisplay::clear],
+    /// [home][LcdDisplay::home] and written characters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.set_position(3, 1);
+    /// let (col, row) = lcd.position(); // (3, 1)
+    /// ```
+    pub fn position(&self) -> (u8, u8) {
+        (self.context.col, self.context.row)
+    }
+
+    /// Get the current backlight state (on or off). (See [set_backlight][LcdDisplay::set_backlight])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// let backlight = lcd.backlight();
+    /// ```
+    pub fn backlight(&self) -> Backlight {
+        if self.backlight {
+            Backlight::On
+        } else {
+            Backlight::Off
+        }
+    }
+
+    /// Check if the display has been put to sleep. (See [sleep][LcdDisplay::sleep])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// let asleep = lcd.is_sleeping();
+    /// ```
+    pub fn is_sleeping(&self) -> bool {
+        self.context.sleeping.is_some()
+    }
+
+    /// Get the current configuration as an [LcdConfig]. (See [from_config][LcdDisplay::from_config])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// let config = lcd.config();
+    /// ```
+    pub fn config(&self) -> LcdConfig {
+        LcdConfig {
+            mode: self.mode(),
+            lines: self.lines(),
+            size: self.size(),
+            display: self.display(),
+            cursor: self.cursor(),
+            blink: self.blink(),
+            layout: self.layout(),
+            autoscroll: self.autoscroll(),
+            cols: self.context.cols,
+        }
+    }
+
+    /// Take a snapshot of the current display settings (lines, size, display, cursor,
+    /// blink, layout, autoscroll and backlight). (See [restore][LcdDisplay::restore])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// let settings = lcd.settings();
+    /// eeprom.write(0, &settings.to_bytes());
+    /// ```
+    pub fn settings(&self) -> Settings {
+        Settings {
+            display_func: self.context.display_func,
+            display_ctrl: self.context.display_ctrl,
+            display_mode: self.context.display_mode,
+            backlight: self.backlight,
+        }
+    }
+
+    /// Re-apply a snapshot of the display settings taken by [settings][LcdDisplay::settings].
+    /// The bus mode is determined by the connected pins and is never changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// let settings = lcd.settings();
+    ///
+    /// lcd.reinit();
+    /// lcd.restore(settings);
+    /// ```
+    pub fn restore(&mut self, settings: Settings) {
+        let settings = settings.masked();
+        let mode = self.context.display_func & Mode::EightBits as u8;
+        self.context.display_ctrl = settings.display_ctrl;
+        self.context.display_mode = settings.display_mode;
+        self.set_function((settings.display_func & !(Mode::EightBits as u8)) | mode);
+
+        if settings.backlight {
+            self.backlight_on();
+        } else {
+            self.backlight_off();
+        }
+    }
+
+    /// Switch to the given settings, only sending the instructions whose bits changed.
+    /// Unlike [restore][LcdDisplay::restore], this sends nothing when the settings are
+    /// already in effect, which keeps the traffic down when moving between UI modes. A
+    /// changed function set (lines or character size) re-sends the whole configuration
+    /// like [set_lines][LcdDisplay::set_lines], since the controller only takes it at the
+    /// start of the initialization sequence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// let editing = Settings::new(
+    ///     lcd.function_set(),
+    ///     DisplayControl { display: Display::On, cursor: Cursor::On, blink: Blink::On },
+    ///     lcd.entry_mode(),
+    ///     Backlight::On,
+    /// );
+    ///
+    /// lcd.apply(editing); // only the display control instruction is sent
+    /// ```
+    pub fn apply(&mut self, settings: Settings) {
+        let settings = settings.masked();
+        let mode = self.context.display_func & Mode::EightBits as u8;
+        let display_func = (settings.display_func & !(Mode::EightBits as u8)) | mode;
+        if display_func != self.context.display_func {
+            self.context.display_ctrl = settings.display_ctrl;
+            self.context.display_mode = settings.display_mode;
+            self.set_function(display_func);
+        }
+
+        if settings.display_ctrl != self.context.display_ctrl {
+            self.context.display_ctrl = settings.display_ctrl;
+            self.command(Command::SetDisplayCtrl as u8 | self.context.display_ctrl);
+            self.wait(self.delays.command);
+        }
+
+        if settings.display_mode != self.context.display_mode {
+            self.context.display_mode = settings.display_mode;
+            self.command(Command::SetDisplayMode as u8 | self.context.display_mode);
+            self.wait(self.delays.command);
+        }
+
+        if settings.backlight != self.backlight {
+            match settings.backlight {
+                true => self.backlight_on(),
+                false => self.backlight_off(),
+            }
+        }
+    }
+
+    /// Get the controller chip the display is configured for. (See [with_controller][LcdDisplay::with_controller])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// let controller = lcd.controller();
+    /// ```
+    pub fn controller(&self) -> Controller {
+        self.context.controller
+    }
+
+    /// Get the character ROM the display is configured for. (See [with_rom][LcdDisplay::with_rom])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// let rom = lcd.rom();
+    /// ```
+    pub fn rom(&self) -> Rom {
+        self.context.rom
+    }
+
+    /// Set what [print_utf8][LcdDisplay::print_utf8] does with characters that aren't in
+    /// the character ROM.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.set_unmappable(Unmappable::Error);
+    /// ```
+    pub fn set_unmappable(&mut self, value: Unmappable) {
+        self.context.unmappable = value;
+    }
+
+    /// Get the number of columns. (See [with_cols][LcdDisplay::with_cols])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// let cols = lcd.cols();
+    /// ```
+    pub fn cols(&self) -> u8 {
+        self.context.cols
+    }
+
+    /// Get the number of rows for the configured number of lines. (See [with_lines][LcdDisplay::with_lines])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// let rows = lcd.rows();
+    /// ```
+    pub fn rows(&self) -> u8 {
+        match self.lines() {
+            Lines::FourLines => 4,
+            Lines::TwoLines => 2,
+            Lines::OneLine => 1,
+        }
+    }
+
+    /// Get the current error code. If an error occurs, the internal code will be
+    /// set to a value other than [Error::None][Error::None] (11u8).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// let code = lcd.error();
+    /// ```
+    pub fn error(&self) -> Error {
+        self.code.clone()
+    }
+
+    /// Reset the error code to [Error::None], after the cause has been dealt with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// if lcd.error() != Error::None {
+    ///     lcd.reinit();
+    ///     lcd.clear_error();
+    /// }
+    /// ```
+    pub fn clear_error(&mut self) {
+        self.code = Error::None;
+    }
+
+    /// Set or remove the error hook. (See [with_on_error][LcdDisplay::with_on_error])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.set_on_error(None);
+    /// ```
+    pub fn set_on_error(&mut self, hook: Option<fn(Error)>) {
+        self.on_error = hook;
+    }
+
+    /// Set or remove the hook called during blocking delays.
+    /// (See [with_yield_hook][LcdDisplay::with_yield_hook])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.set_yield_hook(None, 0);
+    /// ```
+    pub fn set_yield_hook<M: Microseconds>(&mut self, hook: Option<fn()>, interval: M) {
+        self.yielder.set(hook, interval.to_micros());
+    }
+
+    /// Set the retry policy. (See [with_retry_policy][LcdDisplay::with_retry_policy])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.set_retry_policy(RetryPolicy::new(2, 0));
+    /// ```
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry = policy;
+    }
+
+    /// Get the retry policy. (See [with_retry_policy][LcdDisplay::with_retry_policy])
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry
+    }
+
+    /// Get the number of commands, data bytes, retries and errors since the display was
+    /// created or the counters were reset. This method is only available if the `stats`
+    /// feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// let stats = lcd.stats();
+    /// ufmt::uwriteln!(serial, "{} commands, {} bytes", stats.commands, stats.data).ok();
+    /// ```
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// Reset every counter to zero. (See [stats][LcdDisplay::stats])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.reset_stats();
+    /// ```
+    #[cfg(feature = "stats")]
+    pub fn reset_stats(&mut self) {
+        self.stats = Stats::default();
+    }
+
+    /// Set the error code, calling the error hook if there was no error before.
+    fn fail(&mut self, code: Error) {
+        #[cfg(feature = "stats")]
+        {
+            self.stats.errors = self.stats.errors.wrapping_add(1);
+        }
+
+        if self.code == Error::None {
+            if let Some(hook) = self.on_error {
+                hook(code.clone());
+            }
+        }
+        self.code = code;
+    }
+
+    /// Get mutable access to the delay provider owned by the display, so it can be
+    /// reused by application code for its own short waits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.delay_mut().delay_ms(500);
+    /// ```
+    pub fn delay_mut(&mut self) -> &mut D {
+        &mut self.delay
+    }
+
+    /// Replace the delay provider owned by the display, returning the previous one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// let delay = lcd.replace_delay(other_delay);
+    /// ```
+    pub fn replace_delay(&mut self, delay: D) -> D {
+        core::mem::replace(&mut self.delay, delay)
+    }
+
+    /// Print a message to the LCD display.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.print("TEST MESSAGE");
+    /// ```
+    pub fn print(&mut self, text: &str) {
+        self.print_with(text, |lcd, ch| lcd.write(ch as u8));
+    }
+
+    /// Print characters from an iterator, for text that isn't stored as a `&str` (like
+    /// the output of a decoder). Each character is sent as its low byte, like
+    /// [print][LcdDisplay::print] does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.print_iter("TEST MESSAGE".chars().rev());
+    /// ```
+    pub fn print_iter<I>(&mut self, chars: I)
+    where
+        I: IntoIterator<Item = char>,
+    {
+        self.print_chars(chars.into_iter(), |lcd, ch| lcd.write(ch as u8));
+    }
+
+    /// Print bytes from an iterator, for text read from a buffer or program memory one
+    /// byte at a time. Bytes are sent unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.print_bytes(buffer.iter().copied());
+    /// ```
+    pub fn print_bytes<I>(&mut self, bytes: I)
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        self.print_iter(bytes.into_iter().map(char::from));
+    }
+
+    /// Print characters from an iterator, converting each one to its code in the
+    /// [character ROM][LcdDisplay::with_rom]. (See [print_utf8][LcdDisplay::print_utf8])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.print_utf8_iter(decoder.chars());
+    /// ```
+    pub fn print_utf8_iter<I>(&mut self, chars: I)
+    where
+        I: IntoIterator<Item = char>,
+    {
+        self.print_chars(chars.into_iter(), Self::write_char);
+    }
+
+    /// Print a message to the LCD display, converting each character to its code in the
+    /// [character ROM][LcdDisplay::with_rom].
+    ///
+    /// Unlike [print][LcdDisplay::print], which sends the low byte of each character,
+    /// characters like `°`, `µ` or `→` are shown correctly, and characters that aren't in
+    /// the ROM are handled as set by [with_unmappable][LcdDisplay::with_unmappable]
+    /// instead of showing up as random symbols.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.print_utf8("21.5°C → 5µA");
+    /// ```
+    pub fn print_utf8(&mut self, text: &str) {
+        self.print_with(text, Self::write_char);
+    }
+
+    /// Print a degree sign using the code for the [character ROM][LcdDisplay::with_rom].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.print("21.5");
+    /// lcd.print_degree();
+    /// lcd.print("C");
+    /// ```
+    pub fn print_degree(&mut self) {
+        self.write_char('°');
+    }
+
+    /// Print an ohm sign using the code for the [character ROM][LcdDisplay::with_rom].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.print("470");
+    /// lcd.print_ohm();
+    /// ```
+    pub fn print_ohm(&mut self) {
+        self.write_char('Ω');
+    }
+
+    /// Print a micro sign using the code for the [character ROM][LcdDisplay::with_rom].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.print("10");
+    /// lcd.print_micro();
+    /// lcd.print("F");
+    /// ```
+    pub fn print_micro(&mut self) {
+        self.write_char('µ');
+    }
+
+    /// Print a pi sign using the code for the [character ROM][LcdDisplay::with_rom].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.print_pi();
+    /// ```
+    pub fn print_pi(&mut self) {
+        self.write_char('π');
+    }
+
+    /// Print an arrow using the code for the [character ROM][LcdDisplay::with_rom]. Arrows
+    /// that the ROM doesn't have are handled as set by [with_unmappable][LcdDisplay::with_unmappable].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.print_arrow(Arrow::Right);
+    /// lcd.print("Next");
+    /// ```
+    pub fn print_arrow(&mut self, arrow: Arrow) {
+        self.write_char(arrow.as_char());
+    }
+
+    /// Print Japanese text to a display with the A00 character ROM. Full-width katakana
+    /// and hiragana are shown as half-width katakana, with voiced sounds (like `ガ`)
+    /// printed as the base character followed by a voicing mark. Other characters are
+    /// printed as with [print_utf8][LcdDisplay::print_utf8].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.print_kana("コンニチハ");
+    /// lcd.print_kana("ありがとう");
+    /// ```
+    pub fn print_kana(&mut self, text: &str) {
+        self.print_with(text, Self::write_kana);
+    }
+
+    /// Write a kana character with its voicing mark, or any other character converted
+    /// to the character ROM.
+    fn write_kana(&mut self, ch: char) {
+        match self.context.rom.encode_kana(ch) {
+            Some((code, mark)) => {
+                self.write(code);
+                if let Some(mark) = mark {
+                    self.write(mark);
+                }
+            }
+            None => self.write_char(ch),
+        }
+    }
+
+    /// Write a character converted to the character ROM, applying the unmappable policy.
+    fn write_char(&mut self, ch: char) {
+        match (self.context.rom.encode(ch), self.context.unmappable) {
+            (Some(code), _) | (None, Unmappable::Replace(code)) => self.write(code),
+            (None, Unmappable::Skip) => {}
+            (None, Unmappable::Error) => self.fail(Error::Unmappable),
+        }
+    }
+
+    /// Print a message using `write` for each character, wrapping words
+    /// if [Wrap::Word] is set.
+    fn print_with(&mut self, text: &str, write: fn(&mut Self, char)) {
+        self.print_chars(text.chars(), write);
+    }
+
+    /// Print characters from an iterator using `write` for each one, wrapping words
+    /// if [Wrap::Word] is set.
+    fn print_chars<I>(&mut self, chars: I, write: fn(&mut Self, char))
+    where
+        I: Iterator<Item = char>,
+    {
+        if self.context.wrap != Wrap::Word || self.layout() != Layout::LeftToRight {
+            for ch in chars {
+                write(self, ch);
+            }
+            return;
+        }
+
+        let mut chars = chars.peekable();
+        while let Some(ch) = chars.next() {
+            if ch == ' ' {
+                // a space that falls past the last column would only push
+                // the next word along, so it is dropped at the line break
+                match self.context.col >= self.context.cols {
+                    true => self.next_row(),
+                    false => write(self, ch),
+                }
+                continue;
+            }
+
+            // buffer the word, since its length has to be known before it is written
+            let mut word = [' '; WORD_LEN];
+            word[0] = ch;
+            let mut len = 1;
+            while len < WORD_LEN {
+                match chars.next_if(|c| *c != ' ') {
+                    Some(c) => word[len] = c,
+                    None => break,
+                }
+                len += 1;
+            }
+
+            // move words that don't fit on the rest of this row (but
+            // would fit on an empty one) down to the next row
+            let end = self.context.col as usize + len;
+            if self.context.col > 0
+                && end > self.context.cols as usize
+                && len <= self.context.cols as usize
+            {
+                self.next_row();
+            }
+
+            for ch in &word[..len] {
+                write(self, *ch);
+            }
+
+            // the rest of a word that is too long for any row
+            while let Some(c) = chars.next_if(|c| *c != ' ') {
+                write(self, c);
+            }
+        }
+    }
+
+    /// Print text in a right-to-left script (like Hebrew or Arabic, as far as the
+    /// [character ROM][LcdDisplay::with_rom] has its letters) so it reads correctly
+    /// whatever the [Layout] is. The first character goes at the cursor and the rest
+    /// run to the left of it, while numbers keep reading left to right and brackets
+    /// are mirrored. Characters that would pass the first column are dropped, and the
+    /// cursor is left just before the text (or in the first column) for whatever
+    /// comes next.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.set_position(15, 0);
+    /// lcd.print_rtl("HELLO 123"); // shows "123 OLLEH" ending in the last column
+    /// ```
+    pub fn print_rtl(&mut self, text: &str) {
+        let layout = self.layout();
+        let (col, row) = (self.context.col, self.context.row);
+
+        let width = text.chars().filter(|ch| self.prints(*ch)).count();
+        let start = (col as usize + 1).saturating_sub(width);
+        let mut skip = width - (col as usize + 1 - start);
+
+        if layout == Layout::RightToLeft {
+            self.set_layout(Layout::LeftToRight);
+        }
+        self.set_position(start as u8, row);
+
+        // write the text in visual order, from its last character back to its first
+        let mut rest = text;
+        while let Some(ch) = rest.chars().next_back() {
+            if ch.is_ascii_digit() {
+                // numbers read left to right even in right to left scripts
+                let head = rest.trim_end_matches(|c: char| c.is_ascii_digit());
+                for digit in rest[head.len()..].chars() {
+                    self.write_rtl(digit, &mut skip);
+                }
+                rest = head;
+            } else {
+                self.write_rtl(ch, &mut skip);
+                rest = &rest[..rest.len() - ch.len_utf8()];
+            }
+        }
+
+        if layout == Layout::RightToLeft {
+            self.set_layout(layout);
+        }
+        self.set_position((start as u8).saturating_sub(1), row);
+    }
+
+    /// Write one character of right to left text, unless it's one of the first `skip`
+    /// characters (in visual order) that don't fit.
+    fn write_rtl(&mut self, ch: char, skip: &mut usize) {
+        if !self.prints(ch) {
+            return;
+        }
+
+        if *skip > 0 {
+            *skip -= 1;
+            return;
+        }
+
+        let ch = match ch {
+            '(' => ')',
+            ')' => '(',
+            '[' => ']',
+            ']' => '[',
+            '{' => '}',
+            '}' => '{',
+            '<' => '>',
+            '>' => '<',
+            c => c,
+        };
+        self.write_char(ch);
+    }
+
+    /// Check if [write_char][LcdDisplay::write_char] puts `ch` on the display.
+    fn prints(&self, ch: char) -> bool {
+        self.context.rom.encode(ch).is_some()
+            || matches!(self.context.unmappable, Unmappable::Replace(_))
+    }
+
+    /// Move the cursor to a position and print a message there. (See [set_position][LcdDisplay::set_position])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.print_at(0, 1, "SECOND ROW");
+    /// ```
+    pub fn print_at(&mut self, col: u8, row: u8, text: &str) {
+        self.set_position(col, row);
+        self.print(text);
+    }
+
+    /// Print a message down column `col`, one character per row starting at the top
+    /// row, for side labels on four-line displays. Characters that don't fit above the
+    /// bottom of the display are cut off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.print_vertical(19, "TEMP");
+    /// ```
+    pub fn print_vertical(&mut self, col: u8, text: &str) {
+        for (row, ch) in (0..self.rows()).zip(text.chars()) {
+            self.set_position(col, row);
+            self.write(ch as u8);
+        }
+    }
+
+    /// Move the cursor to a position, run `f`, and then move the cursor back to where it
+    /// was before. This makes it easy to update one part of the screen (like a clock in a
+    /// corner) without breaking the flow of text printed elsewhere.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.print("Log: ");
+    /// lcd.with_position(11, 0, |lcd| lcd.print("12:00"));
+    /// lcd.print("started"); // continues after "Log: "
+    /// ```
+    pub fn with_position<F, R>(&mut self, col: u8, row: u8, f: F) -> R
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        let (prev_col, prev_row) = self.position();
+        self.set_position(col, row);
+        let result = f(self);
+        self.set_position(prev_col, prev_row);
+        result
+    }
+
+    /// Write a single character to the LCD display.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.write('A' as u8);
+    /// ```
+    pub fn write(&mut self, value: u8) {
+        self.write_with(value, Self::data);
+    }
+
+    /// Write a single character using `data` to send it, wrapping and
+    /// tracking the cursor and display shift.
+    fn write_with(&mut self, value: u8, data: fn(&mut Self, u8)) {
+        self.context.activity = true;
+        self.undim();
+
+        if self.context.wrap != Wrap::Off
+            && self.layout() == Layout::LeftToRight
+            && self.context.col >= self.context.cols
+        {
+            self.next_row();
+        }
+
+        data(self, value);
+
+        match self.layout() {
+            Layout::LeftToRight => self.context.col = self.context.col.saturating_add(1),
+            Layout::RightToLeft => self.context.col = self.context.col.saturating_sub(1),
+        }
+
+        // with autoscroll on, every write to DDRAM shifts the display
+        // against the direction of the text
+        if (self.context.display_mode & AutoScroll::On as u8) != 0 {
+            match self.layout() {
+                Layout::LeftToRight => self.shift_by(-1),
+                Layout::RightToLeft => self.shift_by(1),
+            }
+        }
+    }
+
+    /// Block for `us` microseconds, calling the yield hook each time the interval set by
+    /// [with_yield_hook][LcdDisplay::with_yield_hook] has passed.
+    fn pause(&mut self, us: u32) {
+        self.yielder.pause(&mut self.delay, us);
+    }
+
+    /// Wait until the display is ready: by polling the busy flag if
+    /// [with_busy_flag][LcdDisplay::with_busy_flag] is used, or for `us` microseconds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// self.wait(self.delays.command);
+    /// ```
+    fn wait(&mut self, us: u32) {
+        let busy = match self.busy {
+            Some(busy) if self.exists(RW) => busy,
+            _ => return self.pause(us),
+        };
+
+        let mut waited = 0;
+        while busy(self) {
+            if waited >= self.busy_timeout {
+                // the display isn't answering, so stop relying on it
+                self.busy = None;
+                self.fail(Error::Timeout);
+                self.pause(us);
+                return;
+            }
+            self.pause(BUSY_POLL);
+            waited += BUSY_POLL;
+        }
+    }
+
+    /// Write a single byte to the currently selected memory (DDRAM or CGRAM).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// self.data(value);
+    /// ```
+    fn data(&mut self, value: u8) {
+        self.send(value, true);
+        self.wait(self.delays.character);
+    }
+
+    /// Start writing a single character without blocking. `now` is a timestamp in
+    /// (wrapping) microseconds, like the value of a free-running timer.
+    ///
+    /// Each call sends at most one nibble (in four-bit mode) or byte (in eight-bit mode).
+    /// While the previous character is still being sent, or the delay after it hasn't
+    /// passed, this returns `WouldBlock` and the character has to be written again.
+    /// Moving to the next row when the text wraps still blocks for a command delay.
+    /// This method is only available if the `nb` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// let mut text = b"Hello".iter().peekable();
+    ///
+    /// loop {
+    ///     if let Some(&&ch) = text.peek() {
+    ///         if lcd.write_nb(ch, micros()).is_ok() {
+    ///             text.next();
+    ///         }
+    ///     }
+    ///     // do other work
+    /// }
+    /// ```
+    #[cfg(feature = "nb")]
+    pub fn write_nb(&mut self, value: u8, now: u32) -> nb::Result<(), core::convert::Infallible> {
+        self.flush_nb(now)?;
+        self.write_with(value, Self::data_nb);
+        self.context.ready_at = Some(now.wrapping_add(self.delays.character));
+        Ok(())
+    }
+
+    /// Finish sending the last character written with [write_nb][LcdDisplay::write_nb]
+    /// without blocking. Returns `WouldBlock` until the character has been sent and the
+    /// delay after it has passed. This method is only available if the `nb` feature
+    /// is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.write_nb(b'A', micros()).ok();
+    /// nb::block!(lcd.flush_nb(micros())).ok();
+    /// ```
+    #[cfg(feature = "nb")]
+    pub fn flush_nb(&mut self, now: u32) -> nb::Result<(), core::convert::Infallible> {
+        if let Some(nibble) = self.context.pending.take() {
+            self.update(nibble);
+            self.context.ready_at = Some(now.wrapping_add(self.delays.character));
+            return Err(nb::Error::WouldBlock);
+        }
+
+        match self.context.ready_at {
+            Some(at) if (now.wrapping_sub(at) as i32) < 0 => Err(nb::Error::WouldBlock),
+            _ => {
+                self.context.ready_at = None;
+                Ok(())
+            }
+        }
+    }
+
+    /// Send the first part of a character, leaving the low nibble in four-bit
+    /// mode for [flush_nb][LcdDisplay::flush_nb].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// self.data_nb(value);
+    /// ```
+    #[cfg(feature = "nb")]
+    fn data_nb(&mut self, value: u8) {
+        self.select(value, true);
+        match self.mode() {
+            Mode::FourBits => {
+                self.update(value >> 4);
+                self.context.pending = Some(value);
+            }
+            Mode::EightBits => {
+                self.update(value);
+            }
+        }
+    }
+
+    /// Move the cursor to the start of the next row, returning to the
+    /// first row after the last one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// self.next_row();
+    /// ```
+    fn next_row(&mut self) {
+        let row = (self.context.row + 1) % self.rows();
+        self.set_position(0, row);
+    }
+
+    /// Get the delay after clear and home for the timing and controller.
+    fn clear_delay(&self) -> u32 {
+        self.delays.clear.max(self.context.controller.clear_delay())
+    }
+
+    /// Add `delta` positions to the tracked display shift, wrapping at the
+    /// length of a DDRAM line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// self.shift_by(-1);
+    /// ```
+    fn shift_by(&mut self, delta: i16) {
+        self.context.shift = ((self.context.shift as i16 + delta) % DDRAM_COLS) as i8;
+    }
+
+    /// Measure how fast characters and commands are sent to the display, using `now` as a
+    /// source of timestamps in microseconds (which may wrap).
+    ///
+    /// This is useful for tuning delays or comparing a GPIO connection with I2C. The display
+    /// is cleared before and after the measurement.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// let result = lcd.benchmark(|| micros());
+    /// uwriteln!(&mut serial, "{} chars/s, {} us/command", result.chars_per_second, result.command_us);
+    /// ```
+    pub fn benchmark<F>(&mut self, mut now: F) -> Benchmark
+    where
+        F: FnMut() -> u32,
+    {
+        self.clear();
+
+        let start = now();
+        for _ in 0..BENCH_CHARS {
+            self.write(b'#');
+        }
+        let chars = now().wrapping_sub(start).max(1);
+
+        let start = now();
+        for _ in 0..BENCH_COMMANDS {
+            self.set_position(0, 0);
+        }
+        let commands = now().wrapping_sub(start);
+
+        self.clear();
+
+        Benchmark {
+            chars_per_second: (BENCH_CHARS as u64 * 1_000_000 / chars as u64) as u32,
+            command_us: commands / BENCH_COMMANDS,
+        }
+    }
+
+    /// Show one page of the character ROM: codes `page * 16` to `page * 16 + 15` on the
+    /// first row, and their range (like `Codes 0x40-0x4F`) on the second. Pages go from
+    /// 0 to 15, and this needs a display with at least 16 columns and two rows.
+    ///
+    /// Codes 0x00-0x0F show the custom characters in CGRAM rather than the ROM.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.rom_page(0xD); // 0xD0-0xDF, katakana on an A00 ROM
+    /// ```
+    pub fn rom_page(&mut self, page: u8) {
+        let first = (page & 0x0F) * ROM_PAGE;
+
+        self.clear();
+        for code in first..=first + (ROM_PAGE - 1) {
+            self.write(code);
+        }
+
+        let hex = |value: u8| match value & 0x0F {
+            digit @ 0..=9 => b'0' + digit,
+            digit => b'A' + digit - 10,
+        };
+
+        self.set_position(0, 1);
+        self.print("Codes 0x");
+        self.write(hex(first >> 4));
+        self.write(hex(first));
+        self.print("-0x");
+        self.write(hex(first >> 4));
+        self.write(hex(ROM_PAGE - 1));
+    }
+
+    /// Page through the whole character ROM with [rom_page][LcdDisplay::rom_page],
+    /// showing each page for `pause_ms` milliseconds, so the ROM variant (A00, A02 or
+    /// another) fitted to the display can be identified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.rom_dump(3000);
+    /// ```
+    pub fn rom_dump(&mut self, pause_ms: u32) {
+        for page in 0..ROM_PAGE {
+            self.rom_page(page);
+            self.pause(pause_ms.saturating_mul(1000));
+        }
+        self.clear();
+    }
+
+    /// Merge two rows into a single row with a double-height font, for headings. Text for
+    /// a double-height row is written to the first of the two rows it covers.
+    ///
+    /// This only works with [Controller::St7036], and is kept when the display is
+    /// initialized again. Other controllers use the same bits for other settings, so
+    /// nothing is sent to them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.set_double_height(DoubleHeight::Top);
+    /// lcd.print_at(0, 0, "ALARM");
+    /// ```
+    pub fn set_double_height(&mut self, rows: DoubleHeight) {
+        if self.context.controller == Controller::St7036 {
+            self.context.double_height = rows;
+            self.send_double_height();
+        }
+    }
+
+    /// Get the rows that use a double-height font.
+    /// (See [set_double_height][LcdDisplay::set_double_height])
+    pub fn double_height(&self) -> DoubleHeight {
+        self.context.double_height
+    }
+
+    /// Send the double height setting of an ST7036 and go back to the normal
+    /// instruction table.
+    fn send_double_height(&mut self) {
+        let position = match self.context.double_height {
+            DoubleHeight::Off => return self.select_table(0),
+            DoubleHeight::Top => St7036::Upper as u8,
+            DoubleHeight::Bottom => 0,
+        };
+
+        // the position is selected in instruction table 2
+        self.select_table(St7036::Table2 as u8);
+        self.command(Command::CursorShift as u8 | position);
+        self.wait(self.delays.command);
+        self.select_table(0);
+    }
+
+    /// Show the segments of ICON RAM, on controllers that have it (see
+    /// [set_icon][LcdDisplay::set_icon]).
+    pub fn icons_on(&mut self) {
+        self.set_icons(true);
+    }
+
+    /// Hide the segments of ICON RAM, on controllers that have it (see
+    /// [set_icon][LcdDisplay::set_icon]).
+    pub fn icons_off(&mut self) {
+        self.set_icons(false);
+    }
+
+    /// Show or hide the icons and send the setting.
+    fn set_icons(&mut self, value: bool) {
+        if self.context.controller.has_icons() {
+            self.context.icons = value;
+            self.send_power_icon();
+        }
+    }
+
+    /// Set the icon segments at an ICON RAM `address` (0-15), where each of the five
+    /// low bits of `segments` is one segment. Which address and bit drives which icon
+    /// (battery, antenna, bell, ...) depends on the glass of the module, so check its
+    /// datasheet. Icons are only visible after [icons_on][LcdDisplay::icons_on].
+    ///
+    /// This only works with [Controller::St7036] and [Controller::Splc792], other
+    /// controllers don't have ICON RAM so nothing is sent to them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.icons_on();
+    /// lcd.set_icon(0x0D, 0b10000); // battery outline on a DOGM162
+    /// ```
+    pub fn set_icon(&mut self, address: u8, segments: u8) {
+        if !self.context.controller.has_icons() {
+            return;
+        }
+        if address >= ICON_ADDRESSES {
+            self.fail(Error::OutOfBounds);
+            return;
+        }
+
+        self.select_table(St7036::Table1 as u8);
+        self.command(St7036::IconAddr as u8 | address);
+        self.wait(self.delays.command);
+        self.data(segments & 0x1F);
+        self.select_table(0);
+        self.set_position(self.context.col, self.context.row);
+    }
+
+    /// Turn off every icon segment by clearing ICON RAM. (See [set_icon][LcdDisplay::set_icon])
+    pub fn clear_icons(&mut self) {
+        if !self.context.controller.has_icons() {
+            return;
+        }
+
+        // the address counter moves to the next icon address after each write
+        self.select_table(St7036::Table1 as u8);
+        self.command(St7036::IconAddr as u8);
+        self.wait(self.delays.command);
+        for _ in 0..ICON_ADDRESSES {
+            self.data(0);
+        }
+        self.select_table(0);
+        self.set_position(self.context.col, self.context.row);
+    }
+
+    /// Set the contrast of the display (0-63) on controllers with a built-in contrast
+    /// generator. The contrast shares an instruction with the icon setting, so it is
+    /// also sent by [icons_on][LcdDisplay::icons_on] and [icons_off][LcdDisplay::icons_off]
+    /// (with a contrast of 32 if none was set).
+    ///
+    /// This only works with [Controller::St7036] and [Controller::Splc792].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.set_contrast(40);
+    /// ```
+    pub fn set_contrast(&mut self, value: u8) {
+        if self.context.controller.has_icons() {
+            self.context.contrast = Some(value.min(63));
+            self.send_power_icon();
+        }
+    }
+
+    /// Send the icon, booster and contrast settings of an ST7036 or SPLC792 and go back
+    /// to the normal instruction table.
+    fn send_power_icon(&mut self) {
+        let contrast = self.context.contrast.unwrap_or(DEFAULT_CONTRAST);
+        let mut power = St7036::PowerIcon as u8 | (contrast >> 4);
+        if self.context.icons {
+            power |= PowerIcon::IconOn as u8;
+        }
+        if self.context.booster {
+            power |= PowerIcon::Booster as u8;
+        }
+
+        self.select_table(St7036::Table1 as u8);
+        self.command(power);
+        self.wait(self.delays.command);
+        self.command(St7036::Contrast as u8 | (contrast & 0x0F));
+        self.wait(self.delays.command);
+        self.select_table(0);
+    }
+
+    /// Send a function set for an ST7036 or SPLC792 that keeps the bus, line and
+    /// double height settings and selects an instruction `table`.
+    fn select_table(&mut self, table: u8) {
+        let mut function =
+            Command::SetDisplayFunc as u8 | (self.context.display_func & 0x18) | table;
+        if self.context.double_height != DoubleHeight::Off {
+            function |= St7036::DoubleHeight as u8;
+        }
+        self.command(function);
+        self.wait(self.delays.command);
+    }
+
+    /// Switch a WS0010 OLED display into graphic mode, where every pixel can be set with
+    /// [write_graphic][LcdDisplay::write_graphic]. Text written before switching is not
+    /// shown until [character_mode][LcdDisplay::character_mode] is called.
+    ///
+    /// This only works with [Controller::Ws0010]; other controllers interpret the
+    /// command as a cursor shift, so nothing is sent to them and the error code is set
+    /// to [Error::Unsupported].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.graphic_mode();
+    /// lcd.set_graphic_position(0, 0);
+    /// lcd.write_graphic(&[0xFF, 0x81, 0x81, 0xFF]); // a small box
+    /// ```
+    pub fn graphic_mode(&mut self) {
+        if self.context.controller != Controller::Ws0010 {
+            self.fail(Error::Unsupported);
+            return;
+        }
+        self.command(Command::CursorShift as u8 | Ws0010Mode::Graphic as u8);
+        self.wait(self.delays.command);
+    }
+
+    /// Switch a WS0010 OLED display back into character mode and restore the cursor
+    /// position. Like [graphic_mode][LcdDisplay::graphic_mode], other controllers set
+    /// the error code to [Error::Unsupported].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.character_mode();
+    /// lcd.print("Back to text");
+    /// ```
+    pub fn character_mode(&mut self) {
+        if self.context.controller != Controller::Ws0010 {
+            self.fail(Error::Unsupported);
+            return;
+        }
+        self.command(Command::CursorShift as u8 | Ws0010Mode::Character as u8);
+        self.wait(self.delays.command);
+        self.set_position(self.context.col, self.context.row);
+    }
+
+    /// Set the pixel column `x` and the eight-pixel-high row `row` that the next call to
+    /// [write_graphic][LcdDisplay::write_graphic] draws at, in graphic mode. On
+    /// controllers other than [Controller::Ws0010] nothing is sent and the error code is
+    /// set to [Error::Unsupported].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.set_graphic_position(50, 1);
+    /// ```
+    pub fn set_graphic_position(&mut self, x: u8, row: u8) {
+        if self.context.controller != Controller::Ws0010 {
+            self.fail(Error::Unsupported);
+            return;
+        }
+        self.command(Command::SetDDRAMAddr as u8 | (x & 0x7F));
+        self.wait(self.delays.command);
+        self.command(Command::SetCGramAddr as u8 | (row & 0x01));
+        self.wait(self.delays.command);
+    }
+
+    /// Write columns of pixels in graphic mode, starting at the position set by
+    /// [set_graphic_position][LcdDisplay::set_graphic_position]. Each byte is a column of
+    /// eight pixels with the least significant bit at the top. On controllers other than
+    /// [Controller::Ws0010] nothing is sent and the error code is set to
+    /// [Error::Unsupported], since the bytes would be printed as characters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.write_graphic(&[0x18, 0x3C, 0x7E, 0xFF]); // a triangle
+    /// ```
+    pub fn write_graphic(&mut self, columns: &[u8]) {
+        if self.context.controller != Controller::Ws0010 {
+            self.fail(Error::Unsupported);
+            return;
+        }
+        for column in columns {
+            self.data(*column);
+        }
+    }
+
+    /// Send a raw instruction byte to the LCD controller, followed by the standard
+    /// command delay.
+    ///
+    /// This is an escape hatch for controller-specific extensions (extended instruction
+    /// sets, vendor quirks) that the driver doesn't know about. The driver's tracked state
+    /// (settings, cursor position, display shift) is not updated, so prefer the dedicated
+    /// methods for anything they already cover.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.raw_command(0x2A); // function set with the RE bit on some KS0073 clones
+    /// ```
+    pub fn raw_command(&mut self, value: u8) {
+        self.command(value);
+        self.wait(self.delays.command);
+    }
+
+    /// Send a raw data byte to the currently selected memory (DDRAM or CGRAM) without
+    /// updating the driver's tracked cursor position or display shift.
+    /// (See [raw_command][LcdDisplay::raw_command])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.raw_data(0xFF);
+    /// ```
+    pub fn raw_data(&mut self, value: u8) {
+        self.data(value);
+    }
+
+    /// Execute a command on the LCD display, usually by using bitwise OR to combine
+    /// flags in various ways.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// self.command(Command::SetDisplayCtrl as u8 | self.context.display_ctrl);
+    /// ```
+    fn command(&mut self, value: u8) {
+        self.send(value, false);
+    }
+
+    /// Send bytes to the LCD display with the RS pin set either high (for commands)
+    /// or low (to write to memory)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// self.send(value, true);
+    /// ```
+    fn send(&mut self, byte: u8, mode: bool) {
+        // where the byte belongs, in case it has to be sent again
+        let address = self.address();
+
+        let mut attempts = self.retry.attempts.max(1);
+        self.watching = true;
+        loop {
+            self.transfer(byte, mode);
+
+            let Some(error) = self.fault.take() else {
+                break;
+            };
+
+            attempts -= 1;
+            if attempts == 0 {
+                self.watching = false;
+                self.fail(error);
+                break;
+            }
+            self.backoff();
+            self.realign(mode.then_some(address));
+        }
+        self.watching = false;
+
+        self.track(byte, mode);
+    }
+
+    /// Send a byte once, without retrying. (See [send][LcdDisplay::send])
+    fn transfer(&mut self, byte: u8, mode: bool) {
+        self.select(byte, mode);
+
+        #[cfg(feature = "timing-audit")]
+        self.audit_ready();
+
+        match self.mode() {
+            Mode::FourBits => {
+                self.update(byte >> 4);
+                self.update(byte);
+            }
+            Mode::EightBits => {
+                self.update(byte);
+            }
+        }
+
+        #[cfg(feature = "timing-audit")]
+        if let Some(audit) = self.audit.as_mut() {
+            audit.sent = (audit.clock)();
+            audit.execute = match (mode, byte) {
+                (false, 0x01..=0x03) => AUDIT_CLEAR,
+                _ => AUDIT_EXECUTE,
+            };
+        }
+    }
+
+    /// Get the instruction that points the address counter at the current position,
+    /// in CGRAM if a custom character is being written or DDRAM otherwise.
+    fn address(&self) -> u8 {
+        match self.context.cgram {
+            Some(address) => Command::SetCGramAddr as u8 | address,
+            None => {
+                let offset = self.row_offsets()[self.context.row.min(3) as usize];
+                Command::SetDDRAMAddr as u8 | (offset.wrapping_add(self.context.col) & 0x7F)
+            }
+        }
+    }
+
+    /// Follow the CGRAM address counter, which the cursor position doesn't cover, and the
+    /// selected instruction table, since other tables reuse the address commands.
+    fn track(&mut self, byte: u8, mode: bool) {
+        match (mode, self.context.cgram) {
+            (true, Some(address)) => {
+                let next = match self.layout() {
+                    Layout::LeftToRight => address.wrapping_add(1),
+                    Layout::RightToLeft => address.wrapping_sub(1),
+                };
+                self.context.cgram = Some(next & 0x3F);
+            }
+            (true, None) => {}
+            (false, _) if byte & Command::SetDDRAMAddr as u8 != 0 => self.context.cgram = None,
+            (false, _) if byte & Command::SetCGramAddr as u8 != 0 => {
+                if self.context.table == 0 {
+                    self.context.cgram = Some(byte & 0x3F);
+                }
+            }
+            (false, _) if byte & Command::SetDisplayFunc as u8 != 0 => {
+                self.context.table = self.context.controller.instruction_table(byte);
+            }
+            (false, _) if (0x01..=0x03).contains(&byte) => self.context.cgram = None,
+            (false, _) => {}
+        }
+    }
+
+    /// Get the display back in step after a failed transfer: synchronize the nibbles
+    /// again (in four-bit mode), repeat the function set and, for a character, point
+    /// the address counter back at `address`.
+    fn realign(&mut self, address: Option<u8>) {
+        self.set(RS, false);
+        if self.exists(RW) {
+            self.set(RW, false);
+        }
+
+        if self.mode() == Mode::FourBits {
+            // whichever nibble the display expects, this ends in eight-bit mode
+            // and then switches back to four bits
+            self.update(0x03);
+            self.pause(4500);
+            self.update(0x03);
+            self.pause(150);
+            self.update(0x03);
+            self.pause(150);
+            self.update(0x02);
+            self.pause(self.delays.command);
+        }
+
+        self.transfer(self.function_command(), false);
+        self.pause(self.delays.command);
+
+        if let Some(address) = address {
+            self.transfer(address, false);
+            self.pause(self.delays.command);
+        }
+    }
+
+    /// Record an error from a pin or the bus, or hold on to it while a failed transfer
+    /// can still be retried.
+    fn pin_fault(&mut self, error: Error) {
+        match self.watching {
+            true => {
+                self.fault.get_or_insert(error);
+            }
+            false => self.fail(error),
+        }
+    }
+
+    /// Check that the last instruction had time to execute before the next one is sent.
+    #[cfg(feature = "timing-audit")]
+    fn audit_ready(&mut self) {
+        // the busy flag is the controller's own answer, so it overrides the datasheet
+        if self.busy.is_some() && self.exists(RW) {
+            return;
+        }
+
+        if let Some(audit) = self.audit {
+            let elapsed = (audit.clock)().wrapping_sub(audit.sent);
+            debug_assert!(
+                elapsed >= audit.execute,
+                "instruction sent {} ns after the last one, which needs {} ns",
+                elapsed,
+                audit.execute
+            );
+        }
+    }
+
+    /// Prepare to send a byte by setting the RS and RW pins, after finishing
+    /// any character left half-sent by [write_nb][LcdDisplay::write_nb].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// self.select(value, true);
+    /// ```
+    #[cfg_attr(not(feature = "trace"), allow(unused_variables))]
+    fn select(&mut self, byte: u8, mode: bool) {
+        #[cfg(feature = "nb")]
+        if let Some(nibble) = self.context.pending.take() {
+            self.update(nibble);
+        }
+
+        #[cfg(feature = "stats")]
+        match mode {
+            true => self.stats.data = self.stats.data.wrapping_add(1),
+            false => self.stats.commands = self.stats.commands.wrapping_add(1),
+        }
+
+        #[cfg(feature = "trace")]
+        if let Some(hook) = self.trace {
+            hook(match mode {
+                true => Transfer::Data(byte),
+                false => Transfer::Command(byte),
+            });
+        }
+
+        self.set(RS, mode);
+
+        if self.exists(RW) {
+            self.set(RW, false);
+        }
+    }
+
+    /// Move the bits of each nibble of `byte` to the data lines they are wired to.
+    /// (See [with_data_order][LcdDisplay::with_data_order])
+    fn remap(&self, byte: u8) -> u8 {
+        let mut wired = 0;
+        for (line, bit) in (0..).zip(self.data_order) {
+            for half in [0, 4] {
+                wired |= ((byte >> (half + bit)) & 1) << (half + line);
+            }
+        }
+        wired
+    }
+
+    /// Move bits read from the data lines back to their place in each nibble, undoing
+    /// [remap][LcdDisplay::remap].
+    fn unmap(&self, wired: u8) -> u8 {
+        let mut byte = 0;
+        for (line, bit) in (0..).zip(self.data_order) {
+            for half in [0, 4] {
+                byte |= ((wired >> (half + line)) & 1) << (half + bit);
+            }
+        }
+        byte
+    }
+
+    /// Update the on-device memory by sending either the bottom nibble (in
+    /// four-bit mode) or a whole byte (in eight-bit) and then pulsing the enable pin.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// self.update(byte);
+    /// ```
+    fn update(&mut self, byte: u8) {
+        #[cfg(feature = "critical-pulse")]
+        critical_section::with(|_| self.latch(byte));
+
+        #[cfg(not(feature = "critical-pulse"))]
+        self.latch(byte);
+    }
+
+    /// Set the data lines and pulse the enable pin. (See [update][LcdDisplay::update])
+    fn latch(&mut self, byte: u8) {
+        let byte = self.remap(byte);
+        match self.mode() {
+            Mode::FourBits => {
+                self.set(D7, (byte >> 3) & 1 > 0);
+                self.set(D6, (byte >> 2) & 1 > 0);
+                self.set(D5, (byte >> 1) & 1 > 0);
+                self.set(D4, (byte >> 0) & 1 > 0);
+            }
+            Mode::EightBits => {
+                self.set(D7, (byte >> 7) & 1 > 0);
+                self.set(D6, (byte >> 6) & 1 > 0);
+                self.set(D5, (byte >> 5) & 1 > 0);
+                self.set(D4, (byte >> 4) & 1 > 0);
+                self.set(D3, (byte >> 3) & 1 > 0);
+                self.set(D2, (byte >> 2) & 1 > 0);
+                self.set(D1, (byte >> 1) & 1 > 0);
+                self.set(D0, (byte >> 0) & 1 > 0);
+            }
+        };
+
+        // with a port writer, this sends the data with enable low so
+        // it is stable before the pulse
+        self.set(EN, false);
+        self.pulse();
+    }
+
+    /// Set the enable pin high and then low to make the LCD accept the most
+    /// recently transmitted data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// self.pulse();
+    /// ```
+    fn pulse(&mut self) {
+        self.set(EN, true);
+
+        #[cfg(feature = "timing-audit")]
+        if let Some(audit) = self.audit.as_mut() {
+            audit.rose = (audit.clock)();
+        }
+
+        if self.delays.pulse > 0 {
+            self.delay.delay_us(self.delays.pulse);
+        }
+
+        #[cfg(feature = "timing-audit")]
+        if let Some(audit) = self.audit {
+            let width = (audit.clock)().wrapping_sub(audit.rose);
+            debug_assert!(
+                width >= AUDIT_PULSE,
+                "enable pulse was {} ns wide, the minimum is {} ns",
+                width,
+                AUDIT_PULSE
+            );
+        }
+
+        self.set(EN, false);
+
+        if self.delays.hold > 0 {
+            self.delay.delay_us(self.delays.hold);
+        }
+    }
+
+    /// Set a pin at position `index` to a particular value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// self.set(RS, true);
+    /// ```
+    fn set(&mut self, index: u8, value: bool) {
+        let value = value ^ (self.inverted & (1 << index) != 0);
+
+        if self.port.is_some() {
+            match value {
+                true => self.levels |= 1 << index,
+                false => self.levels &= !(1 << index),
+            }
+
+            if !self.exists(index) {
+                self.fail(Error::no_pin(index));
+            } else if index == EN {
+                self.flush();
+            }
+            return;
+        }
+
+        let written = self.pins[index as usize].as_mut().map(|p| match value {
+            true => p.set_high().is_ok(),
+            false => p.set_low().is_ok(),
+        });
+
+        match written {
+            Some(true) => {}
+            Some(false) => self.pin_fault(Error::no_pin(index)),
+            None => self.fail(Error::no_pin(index)),
+        }
+    }
+
+    /// Write the collected pin levels and the backlight state with the port writer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// self.flush();
+    /// ```
+    fn flush(&mut self) {
+        let levels = match self.backlight {
+            true => self.levels | 1 << A,
+            false => self.levels & !(1 << A),
+        };
+
+        if let Some(port) = self.port {
+            // inside a transfer the whole transfer is retried instead
+            let mut attempts = match self.watching {
+                true => 1,
+                false => self.retry.attempts.max(1),
+            };
+            while !port(&mut self.pins, levels) {
+                attempts -= 1;
+                if attempts == 0 {
+                    self.pin_fault(Error::BusError);
+                    return;
+                }
+                self.backoff();
+            }
+        }
+    }
+
+    /// Wait between two attempts of an operation, as set by the retry policy.
+    fn backoff(&mut self) {
+        #[cfg(feature = "stats")]
+        {
+            self.stats.retries = self.stats.retries.wrapping_add(1);
+        }
+
+        if self.retry.backoff_us > 0 {
+            self.pause(self.retry.backoff_us);
+        }
+    }
+
+    /// Check that a pin exists
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// if self.exists(RS) {
+    ///     ...
+    /// }
+    /// ```
+    fn exists(&self, index: u8) -> bool {
+        self.pins[index as usize].is_some()
+    }
+
+    /// Put the controller into the configured bus mode and send the function,
+    /// control and entry mode settings, then clear the display.
+    fn init(&mut self) {
+        // builder options that the controller doesn't have
+        if self.context.controller != Controller::St7036 {
+            self.context.double_height = DoubleHeight::Off;
+        }
+        if !self.context.controller.has_icons() {
+            self.context.contrast = None;
+        }
+
+        self.configure();
+        if self.context.double_height != DoubleHeight::Off {
+            self.send_double_height();
+        }
+        if self.context.icons || self.context.contrast.is_some() {
+            self.send_power_icon();
+        }
+
+        self.clear();
+        self.home();
+    }
+
+    /// Switch to a new function set (lines and character size), re-sending the whole
+    /// configuration and checking that the columns still fit.
+    fn set_function(&mut self, function: u8) {
+        self.context.display_func = function;
+        if self.lines() == Lines::OneLine {
+            self.context.font = self.size();
+        }
+        self.reconfigure();
+        self.validate();
+    }
+
+    /// Send the configuration sequence again for new function settings, without
+    /// clearing the display, and point the address counter back at the cursor.
+    fn reconfigure(&mut self) {
+        self.configure();
+        if self.context.double_height != DoubleHeight::Off {
+            self.send_double_height();
+        }
+        if self.context.icons || self.context.contrast.is_some() {
+            self.send_power_icon();
+        }
+
+        self.context.row = self.context.row.min(self.rows() - 1);
+        let address = self.address();
+        self.command(address);
+        self.wait(self.delays.command);
+    }
+
+    /// Send the bus mode synchronization sequence followed by the function,
+    /// control and entry mode settings.
+    fn configure(&mut self) {
+        self.set(RS, false);
+        self.set(EN, false);
+
+        if self.exists(RW) {
+            self.set(RW, false);
+        }
+
+        match self.mode() {
+            Mode::FourBits => {
+                // display function is four bit
+                self.update(0x03);
+                self.pause(4500);
+
+                self.update(0x03);
+                self.pause(4500);
+
+                self.update(0x03);
+                self.pause(150);
+
+                self.update(0x02);
+            }
+            Mode::EightBits => {
+                // display function is eight bit
+                self.command(self.function_command());
+                self.pause(4500);
+
+                self.command(self.function_command());
+                self.pause(150);
+
+                self.command(self.function_command());
+            }
+        }
+
+        self.command(self.function_command());
+        self.pause(self.delays.command);
+
+        // some clones only latch the function set reliably on a repeat
+        for _ in 0..self.context.controller.extra_function_sets() {
+            self.command(self.function_command());
+            self.pause(self.delays.command);
+        }
+
+        if self.native_four_lines() {
+            self.send_four_lines();
+        }
+
+        self.command(Command::SetDisplayCtrl as u8 | self.context.display_ctrl);
+        self.pause(self.delays.command);
+
+        self.command(Command::SetDisplayMode as u8 | self.context.display_mode);
+        self.pause(self.delays.command);
+    }
+
+    /// Get the function set instruction for the current settings.
+    fn function_command(&self) -> u8 {
+        let mut function = Command::SetDisplayFunc as u8 | self.context.display_func;
+        if self.lines() == Lines::FourLines && self.context.controller.extends_function_set() {
+            function &= !(Ks0073::Extended as u8);
+        }
+        function
+    }
+
+    /// Check if the display uses the native four-line mode of a KS0073.
+    fn native_four_lines(&self) -> bool {
+        self.context.controller == Controller::Ks0073 && self.lines() == Lines::FourLines
+    }
+
+    /// Get the DDRAM address at the start of each row in the current mode. The layout
+    /// set by [with_row_offsets][LcdDisplay::with_row_offsets] is kept while a KS0073
+    /// uses its own four-line layout, so it is back when the lines change again.
+    fn row_offsets(&self) -> [u8; 4] {
+        match self.native_four_lines() {
+            true => KS0073_OFFSETS,
+            false => self.context.offsets,
+        }
+    }
+
+    /// Get the length of each row in display memory, which is 32 characters in the
+    /// four-line layout of a KS0073 and 40 otherwise.
+    fn row_length(&self) -> i16 {
+        match self.native_four_lines() {
+            true => 0x20,
+            false => DDRAM_COLS,
+        }
+    }
+
+    /// Switch a KS0073 into four-line mode through its extended function set, and go
+    /// back to the normal instructions.
+    fn send_four_lines(&mut self) {
+        let function = self.function_command();
+        self.command(function | Ks0073::Extended as u8);
+        self.pause(self.delays.command);
+        self.command(Ks0073::FunctionSet as u8 | Ks0073::FourLines as u8);
+        self.pause(self.delays.command);
+        self.command(function);
+        self.pause(self.delays.command);
+    }
+
+    /// Copy the state that belongs to the physical display currently attached
+    /// to the enable pin.
+    pub(crate) fn context(&self) -> Context {
+        self.context.clone()
+    }
+
+    /// Swap the enable pin and display state with those of another physical
+    /// display on the same bus.
+    pub(crate) fn swap_display(&mut self, en: &mut Option<T>, context: &mut Context) {
+        core::mem::swap(&mut self.pins[EN as usize], en);
+        core::mem::swap(&mut self.context, context);
+    }
+
+    /// Set an error code if display is misconfigured. Currently
+    /// validates the number of pins for the given bus width and
+    /// that the rows fit in DDRAM.
+    fn validate(&mut self) {
+        // four-line displays fit two rows in each 40 character DDRAM line
+        if self.rows() == 4 && self.context.cols as i16 > DDRAM_COLS / 2 {
+            self.fail(Error::InvalidCols);
+        }
+
+        // every data pin for the bus width has to be connected
+        if !match self.mode() {
+            Mode::FourBits => {
+                self.exists(D4) && self.exists(D5) && self.exists(D6) && self.exists(D7)
+            }
+            Mode::EightBits => {
+                self.exists(D0)
+                    && self.exists(D1)
+                    && self.exists(D2)
+                    && self.exists(D3)
+                    && self.exists(D4)
+                    && self.exists(D5)
+                    && self.exists(D6)
+                    && self.exists(D7)
+            }
+        } {
+            self.fail(Error::InvalidMode);
+        }
+    }
+}
+
+impl<T, D> LcdDisplay<T, D>
+where
+    T: IoPin + Sized,
+    D: DelayNs + Sized,
+{
+    /// Switch the data pins to input for every read from the display, and back to output
+    /// afterwards, instead of releasing them by setting them high. This is needed for
+    /// push-pull pins, which would otherwise fight the display while it drives the data
+    /// lines. (See [IoPin])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_rw(rw)
+    ///     .with_io_pins()
+    ///     .with_busy_flag()
+    ///     .build();
+    /// ```
+    pub fn with_io_pins(mut self) -> Self {
+        self.direction = Some(Self::switch_direction);
+        self
+    }
+
+    /// Switch the data pins used by the bus mode to input or back to output.
+    fn switch_direction(&mut self, input: bool) {
+        let pins: &[u8] = match self.mode() {
+            Mode::FourBits => &[D7, D6, D5, D4],
+            Mode::EightBits => &[D7, D6, D5, D4, D3, D2, D1, D0],
+        };
+
+        for index in pins {
+            let switched = self.pins[*index as usize].as_mut().map(|p| match input {
+                true => p.set_input().is_ok(),
+                false => p.set_output().is_ok(),
+            });
+
+            match switched {
+                Some(true) => {}
+                Some(false) => self.pin_fault(Error::no_pin(*index)),
+                None => self.fail(Error::no_pin(*index)),
+            }
+        }
+    }
+}
+
+impl<T, D> LcdDisplay<T, D>
+where
+    T: OutputPin + InputPin + Sized,
+    D: DelayNs + Sized,
+{
+    /// Poll the busy flag of the display instead of waiting a fixed time after each
+    /// command and character, which is usually much faster. This needs an RW
+    /// pin and data pins that can be read, like [self_test][LcdDisplay::self_test]. The
+    /// initialization sequence always uses fixed delays. (See
+    /// [with_busy_timeout][LcdDisplay::with_busy_timeout])
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut lcd: LcdDisplay<_,_> = ...;
-    /// lcd.autoscroll_on();
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_rw(rw)
+    ///     .with_busy_flag()
+    ///     .build();
     /// ```
-    pub fn autoscroll_on(&mut self) {
-        self.set_autoscroll(AutoScroll::On);
+    pub fn with_busy_flag(mut self) -> Self {
+        self.busy = Some(Self::busy_flag);
+        self
     }
 
-    /// Turn autoscroll off. (See [set_autoscroll][LcdDisplay::set_autoscroll])
+    /// Check that a display is responding at the end of [build][LcdDisplay::build], and
+    /// set the error code to [Error::NotDetected] if it isn't. (See [probe][LcdDisplay::probe])
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut lcd: LcdDisplay<_,_> = ...;
-    /// lcd.autoscroll_off();
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_rw(rw)
+    ///     .with_probe()
+    ///     .build();
+    ///
+    /// if lcd.error() == Error::NotDetected {
+    ///     // check the wiring and the power to the display
+    /// }
     /// ```
-    pub fn autoscroll_off(&mut self) {
-        self.set_autoscroll(AutoScroll::Off);
+    pub fn with_probe(mut self) -> Self {
+        self.probe = Some(Self::probe);
+        self
     }
 
-    /// Get the current bus mode. (See [with_half_bus][LcdDisplay::with_half_bus] and [with_full_bus][LcdDisplay::with_full_bus])
+    /// Check that the display responds after [build][LcdDisplay::build] and
+    /// [reinit][LcdDisplay::reinit] initialize it, trying up to `attempts` times with
+    /// [reliable_init_verified][LcdDisplay::reliable_init_verified]. With a power pin or
+    /// switch every retry starts with a [power_cycle][LcdDisplay::power_cycle]. This needs
+    /// an RW pin, and like reliable_init_verified it clears the display. (Default is no check)
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut lcd: LcdDisplay<_,_> = ...;
-    /// let mode = lcd.mode();
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_rw(rw)
+    ///     .with_power_pin(power)
+    ///     .with_verified_init(3)
+    ///     .build();
+    ///
+    /// if lcd.error() == Error::VerifyFailed {
+    ///     // still not responding after two power cycles
+    /// }
     /// ```
-    pub fn mode(&self) -> Mode {
-        if (self.display_func & Mode::EightBits as u8) == 0 {
-            Mode::FourBits
-        } else {
-            Mode::EightBits
-        }
+    pub fn with_verified_init(mut self, attempts: u8) -> Self {
+        self.verify = Some(Self::reliable_init_verified);
+        self.verify_attempts = attempts;
+        self
     }
 
-    /// Get the current text direction layout. (See [set_layout][LcdDisplay::set_layout])
+    /// Check that a display is connected and responding, by waiting for the busy flag to
+    /// clear and then reading back the address counter after moving it. Returns false and
+    /// sets the error code to [Error::NotDetected] if nothing answers, so a blank display
+    /// can be told apart from a wiring or power fault. The check is repeated up to the
+    /// attempts of the [retry policy][LcdDisplay::with_retry_policy], for displays that
+    /// are still powering up. The cursor position is not changed.
+    ///
+    /// This has the same pin requirements as [self_test][LcdDisplay::self_test], and works
+    /// through an I2C port expander as long as its RW pin is connected. Without an RW pin
+    /// the display can't be checked, so the error code is set to [Error::NoPinRW] and
+    /// false is returned.
     ///
     /// # Examples
     ///
     /// ```
     /// let mut lcd: LcdDisplay<_,_> = ...;
-    /// let layout = lcd.layout();
+    ///
+    /// if !lcd.probe() {
+    ///     // nothing is connected
+    /// }
     /// ```
-    pub fn layout(&self) -> Layout {
-        if (self.display_mode & Layout::LeftToRight as u8) == 0 {
-            Layout::RightToLeft
-        } else {
-            Layout::LeftToRight
+    pub fn probe(&mut self) -> bool {
+        if !self.exists(RW) {
+            self.fail(Error::NoPinRW);
+            return false;
         }
+
+        let mut attempts = self.retry.attempts.max(1);
+        while !self.detect() {
+            attempts -= 1;
+            if attempts == 0 {
+                self.fail(Error::NotDetected);
+                return false;
+            }
+            self.backoff();
+        }
+        true
     }
 
-    /// Get the current state of the display (on or off). (See [set_display][LcdDisplay::set_display])
+    /// Check once whether a display answers, by moving the address counter and reading
+    /// it back. (See [probe][LcdDisplay::probe])
+    fn detect(&mut self) -> bool {
+        // floating or pulled-up data lines look like a display that stays busy
+        let mut waited = 0;
+        while self.busy_flag() {
+            if waited >= self.busy_timeout {
+                return false;
+            }
+            self.pause(BUSY_POLL);
+            waited += BUSY_POLL;
+        }
+
+        let mut found = true;
+        for address in PROBE_ADDRESSES {
+            self.command(Command::SetDDRAMAddr as u8 | address);
+            self.wait(self.delays.command);
+            found &= self.read(false) & 0x7F == address;
+        }
+
+        self.set_position(self.context.col, self.context.row);
+        found
+    }
+
+    /// Read the address counter of the display, which is the DDRAM address the next
+    /// character will be written to (or the CGRAM address, right after
+    /// [set_character][LcdDisplay::set_character]). This needs an RW pin like
+    /// [self_test][LcdDisplay::self_test]; without one the error code is set to
+    /// [Error::NoPinRW] and 0 is returned.
     ///
     /// # Examples
     ///
     /// ```
     /// let mut lcd: LcdDisplay<_,_> = ...;
-    /// let display = lcd.display();
+    ///
+    /// lcd.print("Hi");
+    /// assert_eq!(lcd.address_counter(), 0x02);
     /// ```
-    pub fn display(&self) -> Display {
-        if (self.display_ctrl & Display::On as u8) == 0 {
-            Display::Off
-        } else {
-            Display::On
+    pub fn address_counter(&mut self) -> u8 {
+        if !self.exists(RW) {
+            self.fail(Error::NoPinRW);
+            return 0;
         }
+        self.read(false) & 0x7F
     }
 
-    /// Get the current cursor state (on or off). (See [set_cursor][LcdDisplay::set_cursor])
+    /// Update the tracked cursor position from the [address counter][LcdDisplay::address_counter]
+    /// of the display, for when the hardware cursor was moved behind the driver's back (by
+    /// raw commands, or by a display that was reset). Addresses past the end of a row are
+    /// reported as columns beyond [cols][LcdDisplay::cols].
     ///
     /// # Examples
     ///
     /// ```
     /// let mut lcd: LcdDisplay<_,_> = ...;
-    /// let cursor = lcd.cursor();
+    ///
+    /// lcd.raw_command(0x80 | 0x45);
+    /// lcd.sync_position();
+    /// assert_eq!(lcd.position(), (5, 1));
     /// ```
-    pub fn cursor(&self) -> Cursor {
-        if (self.display_ctrl & Cursor::On as u8) == 0 {
-            Cursor::Off
-        } else {
-            Cursor::On
+    pub fn sync_position(&mut self) {
+        if !self.exists(RW) {
+            self.fail(Error::NoPinRW);
+            return;
         }
+
+        let address = self.address_counter();
+        let offsets = self.row_offsets();
+        let row = (0..self.rows())
+            .filter(|row| offsets[*row as usize] <= address)
+            .max_by_key(|row| offsets[*row as usize])
+            .unwrap_or(0);
+
+        self.context.col = address.saturating_sub(offsets[row as usize]);
+        self.context.row = row;
     }
 
-    /// Get the current blink state (on or off). (See [set_blink][LcdDisplay::set_blink])
+    /// Write a known pattern to display memory (DDRAM) and character memory (CGRAM), read
+    /// it back and report which regions passed.
+    ///
+    /// This requires an RW pin and data pins that can be read while released high, like the
+    /// quasi-bidirectional pins of a port expander or open-drain pins with pull-ups (or
+    /// push-pull pins that switch direction, see [with_io_pins][LcdDisplay::with_io_pins]). A
+    /// failure with the RW pin connected usually means a wiring fault on the data lines,
+    /// while garbled text with a passing self test points to timing problems instead.
+    ///
+    /// The test clears the display and overwrites custom character 7. If there is no RW
+    /// pin, both regions fail and the error code is set to [Error::NoPinRW].
     ///
     /// # Examples
     ///
     /// ```
     /// let mut lcd: LcdDisplay<_,_> = ...;
-    /// let blink = lcd.blink();
+    ///
+    /// if !lcd.self_test().passed() {
+    ///     // check the wiring
+    /// }
     /// ```
-    pub fn blink(&self) -> Blink {
-        if (self.display_ctrl & Blink::On as u8) == 0 {
-            Blink::Off
-        } else {
-            Blink::On
+    pub fn self_test(&mut self) -> SelfTest {
+        if !self.exists(RW) {
+            self.fail(Error::NoPinRW);
+            return SelfTest {
+                ddram: false,
+                cgram: false,
+            };
         }
+
+        self.clear();
+        let ddram = self.test_memory(Command::SetDDRAMAddr as u8, 0xFF);
+        let cgram = self.test_memory(Command::SetCGramAddr as u8 | (7 << 3), 0x1F);
+        self.clear();
+
+        SelfTest { ddram, cgram }
     }
 
-    /// Get the current autoscroll state (on or off). (See [set_autoscroll][LcdDisplay::set_autoscroll])
+    /// Run the full initialization sequence and check that the display responds by
+    /// writing a pattern to display memory and reading it back, retrying up to `attempts`
+    /// times. Returns true once the readback matches.
+    ///
+    /// The pattern overwrites the text in display memory, so the display is cleared
+    /// afterwards whether or not the check passed. Print anything that should be shown
+    /// after calling this.
+    ///
+    /// If a [power pin][LcdDisplay::with_power_pin] or switch is set, every retry starts
+    /// with a [power_cycle][LcdDisplay::power_cycle] instead of just running the sequence
+    /// again.
+    ///
+    /// This has the same pin requirements as [self_test][LcdDisplay::self_test]. Without
+    /// an RW pin the display can't be checked, so it is initialized once, the error code is
+    /// set to [Error::NoPinRW] and false is returned. If every attempt fails the error code
+    /// is set to [Error::VerifyFailed].
     ///
     /// # Examples
     ///
     /// ```
     /// let mut lcd: LcdDisplay<_,_> = ...;
-    /// let autoscroll = lcd.autoscroll();
+    ///
+    /// if !lcd.reliable_init_verified(5) {
+    ///     // the display isn't responding
+    /// }
     /// ```
-    pub fn autoscroll(&self) -> AutoScroll {
-        if (self.display_mode & AutoScroll::On as u8) == 0 {
-            AutoScroll::Off
-        } else {
-            AutoScroll::On
+    pub fn reliable_init_verified(&mut self, attempts: u8) -> bool {
+        if !self.exists(RW) {
+            self.initialize();
+            self.fail(Error::NoPinRW);
+            return false;
+        }
+
+        let mut attempts = attempts.max(1);
+        let mut retry = false;
+        loop {
+            match retry && self.power.is_some() {
+                true => self.power_cycle(),
+                false => self.initialize(),
+            }
+            retry = true;
+
+            if self.test_memory(Command::SetDDRAMAddr as u8, 0xFF) {
+                self.clear();
+                return true;
+            }
+
+            attempts -= 1;
+            if attempts == 0 {
+                break;
+            }
+
+            #[cfg(feature = "stats")]
+            {
+                self.stats.retries = self.stats.retries.wrapping_add(1);
+            }
         }
+
+        self.clear();
+        self.fail(Error::VerifyFailed);
+        false
     }
 
-    /// Get the number of lines. (See [with_lines][LcdDisplay::with_lines])
+    /// Print a message to the LCD display, reading back each character and re-sending it
+    /// if it didn't arrive intact. (See [write_verified][LcdDisplay::write_verified])
     ///
     /// # Examples
     ///
     /// ```
     /// let mut lcd: LcdDisplay<_,_> = ...;
-    /// let lines = lcd.lines();
+    /// lcd.print_verified("TEST MESSAGE");
     /// ```
-    pub fn lines(&self) -> Lines {
-        let flag_bits: u8 = self.display_func & 0x0C;
-        if flag_bits == Lines::FourLines as u8 {
-            Lines::FourLines
-        } else if flag_bits == Lines::TwoLines as u8 {
-            Lines::TwoLines
-        } else {
-            Lines::OneLine
-        }
+    pub fn print_verified(&mut self, text: &str) {
+        self.print_with(text, |lcd, ch| lcd.write_verified(ch as u8));
     }
 
-    /// Get the current error code. If an error occurs, the internal code will be
-    /// set to a value other than [Error::None][Error::None] (11u8).
+    /// Read every custom character back after [set_character][LcdDisplay::set_character]
+    /// stores it, and re-send it on a mismatch. (See [set_character_verified][LcdDisplay::set_character_verified])
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut lcd: LcdDisplay<_,_> = ...;
-    /// let code = lcd.error();
+    /// ...
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_rw(rw)
+    ///     .with_verified_characters()
+    ///     .build();
     /// ```
-    pub fn error(&self) -> Error {
-        self.code.clone()
+    pub fn with_verified_characters(mut self) -> Self {
+        self.character_check = Some(Self::set_character_verified);
+        self
     }
 
-    /// Print a message to the LCD display.
+    /// Store a custom character like [set_character][LcdDisplay::set_character], then read
+    /// it back from character memory and re-send it on a mismatch, up to the number of
+    /// attempts set by [with_retry_policy][LcdDisplay::with_retry_policy]. Returns true
+    /// once the glyph reads back intact.
+    ///
+    /// Corrupted custom characters are a common sign of marginal wiring, and otherwise
+    /// only show up as garbled glyphs. This has the same pin requirements as
+    /// [self_test][LcdDisplay::self_test], and if the glyph still doesn't match after every
+    /// retry the error code is set to [Error::VerifyFailed]. Without an RW pin the glyph
+    /// is stored without checking, the error code is set to [Error::NoPinRW] and false is
+    /// returned.
     ///
     /// # Examples
     ///
     /// ```
     /// let mut lcd: LcdDisplay<_,_> = ...;
-    /// lcd.print("TEST MESSAGE");
+    ///
+    /// if !lcd.set_character_verified(0, glyph) {
+    ///     // check the data lines
+    /// }
     /// ```
-    pub fn print(&mut self, text: &str) {
-        for ch in text.chars() {
-            self.write(ch as u8);
+    pub fn set_character_verified(&mut self, location: u8, map: [u8; 8]) -> bool {
+        if location > 7 {
+            self.fail(Error::OutOfBounds);
+        }
+        let location = location & 0x7;
+
+        if !self.exists(RW) {
+            self.upload_character(location, map);
+            self.return_to_ddram();
+            self.fail(Error::NoPinRW);
+            return false;
         }
+
+        let mut attempts = self.retry.attempts.max(1);
+        let verified = loop {
+            self.upload_character(location, map);
+
+            self.command(Command::SetCGramAddr as u8 | (location << 3));
+            self.wait(self.delays.command);
+            if map.iter().all(|row| self.read(true) & 0x1F == row & 0x1F) {
+                break true;
+            }
+
+            attempts -= 1;
+            if attempts == 0 {
+                self.fail(Error::VerifyFailed);
+                break false;
+            }
+            self.backoff();
+        };
+        self.return_to_ddram();
+        verified
     }
 
-    /// Write a single character to the LCD display.
+    /// Write a single character to the LCD display, then read it back from display memory
+    /// and re-send it on a mismatch, up to the number of attempts set by
+    /// [with_retry_policy][LcdDisplay::with_retry_policy].
+    ///
+    /// This turns occasional corruption on long or noisy cables into a self-healing display.
+    /// It has the same pin requirements as [self_test][LcdDisplay::self_test], and if the
+    /// character still doesn't match after every retry the error code is set to
+    /// [Error::VerifyFailed]. Retries shift the display again if autoscroll is on.
     ///
     /// # Examples
     ///
     /// ```
     /// let mut lcd: LcdDisplay<_,_> = ...;
-    /// lcd.write('A' as u8);
+    /// lcd.write_verified(b'A');
     /// ```
-    pub fn write(&mut self, value: u8) {
-        self.delay.delay_us(CHR_DELAY);
-        self.send(value, true);
+    pub fn write_verified(&mut self, value: u8) {
+        self.write_with(value, Self::data_verified);
     }
 
-    /// Execute a command on the LCD display, usually by using bitwise OR to combine
-    /// flags in various ways.
+    /// Write a byte to display memory and read it back, retrying on a mismatch.
+    fn data_verified(&mut self, value: u8) {
+        let address = self.read(false) & 0x7F;
+
+        let mut attempts = self.retry.attempts.max(1);
+        loop {
+            self.data(value);
+
+            self.command(Command::SetDDRAMAddr as u8 | address);
+            self.wait(self.delays.command);
+
+            // reading moves the address counter past the byte, like writing does
+            if self.read(true) == value {
+                return;
+            }
+
+            attempts -= 1;
+            if attempts == 0 {
+                self.fail(Error::VerifyFailed);
+                return;
+            }
+            self.backoff();
+
+            self.command(Command::SetDDRAMAddr as u8 | address);
+            self.wait(self.delays.command);
+        }
+    }
+
+    /// Write [TEST_PATTERN] starting at the address set by `command` and check
+    /// that it reads back the same (only comparing the bits in `mask`).
+    fn test_memory(&mut self, command: u8, mask: u8) -> bool {
+        self.command(command);
+        self.wait(self.delays.command);
+        for byte in TEST_PATTERN {
+            self.data(byte);
+        }
+
+        self.command(command);
+        self.wait(self.delays.command);
+        TEST_PATTERN
+            .iter()
+            .all(|byte| (self.read(true) & mask) == (byte & mask))
+    }
+
+    /// Read a byte from the LCD display with the RS pin set either high (to read
+    /// from memory) or low (to read the busy flag and address counter).
     ///
     /// # Examples
     ///
     /// ```
-    /// self.command(Command::SetDisplayCtrl as u8 | self.display_ctrl);
+    /// let value = self.read(true);
     /// ```
-    fn command(&mut self, value: u8) {
-        self.send(value, false);
+    fn read(&mut self, mode: bool) -> u8 {
+        let value = self.fetch(mode);
+        self.pause(self.delays.character);
+        value
     }
 
-    /// Send bytes to the LCD display with the RS pin set either high (for commands)
-    /// or low (to write to memory)
+    /// Read a byte like [read][LcdDisplay::read], without waiting afterwards.
     ///
     /// # Examples
     ///
     /// ```
-    /// self.send(value, true);
+    /// let status = self.fetch(false);
     /// ```
-    fn send(&mut self, byte: u8, mode: bool) {
+    fn fetch(&mut self, mode: bool) -> u8 {
         self.set(RS, mode);
+        self.set(RW, true);
 
-        if self.exists(RW) {
-            self.set(RW, false);
-        }
-
-        match self.mode() {
+        let value = match self.mode() {
             Mode::FourBits => {
-                self.update(byte >> 4);
-                self.update(byte);
+                let high = self.receive();
+                let low = self.receive();
+                (high << 4) | (low & 0x0F)
             }
-            Mode::EightBits => {
-                self.update(byte);
-            }
-        }
+            Mode::EightBits => self.receive(),
+        };
+
+        self.set(RW, false);
+        value
     }
 
-    /// Update the on-device memory by sending either the bottom nibble (in
-    /// four-bit mode) or a whole byte (in eight-bit) and then pulsing the enable pin.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// self.update(byte);
-    /// ```
-    fn update(&mut self, byte: u8) {
-        self.set(EN, false);
-        match self.mode() {
-            Mode::FourBits => {
-                self.set(D7, (byte >> 3) & 1 > 0);
-                self.set(D6, (byte >> 2) & 1 > 0);
-                self.set(D5, (byte >> 1) & 1 > 0);
-                self.set(D4, (byte >> 0) & 1 > 0);
-            }
-            Mode::EightBits => {
-                self.set(D7, (byte >> 7) & 1 > 0);
-                self.set(D6, (byte >> 6) & 1 > 0);
-                self.set(D5, (byte >> 5) & 1 > 0);
-                self.set(D4, (byte >> 4) & 1 > 0);
-                self.set(D3, (byte >> 3) & 1 > 0);
-                self.set(D2, (byte >> 2) & 1 > 0);
-                self.set(D1, (byte >> 1) & 1 > 0);
-                self.set(D0, (byte >> 0) & 1 > 0);
-            }
-        };
-        self.pulse();
+    /// Check the busy flag, which is set while the display is still processing
+    /// the last instruction.
+    fn busy_flag(&mut self) -> bool {
+        self.fetch(false) & 0x80 != 0
     }
 
-    /// Set the enable pin high and then low to make the LCD accept the most
-    /// recently transmitted data.
+    /// Release the data pins, pulse the enable pin high and sample either the top
+    /// nibble (in four-bit mode) or the whole byte (in eight-bit mode).
     ///
     /// # Examples
     ///
     /// ```
-    /// self.pulse();
+    /// let nibble = self.receive();
     /// ```
-    fn pulse(&mut self) {
+    fn receive(&mut self) -> u8 {
+        let pins: &[u8] = match self.mode() {
+            Mode::FourBits => &[D7, D6, D5, D4],
+            Mode::EightBits => &[D7, D6, D5, D4, D3, D2, D1, D0],
+        };
+
+        // let the controller drive the data lines
+        match self.direction {
+            Some(switch) => switch(self, true),
+            None => {
+                for pin in pins {
+                    self.set(*pin, true);
+                }
+            }
+        }
+
+        self.set(EN, false);
         self.set(EN, true);
+        self.delay.delay_us(self.delays.pulse.max(1));
+
+        let mut value = 0;
+        for pin in pins {
+            value = (value << 1) | self.get(*pin) as u8;
+        }
+
         self.set(EN, false);
-    }
 
-    /// Set a pin at position `index` to a particular value
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// self.set(RS, true);
-    /// ```
-    fn set(&mut self, index: u8, value: bool) {
-        if self.pins[index as usize]
-            .as_mut()
-            .and_then(|p| match value {
-                true => p.set_high().ok(),
-                false => p.set_low().ok(),
-            })
-            .is_none()
-        {
-            self.code = index.into();
+        if let Some(switch) = self.direction {
+            switch(self, false);
+        }
+
+        if self.delays.hold > 0 {
+            self.delay.delay_us(self.delays.hold);
         }
+        self.unmap(value)
     }
 
-    /// Check that a pin exists
+    /// Read the level of the pin at position `index`
     ///
     /// # Examples
     ///
     /// ```
-    /// if self.exists(RS) {
-    ///     ...
-    /// }
+    /// let high = self.get(D7);
     /// ```
-    fn exists(&self, index: u8) -> bool {
-        self.pins[index as usize].is_some()
-    }
-
-    /// Set an error code if display is misconfigured. Currently
-    /// only validates the number of pins for the given bus width.
-    fn validate(&mut self) {
-        if match self.mode() {
-            Mode::FourBits => {
-                self.exists(D4) || self.exists(D5) || self.exists(D6) || self.exists(D7)
-            }
-            Mode::EightBits => {
-                self.exists(D0)
-                    || self.exists(D1)
-                    || self.exists(D2)
-                    || self.exists(D3)
-                    || self.exists(D4)
-                    || self.exists(D5)
-                    || self.exists(D6)
-                    || self.exists(D7)
+    fn get(&mut self, index: u8) -> bool {
+        match self.pins[index as usize].as_mut().map(|p| p.is_high()) {
+            Some(Ok(value)) => value,
+            _ => {
+                self.fail(Error::no_pin(index));
+                false
             }
-        } {
-            self.code = Error::InvalidMode;
         }
     }
 }
@@ -1252,7 +5929,7 @@ where
 #[cfg(feature = "ufmt")]
 impl<T, D> ufmt::uWrite for LcdDisplay<T, D>
 where
-    T: OutputPin<Error = core::convert::Infallible> + Sized,
+    T: OutputPin + Sized,
     D: DelayNs + Sized,
 {
     type Error = core::convert::Infallible;
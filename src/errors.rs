@@ -1,3 +1,5 @@
+use core::fmt;
+
 /// Error type for [LcdDisplay][crate::display::LcdDisplay], returned by [LcdDisplay::error][crate::display::LcdDisplay::error]
 ///
 /// LcdDisplay uses an internal error code rather than the standard rust
@@ -40,11 +42,91 @@ pub enum Error {
     InvalidMode = 12,
     /// Invalid conversion from u8 to Error
     InvalidCode = 13,
+    /// [Column count][crate::display::LcdDisplay::with_cols] doesn't fit in DDRAM for the number of lines
+    InvalidCols = 14,
+    /// A [verified write][crate::display::LcdDisplay::write_verified] didn't read back correctly
+    VerifyFailed = 15,
+    /// The bus (I2C, SPI or serial) reported an error
+    BusError = 16,
+    /// A character passed to [print_utf8][crate::display::LcdDisplay::print_utf8] isn't in the character ROM
+    Unmappable = 17,
+    /// A position, custom character slot or other argument was outside of the display
+    OutOfBounds = 18,
+    /// The display didn't finish an operation in time
+    Timeout = 19,
+    /// No display answered on the bus
+    NotDetected = 20,
+    /// Every custom character slot (CGRAM) is already in use (see [add_character][crate::display::LcdDisplay::add_character])
+    CgRamFull = 21,
+    /// The [power pin or switch][crate::display::LcdDisplay::with_power_pin] couldn't switch the supply
+    PowerFailed = 22,
+    /// The [controller][crate::display::Controller] doesn't support the operation
+    Unsupported = 23,
+}
+
+impl Error {
+    /// Get a short description of the error, for logging.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// if lcd.error() != Error::None {
+    ///     log(lcd.error().as_str());
+    /// }
+    /// ```
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Error::NoPinRS => "no RS pin",
+            Error::NoPinEN => "no EN pin",
+            Error::NoPinRW => "no RW pin",
+            Error::NoPinD0 => "no D0 pin",
+            Error::NoPinD1 => "no D1 pin",
+            Error::NoPinD2 => "no D2 pin",
+            Error::NoPinD3 => "no D3 pin",
+            Error::NoPinD4 => "no D4 pin",
+            Error::NoPinD5 => "no D5 pin",
+            Error::NoPinD6 => "no D6 pin",
+            Error::NoPinD7 => "no D7 pin",
+            Error::None => "no error",
+            Error::InvalidMode => "invalid bus mode",
+            Error::InvalidCode => "invalid error code",
+            Error::InvalidCols => "too many columns",
+            Error::VerifyFailed => "verify failed",
+            Error::BusError => "bus error",
+            Error::Unmappable => "unmappable character",
+            Error::OutOfBounds => "out of bounds",
+            Error::Timeout => "timed out",
+            Error::NotDetected => "display not detected",
+            Error::CgRamFull => "CGRAM full",
+            Error::PowerFailed => "power switch failed",
+            Error::Unsupported => "not supported",
+        }
+    }
+
+    /// Get the numeric code of the error. Codes don't change between releases, so they
+    /// can be stored or sent and converted back with `Error::try_from`.
+    pub fn code(&self) -> u8 {
+        self.clone() as u8
+    }
+
+    /// The error for a missing pin, by pin index.
+    pub(crate) fn no_pin(index: u8) -> Self {
+        match index {
+            0..=10 => Error::try_from(index).unwrap_or(Error::InvalidCode),
+            _ => Error::InvalidCode,
+        }
+    }
 }
 
-impl From<u8> for Error {
-    fn from(v: u8) -> Self {
-        match v {
+/// Convert a numeric code back to an error. Codes that don't belong to any error are
+/// rejected with [Error::InvalidCode].
+impl TryFrom<u8> for Error {
+    type Error = Error;
+
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
+        Ok(match v {
             0 => Error::NoPinRS,
             1 => Error::NoPinEN,
             2 => Error::NoPinRW,
@@ -58,7 +140,30 @@ impl From<u8> for Error {
             10 => Error::NoPinD7,
             11 => Error::None,
             12 => Error::InvalidMode,
-            _ => Error::InvalidCode,
-        }
+            13 => Error::InvalidCode,
+            14 => Error::InvalidCols,
+            15 => Error::VerifyFailed,
+            16 => Error::BusError,
+            17 => Error::Unmappable,
+            18 => Error::OutOfBounds,
+            19 => Error::Timeout,
+            20 => Error::NotDetected,
+            21 => Error::CgRamFull,
+            22 => Error::PowerFailed,
+            23 => Error::Unsupported,
+            _ => return Err(Error::InvalidCode),
+        })
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.as_str(), self.code())
     }
 }
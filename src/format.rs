@@ -0,0 +1,146 @@
+//! Allows formatting text with ufmt before sending it to the display
+
+use core::convert::Infallible;
+
+/// A fixed-size buffer that text can be formatted into with ufmt
+///
+/// Text that doesn't fit is silently truncated (at a character boundary). Formatting into a
+/// buffer first and then printing it means the slow per-character writes to the display
+/// happen in one burst, so updates look atomic. See [uprint_at!][crate::uprint_at!].
+///
+/// This type is only available if the `ufmt` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// let mut buffer: FormatBuffer<16> = FormatBuffer::new();
+/// uwrite!(&mut buffer, "T: {} C", temperature).ok();
+///
+/// let mut lcd: LcdDisplay<_,_> = ...;
+/// lcd.print_at(0, 1, buffer.as_str());
+/// ```
+pub struct FormatBuffer<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> FormatBuffer<N> {
+    /// Create a new, empty buffer
+    pub fn new() -> Self {
+        Self {
+            bytes: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Get the formatted text
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.bytes[..self.len]).unwrap_or_default()
+    }
+
+    /// Remove all text from the buffer
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> Default for FormatBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> ufmt::uWrite for FormatBuffer<N> {
+    type Error = Infallible;
+
+    fn write_str(&mut self, s: &str) -> Result<(), Self::Error> {
+        for ch in s.chars() {
+            let size = ch.len_utf8();
+            if self.len + size > N {
+                break;
+            }
+            ch.encode_utf8(&mut self.bytes[self.len..]);
+            self.len += size;
+        }
+        Ok(())
+    }
+}
+
+/// A fixed-point number with `N` decimal places, for printing with ufmt
+///
+/// ufmt can't format floats, but sensor values are usually available as scaled integers
+/// anyway (like tenths of a degree). Fixed prints the integer with a decimal point `N`
+/// places from the right, so `Fixed::<1>(235)` prints `23.5` and `Fixed::<2>(-5)` prints
+/// `-0.05`.
+///
+/// This type is only available if the `ufmt` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// let mut lcd: LcdDisplay<_,_> = ...;
+///
+/// let tenths = sensor.read_tenths();
+/// uwrite!(lcd, "T: {} C", Fixed::<1>(tenths)).ok();
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Fixed<const N: u8>(pub i32);
+
+impl<const N: u8> ufmt::uDisplay for Fixed<N> {
+    fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+    where
+        W: ufmt::uWrite + ?Sized,
+    {
+        // the digits of the magnitude, least significant first
+        let mut digits = [0u8; 10];
+        let mut len = 0;
+        let mut value = self.0.unsigned_abs();
+        loop {
+            digits[len] = b'0' + (value % 10) as u8;
+            len += 1;
+            value /= 10;
+            if value == 0 {
+                break;
+            }
+        }
+
+        if self.0 < 0 {
+            f.write_char('-')?;
+        }
+
+        // pad with zeros so there is always a digit before the point
+        let places = N as usize;
+        for i in (0..len.max(places + 1)).rev() {
+            f.write_char(match i < len {
+                true => digits[i] as char,
+                false => '0',
+            })?;
+            if i == places && places > 0 {
+                f.write_char('.')?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Format text with ufmt into a stack buffer and then print it at a position in one burst.
+/// (See [FormatBuffer])
+///
+/// The buffer holds 40 bytes (a full row of DDRAM) and longer text is truncated. This macro
+/// is only available if the `ufmt` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// let mut lcd: LcdDisplay<_,_> = ...;
+/// uprint_at!(lcd, 0, 1, "Fan: {} %", speed);
+/// ```
+#[macro_export]
+macro_rules! uprint_at {
+    ($lcd:expr, $col:expr, $row:expr, $($arg:tt)*) => {{
+        use $crate::ufmt;
+        let mut buffer = $crate::FormatBuffer::<40>::new();
+        let _ = ufmt::uwrite!(&mut buffer, $($arg)*);
+        $lcd.print_at($col, $row, buffer.as_str());
+    }};
+}
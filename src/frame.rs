@@ -0,0 +1,190 @@
+//! Box and divider drawing with line glyphs stored in CGRAM
+
+use crate::{glyph, CharacterDisplay, LcdDisplay};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+
+/// First CGRAM slot used by the border glyphs. Slots 0 and 1 are left free for other
+/// custom characters.
+pub const FRAME_SLOT: u8 = 2;
+
+/// The border glyphs, in the order they are stored from [FRAME_SLOT]
+const BORDERS: [[u8; 8]; 6] = [
+    glyph(&[".....", ".....", ".....", "#####"]),
+    glyph(&[
+        "..#..", "..#..", "..#..", "..#..", "..#..", "..#..", "..#..", "..#..",
+    ]),
+    glyph(&[
+        ".....", ".....", ".....", "..###", "..#..", "..#..", "..#..", "..#..",
+    ]),
+    glyph(&[
+        ".....", ".....", ".....", "###..", "..#..", "..#..", "..#..", "..#..",
+    ]),
+    glyph(&["..#..", "..#..", "..#..", "..###"]),
+    glyph(&["..#..", "..#..", "..#..", "###.."]),
+];
+
+/// Character codes of the border glyphs
+const HORIZONTAL: u8 = FRAME_SLOT;
+const VERTICAL: u8 = FRAME_SLOT + 1;
+const TOP_LEFT: u8 = FRAME_SLOT + 2;
+const TOP_RIGHT: u8 = FRAME_SLOT + 3;
+const BOTTOM_LEFT: u8 = FRAME_SLOT + 4;
+const BOTTOM_RIGHT: u8 = FRAME_SLOT + 5;
+
+/// A rectangular area of the display, in characters
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Window {
+    col: u8,
+    row: u8,
+    width: u8,
+    height: u8,
+}
+
+impl Window {
+    /// Create a window that is `width` characters wide and `height` rows high, with
+    /// the top left corner at `col`, `row`.
+    pub const fn new(col: u8, row: u8, width: u8, height: u8) -> Self {
+        Self {
+            col,
+            row,
+            width,
+            height,
+        }
+    }
+
+    /// Get the column and row of the top left corner.
+    pub fn position(&self) -> (u8, u8) {
+        (self.col, self.row)
+    }
+
+    /// Get the width and height, in characters.
+    pub fn size(&self) -> (u8, u8) {
+        (self.width, self.height)
+    }
+}
+
+impl<T, D> LcdDisplay<T, D>
+where
+    T: OutputPin + Sized,
+    D: DelayNs + Sized,
+{
+    /// Store the border glyphs in CGRAM slots 2 to 7 (see [FRAME_SLOT]). This is done
+    /// by [draw_frame][LcdDisplay::draw_frame] and [draw_hline][LcdDisplay::draw_hline],
+    /// and only needs to be called again after other custom characters were stored in
+    /// those slots.
+    pub fn upload_frame_glyphs(&mut self) {
+        upload_frame_glyphs(self);
+    }
+
+    /// Draw a box along the edges of `window`, leaving the inside untouched. Windows
+    /// narrower or lower than two characters aren't drawn, and any part of the box
+    /// that is off the display is skipped. The cursor position is not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.draw_frame(Window::new(0, 0, 20, 4));
+    /// lcd.print_at(2, 1, "Main menu");
+    /// ```
+    pub fn draw_frame(&mut self, window: Window) {
+        draw_frame(self, window);
+    }
+
+    /// Draw a horizontal divider on `row` from column `from` to column `to` (inclusive).
+    /// Any part of the line that is off the display is skipped. The cursor position is
+    /// not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    ///
+    /// lcd.print_at(0, 0, "Status");
+    /// lcd.draw_hline(1, 0, 19);
+    /// ```
+    pub fn draw_hline(&mut self, row: u8, from: u8, to: u8) {
+        draw_hline(self, row, from, to);
+    }
+}
+
+/// Store the border glyphs. (See [CharacterDisplay::upload_frame_glyphs])
+pub(crate) fn upload_frame_glyphs<L>(lcd: &mut L)
+where
+    L: CharacterDisplay + ?Sized,
+{
+    let position = lcd.position();
+    for (slot, map) in (FRAME_SLOT..).zip(BORDERS.iter()) {
+        lcd.set_character(slot, *map);
+    }
+    lcd.set_position(position.0, position.1);
+}
+
+/// Draw a box. (See [CharacterDisplay::draw_frame])
+pub(crate) fn draw_frame<L>(lcd: &mut L, window: Window)
+where
+    L: CharacterDisplay + ?Sized,
+{
+    if window.width < 2 || window.height < 2 {
+        return;
+    }
+
+    upload_frame_glyphs(lcd);
+    let position = lcd.position();
+
+    let right = window.col.saturating_add(window.width - 1);
+    let bottom = window.row.saturating_add(window.height - 1);
+    frame_line(lcd, window.row, window.col, right, TOP_LEFT, TOP_RIGHT);
+    frame_line(lcd, bottom, window.col, right, BOTTOM_LEFT, BOTTOM_RIGHT);
+
+    for row in window.row + 1..bottom {
+        frame_cell(lcd, window.col, row, VERTICAL);
+        frame_cell(lcd, right, row, VERTICAL);
+    }
+    lcd.set_position(position.0, position.1);
+}
+
+/// Draw a divider. (See [CharacterDisplay::draw_hline])
+pub(crate) fn draw_hline<L>(lcd: &mut L, row: u8, from: u8, to: u8)
+where
+    L: CharacterDisplay + ?Sized,
+{
+    upload_frame_glyphs(lcd);
+    let position = lcd.position();
+    let (from, to) = (from.min(to), from.max(to));
+    frame_line(lcd, row, from, to, HORIZONTAL, HORIZONTAL);
+    lcd.set_position(position.0, position.1);
+}
+
+/// Write a horizontal line of border glyphs with the given end glyphs.
+fn frame_line<L>(lcd: &mut L, row: u8, from: u8, to: u8, first: u8, last: u8)
+where
+    L: CharacterDisplay + ?Sized,
+{
+    if row >= lcd.rows() || from >= lcd.cols() {
+        return;
+    }
+
+    lcd.set_position(from, row);
+    for col in from..=to.min(lcd.cols() - 1) {
+        lcd.write(match col {
+            c if c == from => first,
+            c if c == to => last,
+            _ => HORIZONTAL,
+        });
+    }
+}
+
+/// Write a single glyph if it is on the display.
+fn frame_cell<L>(lcd: &mut L, col: u8, row: u8, code: u8)
+where
+    L: CharacterDisplay + ?Sized,
+{
+    if col < lcd.cols() && row < lcd.rows() {
+        lcd.set_position(col, row);
+        lcd.write(code);
+    }
+}
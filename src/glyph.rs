@@ -0,0 +1,102 @@
+//! Helpers for building custom character bitmaps from ASCII art
+
+/// Build a custom character map for [set_character][crate::LcdDisplay::set_character] from
+/// rows of ASCII art, where `#` is a lit pixel and `.` (or a space) is an unlit one.
+///
+/// Each row can be at most five pixels wide and there can be at most eight rows. Missing
+/// rows are left blank. Any other character is rejected, which is a compile error when the
+/// function is evaluated in a const context (as the [glyph!][crate::glyph!] macro does).
+///
+/// # Examples
+///
+/// ```
+/// use ag_lcd::glyph;
+///
+/// const SMILEY: [u8; 8] = glyph(&[
+///     ".....",
+///     ".#.#.",
+///     ".....",
+///     "#...#",
+///     ".###.",
+/// ]);
+///
+/// assert_eq!(SMILEY, [0b00000, 0b01010, 0b00000, 0b10001, 0b01110, 0, 0, 0]);
+/// ```
+pub const fn glyph(rows: &[&str]) -> [u8; 8] {
+    assert!(rows.len() <= 8, "glyph has more than 8 rows");
+
+    let mut map = [0u8; 8];
+    let mut r = 0;
+    while r < rows.len() {
+        let row = rows[r].as_bytes();
+        assert!(row.len() <= 5, "glyph row is wider than 5 pixels");
+
+        let mut c = 0;
+        while c < row.len() {
+            match row[c] {
+                b'#' => map[r] |= 1 << (4 - c),
+                b'.' | b' ' => (),
+                _ => panic!("glyph rows may only contain '#', '.' or ' '"),
+            }
+            c += 1;
+        }
+        r += 1;
+    }
+    map
+}
+
+/// Turn a custom character map upside down, for displays that are mounted rotated by
+/// 180°. The rows are reversed and each row is mirrored, so the bottom row (where the
+/// cursor underline is drawn) becomes the top one.
+///
+/// # Examples
+///
+/// ```
+/// use ag_lcd::{glyph, rotate_glyph};
+///
+/// const ARROW: [u8; 8] = glyph(&["..#..", ".##..", "#####"]);
+///
+/// assert_eq!(rotate_glyph(ARROW), [0, 0, 0, 0, 0, 0b11111, 0b00110, 0b00100]);
+/// ```
+pub const fn rotate_glyph(map: [u8; 8]) -> [u8; 8] {
+    let mut rotated = [0u8; 8];
+    let mut r = 0;
+    while r < 8 {
+        let row = map[7 - r];
+        let mut c = 0;
+        while c < 5 {
+            if row & (1 << c) != 0 {
+                rotated[r] |= 1 << (4 - c);
+            }
+            c += 1;
+        }
+        r += 1;
+    }
+    rotated
+}
+
+/// Build a custom character map at compile time from rows of ASCII art. (See [glyph][crate::glyph()])
+///
+/// # Examples
+///
+/// ```
+/// let mut lcd: LcdDisplay<_,_> = ...;
+///
+/// lcd.set_character(0, ag_lcd::glyph![
+///     "..##.",
+///     "....#",
+///     "##..#",
+///     "....#",
+///     "....#",
+///     "##..#",
+///     "....#",
+///     "..##.",
+/// ]);
+/// ```
+#[macro_export]
+macro_rules! glyph {
+    ($($row:expr),* $(,)?) => {{
+        const MAP: [u8; 8] = $crate::glyph(&[$($row),*]);
+        MAP
+    }};
+}
@@ -0,0 +1,166 @@
+//! Allows drawing custom characters with [embedded-graphics](https://crates.io/crates/embedded-graphics)
+
+use crate::LcdDisplay;
+use core::convert::Infallible;
+use embedded_graphics::mono_font::{MonoFont, MonoTextStyle};
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::text::{Baseline, Text};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+
+/// Width of a character cell in pixels
+const CELL_WIDTH: usize = 5;
+
+/// Height of a character cell in pixels
+const CELL_HEIGHT: usize = 8;
+
+/// A drawing surface made of `COLS` x `ROWS` character cells, each backed by a custom
+/// character (CGRAM) slot
+///
+/// Anything that embedded-graphics can draw (primitives, images, text) can be drawn onto
+/// the canvas, which is then uploaded to CGRAM with [upload][GlyphCanvas::upload] and
+/// shown on screen with [show][GlyphCanvas::show]. There are only eight CGRAM slots, so
+/// `COLS * ROWS` must be at most 8.
+///
+/// This type is only available if the `embedded-graphics` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// use embedded_graphics::{pixelcolor::BinaryColor, prelude::*, primitives::*};
+///
+/// let mut canvas: GlyphCanvas<2, 1> = GlyphCanvas::new();
+/// Circle::new(Point::new(1, 0), 8)
+///     .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+///     .draw(&mut canvas)
+///     .ok();
+///
+/// let mut lcd: LcdDisplay<_,_> = ...;
+/// canvas.upload(&mut lcd, 0);
+/// canvas.show(&mut lcd, 0, 0, 0);
+/// ```
+pub struct GlyphCanvas<const COLS: usize, const ROWS: usize> {
+    cells: [[[u8; CELL_HEIGHT]; COLS]; ROWS],
+}
+
+impl<const COLS: usize, const ROWS: usize> GlyphCanvas<COLS, ROWS> {
+    /// Stops canvases with more cells than CGRAM slots from compiling
+    const FITS: () = assert!(COLS * ROWS <= 8, "a canvas can use at most 8 CGRAM slots");
+
+    /// Create a new, blank canvas. A canvas with more than 8 cells doesn't compile.
+    pub fn new() -> Self {
+        let () = Self::FITS;
+        Self {
+            cells: [[[0; CELL_HEIGHT]; COLS]; ROWS],
+        }
+    }
+
+    /// Get the character map of the cell at `col`, `row`
+    pub fn glyph(&self, col: usize, row: usize) -> [u8; 8] {
+        self.cells
+            .get(row)
+            .and_then(|r| r.get(col))
+            .copied()
+            .unwrap_or([0; CELL_HEIGHT])
+    }
+
+    /// Write every cell to CGRAM, in row-major order starting at slot `first`.
+    pub fn upload<T, D>(&self, lcd: &mut LcdDisplay<T, D>, first: u8)
+    where
+        T: OutputPin + Sized,
+        D: DelayNs + Sized,
+    {
+        for (i, map) in self.cells.iter().flatten().enumerate() {
+            lcd.set_character(first.wrapping_add(i as u8), *map);
+        }
+    }
+
+    /// Print the uploaded cells to the display with the top-left cell at `col`, `row`,
+    /// where `first` is the slot that was passed to [upload][GlyphCanvas::upload].
+    pub fn show<T, D>(&self, lcd: &mut LcdDisplay<T, D>, col: u8, row: u8, first: u8)
+    where
+        T: OutputPin + Sized,
+        D: DelayNs + Sized,
+    {
+        for r in 0..ROWS {
+            lcd.set_position(col, row.saturating_add(r as u8));
+            for c in 0..COLS {
+                lcd.write(first.wrapping_add((r * COLS + c) as u8) & 0x7);
+            }
+        }
+    }
+}
+
+impl<const COLS: usize, const ROWS: usize> Default for GlyphCanvas<COLS, ROWS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const COLS: usize, const ROWS: usize> OriginDimensions for GlyphCanvas<COLS, ROWS> {
+    fn size(&self) -> Size {
+        Size::new((COLS * CELL_WIDTH) as u32, (ROWS * CELL_HEIGHT) as u32)
+    }
+}
+
+impl<const COLS: usize, const ROWS: usize> DrawTarget for GlyphCanvas<COLS, ROWS> {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if point.x < 0 || point.y < 0 {
+                continue;
+            }
+
+            let (x, y) = (point.x as usize, point.y as usize);
+            let Some(line) = self
+                .cells
+                .get_mut(y / CELL_HEIGHT)
+                .and_then(|r| r.get_mut(x / CELL_WIDTH))
+                .map(|cell| &mut cell[y % CELL_HEIGHT])
+            else {
+                continue;
+            };
+
+            let bit = 1 << (CELL_WIDTH - 1 - x % CELL_WIDTH);
+            match color {
+                BinaryColor::On => *line |= bit,
+                BinaryColor::Off => *line &= !bit,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Rasterize a single character of an embedded-graphics [MonoFont] into a character map
+/// for [set_character][LcdDisplay::set_character]. Glyphs larger than 5x8 pixels are cropped
+/// to the top-left corner.
+///
+/// This function is only available if the `embedded-graphics` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// use embedded_graphics::mono_font::ascii::FONT_4X6;
+///
+/// let mut lcd: LcdDisplay<_,_> = ...;
+/// lcd.set_character(0, mono_glyph(&FONT_4X6, '&'));
+/// ```
+pub fn mono_glyph(font: &MonoFont, ch: char) -> [u8; 8] {
+    let mut canvas: GlyphCanvas<1, 1> = GlyphCanvas::new();
+    let mut buffer = [0u8; 4];
+    let style = MonoTextStyle::new(font, BinaryColor::On);
+    let _ = Text::with_baseline(
+        ch.encode_utf8(&mut buffer),
+        Point::zero(),
+        style,
+        Baseline::Top,
+    )
+    .draw(&mut canvas);
+    canvas.glyph(0, 0)
+}
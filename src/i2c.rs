@@ -3,6 +3,7 @@
 use crate::LcdDisplay;
 use core::fmt::Debug;
 use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{InputPin, OutputPin};
 use port_expander::{dev::pcf8574, mode::QuasiBidirectional, I2cBus, Pcf8574, Pcf8574a, Pin, PortMutex};
 
 impl<'a, D, M, I2C> LcdDisplay<Pin<'a, QuasiBidirectional, M>, D>
@@ -29,6 +30,71 @@ where
             .with_backlight(p3)
             .with_rw(p1)
             .with_half_bus(p4, p5, p6, p7)
+            .with_port(Self::write_port)
+    }
+
+    /// Like [`from_parts`], but leaves RW unused and returns P1 so it can be used as an
+    /// input. The board must tie RW to GND, since P1 is never driven low.
+    fn from_parts_without_rw(
+        parts: pcf8574::Parts<'a, I2C, M>,
+        delay: D,
+    ) -> (Self, Pin<'a, QuasiBidirectional, M>) {
+        let pcf8574::Parts {
+            p0,
+            p1,
+            p2,
+            p3,
+            p4,
+            p5,
+            p6,
+            p7,
+        } = parts;
+        let lcd = LcdDisplay::new(p0, p2, delay)
+            .with_backlight(p3)
+            .with_half_bus(p4, p5, p6, p7)
+            .with_port(Self::write_port);
+        (lcd, p1)
+    }
+
+    /// Write the levels of all the display pins to the expander as a single port byte, so
+    /// the backlight (P3) and the other pins are never glitched by per-pin updates.
+    fn write_port(pins: &mut [Option<Pin<'a, QuasiBidirectional, M>>; 12], levels: u16) -> bool {
+        let level = |index: u8| levels & (1 << index) != 0;
+        let [rs, en, rw, _, _, _, _, d4, d5, d6, d7, a] = pins;
+        match (rs, en, rw, d4, d5, d6, d7, a) {
+            (Some(rs), Some(en), Some(rw), Some(d4), Some(d5), Some(d6), Some(d7), Some(a)) => {
+                port_expander::write_multiple(
+                    [rs, en, rw, d4, d5, d6, d7, a],
+                    [
+                        level(0),
+                        level(1),
+                        level(2),
+                        level(7),
+                        level(8),
+                        level(9),
+                        level(10),
+                        level(11),
+                    ],
+                )
+                .is_ok()
+            }
+            (Some(rs), Some(en), None, Some(d4), Some(d5), Some(d6), Some(d7), Some(a)) => {
+                port_expander::write_multiple(
+                    [rs, en, d4, d5, d6, d7, a],
+                    [
+                        level(0),
+                        level(1),
+                        level(7),
+                        level(8),
+                        level(9),
+                        level(10),
+                        level(11),
+                    ],
+                )
+                .is_ok()
+            }
+            _ => false,
+        }
     }
 
     /// Creates a new [`LcdDisplay`] using PCF8572A for interfacing
@@ -94,4 +160,238 @@ where
     pub fn new_pcf8574(expander: &'a mut Pcf8574<M>, delay: D) -> Self {
         Self::from_parts(expander.split(), delay)
     }
+
+    /// Creates a new [`LcdDisplay`] using PCF8574A for interfacing, for boards where the
+    /// LCD's RW pin is tied to GND. The unused P1 pin is returned alongside the display so
+    /// it can be used for a button (see [`ExpanderButtons`]).
+    ///
+    /// Check the board before using this. On most backpacks P1 is wired to RW, and an
+    /// expander pin that isn't driven floats high, so the display would stay in read mode
+    /// and fight the expander on the data lines. Use
+    /// [`new_pcf8574a`](LcdDisplay::new_pcf8574a) for those boards.
+    ///
+    /// This method is only available if the `i2c` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut i2c_expander = Pcf8574a::new(i2c_bus, true, true, true);
+    ///
+    /// let (lcd, p1) = LcdDisplay::new_pcf8574a_without_rw(&mut i2c_expander, delay);
+    /// let mut lcd: LcdDisplay<_,_> = lcd.build();
+    /// let mut buttons = ExpanderButtons::new([p1]);
+    /// ```
+    #[inline]
+    pub fn new_pcf8574a_without_rw(
+        expander: &'a mut Pcf8574a<M>,
+        delay: D,
+    ) -> (Self, Pin<'a, QuasiBidirectional, M>) {
+        Self::from_parts_without_rw(expander.split(), delay)
+    }
+
+    /// Creates a new [`LcdDisplay`] using PCF8574 for interfacing, for boards where the
+    /// LCD's RW pin is tied to GND. The unused P1 pin is returned alongside the display so
+    /// it can be used for a button (see [`ExpanderButtons`]).
+    ///
+    /// Only use this if RW really is tied to GND, not wired to P1. (See
+    /// [`new_pcf8574a_without_rw`](LcdDisplay::new_pcf8574a_without_rw))
+    ///
+    /// This method is only available if the `i2c` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut i2c_expander = Pcf8574::new(i2c_bus, true, true, true);
+    ///
+    /// let (lcd, p1) = LcdDisplay::new_pcf8574_without_rw(&mut i2c_expander, delay);
+    /// let mut lcd: LcdDisplay<_,_> = lcd.build();
+    /// let mut buttons = ExpanderButtons::new([p1]);
+    /// ```
+    #[inline]
+    pub fn new_pcf8574_without_rw(
+        expander: &'a mut Pcf8574<M>,
+        delay: D,
+    ) -> (Self, Pin<'a, QuasiBidirectional, M>) {
+        Self::from_parts_without_rw(expander.split(), delay)
+    }
+}
+
+/// The port expander pins connected to each pin of the display, for [`LcdDisplay::new_expander`]
+///
+/// Backpack boards wire the display to the expander in different orders, so the pins are
+/// given by name. The RW and backlight pins are optional, since some boards tie RW to GND
+/// or leave the backlight always on.
+///
+/// This type is only available if the `i2c` feature is enabled.
+pub struct ExpanderPins<P>
+where
+    P: OutputPin,
+{
+    /// Register select pin
+    pub rs: P,
+    /// Enable pin
+    pub en: P,
+    /// Read/write pin, if it is connected
+    pub rw: Option<P>,
+    /// Data pin D4
+    pub d4: P,
+    /// Data pin D5
+    pub d5: P,
+    /// Data pin D6
+    pub d6: P,
+    /// Data pin D7
+    pub d7: P,
+    /// Backlight pin, if it is connected
+    pub backlight: Option<P>,
+}
+
+impl<T, D> LcdDisplay<T, D>
+where
+    T: OutputPin + Sized,
+    D: DelayNs + Sized,
+{
+    /// Creates a new [`LcdDisplay`] from the pins of any port expander, in four-bit mode.
+    ///
+    /// This works with every device supported by `port-expander` (and anything else whose
+    /// pins implement [`OutputPin`]), so boards with other expander chips or wiring don't
+    /// need a dedicated constructor. Unlike [`new_pcf8574`], each pin is written on its
+    /// own, so an update of the data pins takes several bus transfers.
+    ///
+    /// This method is only available if the `i2c` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut i2c_expander = Mcp23x17::new_mcp23017(i2c_bus, false, false, false);
+    /// let pins = i2c_expander.split();
+    ///
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new_expander(
+    ///     ExpanderPins {
+    ///         rs: pins.gpb7.into_output().unwrap(),
+    ///         en: pins.gpb5.into_output().unwrap(),
+    ///         rw: Some(pins.gpb6.into_output().unwrap()),
+    ///         d4: pins.gpb4.into_output().unwrap(),
+    ///         d5: pins.gpb3.into_output().unwrap(),
+    ///         d6: pins.gpb2.into_output().unwrap(),
+    ///         d7: pins.gpb1.into_output().unwrap(),
+    ///         backlight: None,
+    ///     },
+    ///     delay,
+    /// )
+    /// .build();
+    /// ```
+    ///
+    /// [`new_pcf8574`]: LcdDisplay::new_pcf8574
+    pub fn new_expander(pins: ExpanderPins<T>, delay: D) -> Self {
+        let ExpanderPins {
+            rs,
+            en,
+            rw,
+            d4,
+            d5,
+            d6,
+            d7,
+            backlight,
+        } = pins;
+
+        let mut lcd = LcdDisplay::new(rs, en, delay).with_half_bus(d4, d5, d6, d7);
+        if let Some(rw) = rw {
+            lcd = lcd.with_rw(rw);
+        }
+        if let Some(backlight) = backlight {
+            lcd = lcd.with_backlight(backlight);
+        }
+        lcd
+    }
+}
+
+/// Buttons connected to spare port expander pins
+///
+/// Each button should connect its pin to GND when pressed. Port expanders like the PCF8574
+/// weakly pull their quasi-bidirectional pins high, so no external pull-up is needed. Any
+/// [`InputPin`] works, so the buttons can be on the LCD's expander or a second one on the
+/// same bus. Up to 16 buttons are supported.
+///
+/// This type is only available if the `i2c` feature is enabled.
+pub struct ExpanderButtons<P, const N: usize>
+where
+    P: InputPin,
+{
+    pins: [P; N],
+    last: u16,
+}
+
+impl<P, const N: usize> ExpanderButtons<P, N>
+where
+    P: InputPin,
+{
+    /// Create a set of buttons from input pins. Button `i` is reported as bit `i`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut keys = Pcf8574::new(i2c_bus, false, false, false);
+    /// let pcf8574::Parts { p0, p1, p2, p3, .. } = keys.split();
+    ///
+    /// let mut buttons = ExpanderButtons::new([p0, p1, p2, p3]);
+    /// ```
+    pub fn new(pins: [P; N]) -> Self {
+        Self { pins, last: 0 }
+    }
+
+    /// Read the current state of every button as a bitmask, where a set bit means the
+    /// button is pressed. Pins that can't be read are reported as released.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut buttons: ExpanderButtons<_, 4> = ...;
+    /// if buttons.read() & 0b0001 != 0 {
+    ///     lcd.print("Button 0");
+    /// }
+    /// ```
+    pub fn read(&mut self) -> u16 {
+        self.pins
+            .iter_mut()
+            .take(16)
+            .enumerate()
+            .fold(0, |mask, (i, pin)| match pin.is_low() {
+                Ok(true) => mask | (1 << i),
+                _ => mask,
+            })
+    }
+
+    /// Check if the button at `index` is currently pressed.
+    pub fn is_pressed(&mut self, index: usize) -> bool {
+        self.pins
+            .get_mut(index)
+            .map(|pin| pin.is_low().unwrap_or(false))
+            .unwrap_or(false)
+    }
+
+    /// Poll the buttons and return a bitmask of the buttons that were pressed since
+    /// the last call, so holding a button down only reports it once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut buttons: ExpanderButtons<_, 4> = ...;
+    /// loop {
+    ///     let pressed = buttons.poll();
+    ///     if pressed & 0b0010 != 0 {
+    ///         lcd.scroll_left(1);
+    ///     }
+    /// }
+    /// ```
+    pub fn poll(&mut self) -> u16 {
+        let current = self.read();
+        let pressed = current & !self.last;
+        self.last = current;
+        pressed
+    }
+
+    /// Release the underlying pins.
+    pub fn release(self) -> [P; N] {
+        self.pins
+    }
 }
@@ -0,0 +1,158 @@
+//! Battery level and signal strength icons that each use one CGRAM slot
+
+use crate::CharacterDisplay;
+
+/// Number of fill levels of the battery icon above empty
+const BATTERY_LEVELS: u8 = 6;
+
+/// Height of each bar of the signal icon, from left to right
+const BAR_HEIGHTS: [usize; 5] = [2, 3, 5, 6, 8];
+
+/// A single-cell icon drawn from a custom character in one CGRAM slot
+struct Icon {
+    col: u8,
+    row: u8,
+    slot: u8,
+    shown: Option<u8>,
+}
+
+impl Icon {
+    /// Create an icon at `col`, `row` that uses CGRAM `slot`.
+    fn new(col: u8, row: u8, slot: u8) -> Self {
+        Self {
+            col,
+            row,
+            slot: slot & 0x7,
+            shown: None,
+        }
+    }
+
+    /// Store `map` and show it in the icon's cell if `level` isn't already shown.
+    fn show<L>(&mut self, lcd: &mut L, level: u8, map: [u8; 8])
+    where
+        L: CharacterDisplay,
+    {
+        if self.shown == Some(level) {
+            return;
+        }
+        self.shown = Some(level);
+
+        let position = lcd.position();
+        lcd.set_character(self.slot, map);
+        lcd.set_position(self.col, self.row);
+        lcd.write(self.slot);
+        lcd.set_position(position.0, position.1);
+    }
+}
+
+/// Map a percentage to one of `levels` steps above zero, rounding to the nearest.
+fn level(percent: u8, levels: u8) -> u8 {
+    ((percent.min(100) as u16 * levels as u16 + 50) / 100) as u8
+}
+
+/// A battery icon that fills up from the bottom with the charge level
+///
+/// The icon takes one cell and one CGRAM slot, and has six fill levels above empty.
+/// [update][BatteryIcon::update] only rewrites the custom character when the shown
+/// level changes, so it can be called with every new reading. The cursor position is
+/// not changed.
+///
+/// # Examples
+///
+/// ```
+/// let mut lcd: LcdDisplay<_,_> = ...;
+/// let mut battery = BatteryIcon::new(15, 0, 6);
+///
+/// loop {
+///     battery.update(&mut lcd, read_charge_percent());
+/// }
+/// ```
+pub struct BatteryIcon {
+    icon: Icon,
+}
+
+impl BatteryIcon {
+    /// Create a battery icon at `col`, `row` that uses CGRAM `slot` (0-7).
+    pub fn new(col: u8, row: u8, slot: u8) -> Self {
+        Self {
+            icon: Icon::new(col, row, slot),
+        }
+    }
+
+    /// Show the charge level, from 0 to 100 percent. Larger values are treated as 100.
+    pub fn update<L>(&mut self, lcd: &mut L, percent: u8)
+    where
+        L: CharacterDisplay,
+    {
+        let level = level(percent, BATTERY_LEVELS);
+
+        let mut map = [0b01110, 0b10001, 0, 0, 0, 0, 0, 0b11111];
+        for (line, pixels) in map[1..7].iter_mut().rev().enumerate() {
+            *pixels = match line < level as usize {
+                true => 0b11111,
+                false => 0b10001,
+            };
+        }
+        self.icon.show(lcd, level, map);
+    }
+
+    /// Redraw the icon on the next update, for example after the display was cleared.
+    pub fn invalidate(&mut self) {
+        self.icon.shown = None;
+    }
+}
+
+/// A signal strength icon with five bars of increasing height
+///
+/// The icon takes one cell and one CGRAM slot. Bars above the signal level are shown
+/// as a dot on the bottom row, so the icon stays visible with no signal.
+/// [update][SignalIcon::update] only rewrites the custom character when the number of
+/// bars changes. The cursor position is not changed.
+///
+/// # Examples
+///
+/// ```
+/// let mut lcd: LcdDisplay<_,_> = ...;
+/// let mut signal = SignalIcon::new(14, 0, 7);
+///
+/// loop {
+///     signal.update(&mut lcd, radio.rssi_percent());
+/// }
+/// ```
+pub struct SignalIcon {
+    icon: Icon,
+}
+
+impl SignalIcon {
+    /// Create a signal icon at `col`, `row` that uses CGRAM `slot` (0-7).
+    pub fn new(col: u8, row: u8, slot: u8) -> Self {
+        Self {
+            icon: Icon::new(col, row, slot),
+        }
+    }
+
+    /// Show the signal strength, from 0 to 100 percent. Larger values are treated as 100.
+    pub fn update<L>(&mut self, lcd: &mut L, percent: u8)
+    where
+        L: CharacterDisplay,
+    {
+        let bars = level(percent, BAR_HEIGHTS.len() as u8);
+
+        let mut map = [0; 8];
+        for (bar, height) in BAR_HEIGHTS.iter().enumerate() {
+            let height = match bar < bars as usize {
+                true => *height,
+                false => 1,
+            };
+            for pixels in map[8 - height..].iter_mut() {
+                *pixels |= 0b10000 >> bar;
+            }
+        }
+        self.icon.show(lcd, bars, map);
+    }
+
+    /// Redraw the icon on the next update, for example after the display was cleared.
+    pub fn invalidate(&mut self) {
+        self.icon.shown = None;
+    }
+}
@@ -0,0 +1,156 @@
+//! Hardware-independent input events for menus, editors and other interactive screens
+
+use crate::Button;
+
+/// A navigation event from the user
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InputEvent {
+    /// Move up, or increase a value
+    Up,
+
+    /// Move down, or decrease a value
+    Down,
+
+    /// Move left
+    Left,
+
+    /// Move right
+    Right,
+
+    /// Confirm or enter the selected item
+    Select,
+
+    /// Cancel or leave the current screen
+    Back,
+}
+
+/// A source of [InputEvent]s, like buttons, a rotary encoder or a keypad
+///
+/// Interactive screens only consume this trait, so any input hardware can drive them.
+/// Closures returning `Option<InputEvent>` implement it directly, which is usually the
+/// easiest way to hook up custom hardware.
+///
+/// # Examples
+///
+/// ```
+/// let mut encoder = RotaryEncoder::new(pin_a, pin_b);
+/// let mut input = || match encoder.update() {
+///     Direction::Clockwise => Some(InputEvent::Down),
+///     Direction::Anticlockwise => Some(InputEvent::Up),
+///     Direction::None => None,
+/// };
+///
+/// if let Some(event) = input.poll() {
+///     // ...
+/// }
+/// ```
+pub trait InputSource {
+    /// Get the next event, or None if nothing happened since the last poll. This should
+    /// not block, so it can be called from a main loop.
+    fn poll(&mut self) -> Option<InputEvent>;
+}
+
+impl<F> InputSource for F
+where
+    F: FnMut() -> Option<InputEvent>,
+{
+    fn poll(&mut self) -> Option<InputEvent> {
+        self()
+    }
+}
+
+impl From<Button> for InputEvent {
+    fn from(button: Button) -> Self {
+        match button {
+            Button::Right => InputEvent::Right,
+            Button::Up => InputEvent::Up,
+            Button::Down => InputEvent::Down,
+            Button::Left => InputEvent::Left,
+            Button::Select => InputEvent::Select,
+        }
+    }
+}
+
+/// The buttons on the LCD Keypad Shield as an [InputSource]
+///
+/// `read` is called on every poll and must return a 10-bit ADC reading of the button
+/// pin (A0). An event is reported once when a button is pressed, not while it is held.
+/// The shield has no BACK button, so [InputEvent::Back] is never reported.
+///
+/// A reading only counts once it is the same for several polls in a row (see
+/// [with_debounce][KeypadInput::with_debounce]), so contact bounce and readings taken
+/// while the voltage moves between two buttons don't report the wrong button.
+///
+/// # Examples
+///
+/// ```
+/// let mut adc = arduino_hal::Adc::new(dp.ADC, Default::default());
+/// let a0 = pins.a0.into_analog_input(&mut adc);
+///
+/// let mut keypad = KeypadInput::new(|| a0.analog_read(&mut adc));
+/// if let Some(InputEvent::Select) = keypad.poll() {
+///     lcd.print("Selected!");
+/// }
+/// ```
+pub struct KeypadInput<F>
+where
+    F: FnMut() -> u16,
+{
+    read: F,
+    last: Option<Button>,
+    reading: Option<Button>,
+    count: u8,
+    debounce: u8,
+}
+
+impl<F> KeypadInput<F>
+where
+    F: FnMut() -> u16,
+{
+    /// Create keypad input from a function that reads the button pin.
+    pub fn new(read: F) -> Self {
+        Self {
+            read,
+            last: None,
+            reading: None,
+            count: 0,
+            debounce: 3,
+        }
+    }
+
+    /// Set how many polls in a row have to read the same button before a press or
+    /// release counts. Poll often enough that this covers a few milliseconds; a value of
+    /// 1 turns debouncing off. (Default is 3)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // polled every millisecond, so a press has to be stable for 5ms
+    /// let mut keypad = KeypadInput::new(|| a0.analog_read(&mut adc))
+    ///     .with_debounce(5);
+    /// ```
+    pub fn with_debounce(mut self, polls: u8) -> Self {
+        self.debounce = polls.max(1);
+        self
+    }
+}
+
+impl<F> InputSource for KeypadInput<F>
+where
+    F: FnMut() -> u16,
+{
+    fn poll(&mut self) -> Option<InputEvent> {
+        let button = Button::from_adc((self.read)());
+        match button == self.reading {
+            true => self.count = self.count.saturating_add(1),
+            false => (self.reading, self.count) = (button, 1),
+        }
+
+        if self.count < self.debounce || button == self.last {
+            return None;
+        }
+        self.last = button;
+        button.map(InputEvent::from)
+    }
+}
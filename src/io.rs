@@ -0,0 +1,110 @@
+//! Pins whose direction can be switched, for reading from the display
+
+use embedded_hal::digital::{ErrorType, InputPin, OutputPin};
+
+/// A pin that can be switched between driving a line and reading it
+///
+/// Reading from the display (the busy flag, [self_test][crate::LcdDisplay::self_test],
+/// verified writes) needs the data lines to be released while the display drives them.
+/// Open-drain pins can simply be set high, but push-pull pins would fight the display.
+/// Data pins that implement IoPin are switched to input for every read and back to
+/// output afterwards, once [with_io_pins][crate::LcdDisplay::with_io_pins] is set.
+///
+/// HAL pins with runtime direction (often called dynamic or flexible pins) can be wrapped
+/// in a [SwitchedPin], and embedded-hal 0.2 `IoPin` pairs in a `LegacyIoPin` (with the
+/// `embedded-hal-02` feature).
+pub trait IoPin: OutputPin + InputPin {
+    /// Stop driving the pin so it can be read.
+    fn set_input(&mut self) -> Result<(), Self::Error>;
+
+    /// Drive the pin again.
+    fn set_output(&mut self) -> Result<(), Self::Error>;
+}
+
+/// A pin with functions that switch its direction
+///
+/// Wraps a pin that can already be driven and read, along with the HAL-specific calls
+/// that change its direction. Non-capturing closures can be given for both.
+///
+/// # Examples
+///
+/// ```
+/// let d4 = SwitchedPin::new(
+///     gpioa.pa4.into_dynamic(),
+///     |pin| pin.make_floating_input(),
+///     |pin| pin.make_push_pull_output(),
+/// );
+/// ...
+///
+/// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+///     .with_half_bus(d4, d5, d6, d7)
+///     .with_rw(rw)
+///     .with_io_pins()
+///     .with_busy_flag()
+///     .build();
+/// ```
+pub struct SwitchedPin<P> {
+    pin: P,
+    input: fn(&mut P),
+    output: fn(&mut P),
+}
+
+impl<P> SwitchedPin<P> {
+    /// Wrap a pin with the functions that switch it to input and to output.
+    pub fn new(pin: P, input: fn(&mut P), output: fn(&mut P)) -> Self {
+        Self { pin, input, output }
+    }
+
+    /// Release the underlying pin.
+    pub fn release(self) -> P {
+        self.pin
+    }
+}
+
+impl<P> ErrorType for SwitchedPin<P>
+where
+    P: ErrorType,
+{
+    type Error = P::Error;
+}
+
+impl<P> OutputPin for SwitchedPin<P>
+where
+    P: OutputPin,
+{
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.pin.set_low()
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.pin.set_high()
+    }
+}
+
+impl<P> InputPin for SwitchedPin<P>
+where
+    P: InputPin,
+{
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        self.pin.is_high()
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self.pin.is_low()
+    }
+}
+
+impl<P> IoPin for SwitchedPin<P>
+where
+    P: OutputPin + InputPin,
+{
+    fn set_input(&mut self) -> Result<(), Self::Error> {
+        (self.input)(&mut self.pin);
+        Ok(())
+    }
+
+    fn set_output(&mut self) -> Result<(), Self::Error> {
+        (self.output)(&mut self.pin);
+        Ok(())
+    }
+}
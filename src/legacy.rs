@@ -0,0 +1,221 @@
+//! Adapters for pins and delays from HALs built on embedded-hal 0.2
+
+use crate::IoPin;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{ErrorKind, ErrorType, InputPin, OutputPin};
+use embedded_hal_02::blocking::delay::DelayUs;
+use embedded_hal_02::digital::v2 as legacy;
+use embedded_hal_02::digital::v2::PinState;
+
+/// A pin from a HAL built on embedded-hal 0.2
+///
+/// Older HALs implement the embedded-hal 0.2 pin traits, often with `()` or `void::Void`
+/// as the error type, which [LcdDisplay][crate::LcdDisplay] can't use directly. Wrapping
+/// each pin in a LegacyPin makes it an embedded-hal 1.0 pin, with any error reported as
+/// [ErrorKind::Other]. Pins that implement the 0.2 `InputPin` trait can also be read.
+///
+/// This type is only available if the `embedded-hal-02` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// let rs = LegacyPin::new(gpioa.pa0.into_push_pull_output());
+/// let en = LegacyPin::new(gpioa.pa1.into_push_pull_output());
+/// ...
+///
+/// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, LegacyDelay::new(delay))
+///     .with_half_bus(d4, d5, d6, d7)
+///     .build();
+/// ```
+pub struct LegacyPin<P> {
+    pin: P,
+}
+
+impl<P> LegacyPin<P> {
+    /// Wrap an embedded-hal 0.2 pin.
+    pub fn new(pin: P) -> Self {
+        Self { pin }
+    }
+
+    /// Release the underlying pin.
+    pub fn release(self) -> P {
+        self.pin
+    }
+}
+
+impl<P> ErrorType for LegacyPin<P> {
+    type Error = ErrorKind;
+}
+
+impl<P> OutputPin for LegacyPin<P>
+where
+    P: legacy::OutputPin,
+{
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.pin.set_low().map_err(|_| ErrorKind::Other)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.pin.set_high().map_err(|_| ErrorKind::Other)
+    }
+}
+
+impl<P> InputPin for LegacyPin<P>
+where
+    P: legacy::InputPin,
+{
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        legacy::InputPin::is_high(&self.pin).map_err(|_| ErrorKind::Other)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        legacy::InputPin::is_low(&self.pin).map_err(|_| ErrorKind::Other)
+    }
+}
+
+/// The current type of a [LegacyIoPin]
+enum Side<I, O> {
+    Input(I),
+    Output(O),
+}
+
+/// A pair of embedded-hal 0.2 pin types that convert into each other
+///
+/// HALs built on embedded-hal 0.2 change pin direction by converting between an input
+/// and an output type with the `IoPin` trait. A LegacyIoPin holds whichever of the two
+/// the pin currently is and implements [IoPin], so it can be used as a data pin with
+/// [with_io_pins][crate::LcdDisplay::with_io_pins]. Writing while it's an input or
+/// reading while it's an output reports [ErrorKind::Other], and so does every use after
+/// a failed conversion.
+///
+/// This type is only available if the `embedded-hal-02` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// let d4 = LegacyIoPin::new(gpioa.pa4.into_push_pull_output());
+/// ...
+///
+/// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, LegacyDelay::new(delay))
+///     .with_half_bus(d4, d5, d6, d7)
+///     .with_rw(rw)
+///     .with_io_pins()
+///     .build();
+/// ```
+pub struct LegacyIoPin<I, O> {
+    pin: Option<Side<I, O>>,
+}
+
+impl<I, O> LegacyIoPin<I, O> {
+    /// Wrap an embedded-hal 0.2 pin that is currently an output.
+    pub fn new(pin: O) -> Self {
+        Self {
+            pin: Some(Side::Output(pin)),
+        }
+    }
+}
+
+impl<I, O> ErrorType for LegacyIoPin<I, O> {
+    type Error = ErrorKind;
+}
+
+impl<I, O> OutputPin for LegacyIoPin<I, O>
+where
+    O: legacy::OutputPin,
+{
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        match &mut self.pin {
+            Some(Side::Output(pin)) => pin.set_low().map_err(|_| ErrorKind::Other),
+            _ => Err(ErrorKind::Other),
+        }
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        match &mut self.pin {
+            Some(Side::Output(pin)) => pin.set_high().map_err(|_| ErrorKind::Other),
+            _ => Err(ErrorKind::Other),
+        }
+    }
+}
+
+impl<I, O> InputPin for LegacyIoPin<I, O>
+where
+    I: legacy::InputPin,
+{
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        match &self.pin {
+            Some(Side::Input(pin)) => pin.is_high().map_err(|_| ErrorKind::Other),
+            _ => Err(ErrorKind::Other),
+        }
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        match &self.pin {
+            Some(Side::Input(pin)) => pin.is_low().map_err(|_| ErrorKind::Other),
+            _ => Err(ErrorKind::Other),
+        }
+    }
+}
+
+impl<I, O> IoPin for LegacyIoPin<I, O>
+where
+    I: legacy::InputPin + legacy::IoPin<I, O>,
+    O: legacy::OutputPin + legacy::IoPin<I, O>,
+{
+    fn set_input(&mut self) -> Result<(), Self::Error> {
+        self.pin = match self.pin.take() {
+            Some(Side::Output(pin)) => pin.into_input_pin().ok().map(Side::Input),
+            side => side,
+        };
+        match self.pin {
+            Some(Side::Input(_)) => Ok(()),
+            _ => Err(ErrorKind::Other),
+        }
+    }
+
+    fn set_output(&mut self) -> Result<(), Self::Error> {
+        self.pin = match self.pin.take() {
+            Some(Side::Input(pin)) => pin.into_output_pin(PinState::High).ok().map(Side::Output),
+            side => side,
+        };
+        match self.pin {
+            Some(Side::Output(_)) => Ok(()),
+            _ => Err(ErrorKind::Other),
+        }
+    }
+}
+
+/// A delay from a HAL built on embedded-hal 0.2
+///
+/// Wraps anything that implements the embedded-hal 0.2 `DelayUs<u32>` trait, so it can be
+/// given to [LcdDisplay][crate::LcdDisplay] along with [LegacyPin]s. Delays are rounded up
+/// to whole microseconds. This type is only available if the `embedded-hal-02` feature
+/// is enabled.
+pub struct LegacyDelay<D> {
+    delay: D,
+}
+
+impl<D> LegacyDelay<D> {
+    /// Wrap an embedded-hal 0.2 delay.
+    pub fn new(delay: D) -> Self {
+        Self { delay }
+    }
+
+    /// Release the underlying delay.
+    pub fn release(self) -> D {
+        self.delay
+    }
+}
+
+impl<D> DelayNs for LegacyDelay<D>
+where
+    D: DelayUs<u32>,
+{
+    fn delay_ns(&mut self, ns: u32) {
+        self.delay.delay_us(ns.div_ceil(1000));
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        self.delay.delay_us(us);
+    }
+}
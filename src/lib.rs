@@ -65,11 +65,103 @@
 //! ```
 //!
 
+mod aip31068;
+mod backpack;
+mod big;
+mod buffered;
+mod character;
+mod charset;
+#[cfg(any(feature = "cortex-m", feature = "avr-delay"))]
+mod cycles;
 mod display;
 mod errors;
+mod font;
+#[cfg(feature = "ufmt")]
+mod format;
+mod frame;
+mod glyph;
+#[cfg(feature = "embedded-graphics")]
+mod graphics;
 #[cfg(feature = "i2c")]
 #[doc(hidden)]
 pub mod i2c;
+mod indicator;
+mod input;
+mod io;
+#[cfg(feature = "embedded-hal-02")]
+mod legacy;
+#[cfg(feature = "logger")]
+mod logger;
+mod marquee;
+mod multi;
+mod newhaven;
+mod parse;
+mod pin;
+#[cfg(all(feature = "progmem", target_arch = "avr"))]
+mod progmem;
+mod scene;
+mod scrollbar;
+mod shield;
+mod shift;
+mod si;
+mod sparkline;
+mod status;
+mod storage;
+mod tabs;
+#[cfg(feature = "ufmt")]
+mod tee;
+mod time;
+#[cfg(feature = "nb")]
+mod timer;
+mod ui;
 
+pub use aip31068::{Aip31068Lcd, AIP31068_ADDRESS};
+pub use backpack::Backpack;
+pub use big::BigFont;
+pub use buffered::{BufferedLcd, Rotation, Snapshot};
+pub use character::CharacterDisplay;
+pub use charset::{Arrow, Rom, Unmappable};
+#[cfg(any(feature = "cortex-m", feature = "avr-delay"))]
+pub use cycles::CycleDelay;
 pub use display::*;
 pub use errors::Error;
+#[cfg(feature = "ufmt")]
+pub use format::{Fixed, FormatBuffer};
+pub use frame::{Window, FRAME_SLOT};
+pub use glyph::{glyph, rotate_glyph};
+#[cfg(feature = "embedded-graphics")]
+pub use graphics::{mono_glyph, GlyphCanvas};
+#[cfg(feature = "i2c")]
+pub use i2c::{ExpanderButtons, ExpanderPins};
+pub use indicator::{BatteryIcon, SignalIcon};
+pub use input::{InputEvent, InputSource, KeypadInput};
+pub use io::{IoPin, SwitchedPin};
+#[cfg(feature = "embedded-hal-02")]
+pub use legacy::{LegacyDelay, LegacyIoPin, LegacyPin};
+#[cfg(feature = "logger")]
+pub use logger::{LcdLog, LOG, LOG_SIZE};
+pub use marquee::Marquee;
+pub use multi::MultiLcd;
+pub use newhaven::{I2cTransport, NewhavenLcd, SpiTransport, Transport, NEWHAVEN_ADDRESS};
+pub use parse::ParseError;
+pub use pin::AnyPin;
+pub use scene::{Align, Field, Scene};
+pub use scrollbar::Scrollbar;
+pub use shield::{Button, ShieldBacklight};
+pub use shift::{ShiftPin, ShiftPins, ShiftRegister};
+pub use si::{SiValue, SI_MAX_WIDTH};
+pub use sparkline::Sparkline;
+pub use status::{StatusLog, StatusRow};
+pub use storage::{GlyphSet, GLYPH_SET_SIZE};
+pub use tabs::{TabStops, MAX_TAB_STOPS};
+#[cfg(feature = "ufmt")]
+pub use tee::Tee;
+pub use time::Microseconds;
+#[cfg(feature = "nb")]
+pub use timer::{CountDown, TimerDelay};
+pub use ui::{Animate, BlinkText, Spinner, Ui};
+
+// used by the uprint_at and lcd_log macros, so callers don't need their own ufmt dependency
+#[cfg(feature = "ufmt")]
+#[doc(hidden)]
+pub use ufmt;
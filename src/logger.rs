@@ -0,0 +1,153 @@
+//! A log that can be written from interrupt handlers and shown on the display later
+
+use crate::CharacterDisplay;
+use core::cell::RefCell;
+use critical_section::Mutex;
+
+/// Size of the global [LOG] in bytes
+pub const LOG_SIZE: usize = 64;
+
+/// The log written by [lcd_log!][crate::lcd_log!]
+pub static LOG: LcdLog<LOG_SIZE> = LcdLog::new();
+
+/// Queued bytes, stored as a ring buffer
+struct Queue<const N: usize> {
+    bytes: [u8; N],
+    start: usize,
+    len: usize,
+}
+
+/// A queue of log text, shared between interrupt handlers and the main loop
+///
+/// Writing to the display is slow and can't be done from an interrupt, so text is
+/// [pushed][LcdLog::push] into a fixed-size buffer inside a critical section and the
+/// main loop shows it later with [flush][LcdLog::flush]. When the buffer is full the
+/// oldest text is dropped. The global [LOG] is used by [lcd_log!][crate::lcd_log!], but
+/// other logs can be declared as statics too.
+///
+/// This type is only available if the `logger` feature is enabled, and needs a
+/// [critical-section](https://crates.io/crates/critical-section) implementation for
+/// the target (usually enabled with a feature of the HAL or the cortex-m crate).
+///
+/// # Examples
+///
+/// ```
+/// #[interrupt]
+/// fn TIMER0() {
+///     lcd_log!("fault {}", code);
+/// }
+///
+/// loop {
+///     ag_lcd::LOG.flush(&mut lcd);
+/// }
+/// ```
+pub struct LcdLog<const N: usize> {
+    queue: Mutex<RefCell<Queue<N>>>,
+}
+
+impl<const N: usize> LcdLog<N> {
+    /// Create an empty log.
+    pub const fn new() -> Self {
+        Self {
+            queue: Mutex::new(RefCell::new(Queue {
+                bytes: [0; N],
+                start: 0,
+                len: 0,
+            })),
+        }
+    }
+
+    /// Add text to the log. A `\n` starts a new row when the log is flushed. This can
+    /// be called from interrupt handlers and panic handlers.
+    pub fn push(&self, text: &str) {
+        if N == 0 {
+            return;
+        }
+
+        critical_section::with(|cs| {
+            let mut queue = self.queue.borrow_ref_mut(cs);
+            for byte in text.bytes() {
+                let end = (queue.start + queue.len) % N;
+                queue.bytes[end] = byte;
+                if queue.len < N {
+                    queue.len += 1;
+                } else {
+                    // drop the oldest byte
+                    queue.start = (queue.start + 1) % N;
+                }
+            }
+        });
+    }
+
+    /// Check if there is no text waiting to be shown.
+    pub fn is_empty(&self) -> bool {
+        critical_section::with(|cs| self.queue.borrow_ref(cs).len == 0)
+    }
+
+    /// Show the queued text at the cursor position, emptying the log. Each `\n` moves
+    /// to the start of the next row (after the last row comes the first) and blanks it.
+    /// Call this from the main loop, not from an interrupt.
+    pub fn flush<L>(&self, lcd: &mut L)
+    where
+        L: CharacterDisplay,
+    {
+        while let Some(byte) = self.pop() {
+            match byte {
+                b'\n' => {
+                    let row = (lcd.position().1 + 1) % lcd.rows();
+                    lcd.set_position(0, row);
+                    for _ in 0..lcd.cols() {
+                        lcd.write(b' ');
+                    }
+                    lcd.set_position(0, row);
+                }
+                byte => lcd.write(byte),
+            }
+        }
+    }
+
+    /// Take the oldest queued byte.
+    fn pop(&self) -> Option<u8> {
+        critical_section::with(|cs| {
+            let mut queue = self.queue.borrow_ref_mut(cs);
+            if queue.len == 0 {
+                return None;
+            }
+
+            let byte = queue.bytes[queue.start];
+            queue.start = (queue.start + 1) % N;
+            queue.len -= 1;
+            Some(byte)
+        })
+    }
+}
+
+impl<const N: usize> Default for LcdLog<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Format a line of text with ufmt and add it to the global [LOG][crate::LOG], to be
+/// shown on the display by the main loop. (See [LcdLog])
+///
+/// This is safe to call from interrupt handlers. Each call starts a new row with at
+/// most 40 bytes of text. This macro is only available if the `logger` feature is
+/// enabled.
+///
+/// # Examples
+///
+/// ```
+/// lcd_log!("fault {}", code);
+/// ```
+#[macro_export]
+macro_rules! lcd_log {
+    ($($arg:tt)*) => {{
+        use $crate::ufmt;
+        // one push, so lines from interrupts can't end up in the middle
+        let mut buffer = $crate::FormatBuffer::<41>::new();
+        let _ = ufmt::uWrite::write_str(&mut buffer, "\n");
+        let _ = ufmt::uwrite!(&mut buffer, $($arg)*);
+        $crate::LOG.push(buffer.as_str());
+    }};
+}
@@ -0,0 +1,153 @@
+//! Smooth, pixel-by-pixel scrolling text drawn with custom characters
+
+use crate::font::{self, FONT_WIDTH};
+use crate::CharacterDisplay;
+
+/// Width of a character cell in pixels
+const CELL_WIDTH: usize = 5;
+
+/// Width of a character of text in pixels, including the gap after it
+const CHAR_WIDTH: usize = FONT_WIDTH + 1;
+
+/// A line of text that scrolls smoothly across a few cells, one pixel at a time
+///
+/// Scrolling with [scroll_left][crate::LcdDisplay::scroll_left] moves text a whole character
+/// at a time, which looks jumpy. A marquee instead draws the visible part of the text
+/// into custom characters (CGRAM) and redraws them one pixel further along on every
+/// [tick][Marquee::tick]. Each cell uses one of the eight CGRAM slots, so a marquee is
+/// at most 8 cells wide, and the text is drawn with a built-in 5x7 ASCII font.
+///
+/// # Examples
+///
+/// ```
+/// let mut lcd: LcdDisplay<_,_> = ...;
+/// let mut marquee = Marquee::new("Breaking news!", 4, 0, 8);
+///
+/// marquee.show(&mut lcd);
+/// loop {
+///     marquee.tick(&mut lcd);
+///     delay.delay_ms(40);
+/// }
+/// ```
+pub struct Marquee<'a> {
+    text: &'a str,
+    col: u8,
+    row: u8,
+    cells: u8,
+    first: u8,
+    offset: usize,
+}
+
+impl<'a> Marquee<'a> {
+    /// Create a marquee for `text`, shown in `cells` cells (1-8) starting at `col`, `row`.
+    /// The text starts off-screen and scrolls in from the right.
+    pub fn new(text: &'a str, col: u8, row: u8, cells: u8) -> Self {
+        Self {
+            text,
+            col,
+            row,
+            cells: cells.clamp(1, 8),
+            first: 0,
+            offset: 0,
+        }
+    }
+
+    /// Set the first CGRAM slot used by the marquee, to leave other slots free for
+    /// custom characters. (Default is 0)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // use slots 4-7, leaving 0-3 for other custom characters
+    /// let marquee = Marquee::new("Hello", 0, 0, 4).with_first_slot(4);
+    /// ```
+    pub fn with_first_slot(mut self, first: u8) -> Self {
+        self.first = first & 0x7;
+        self
+    }
+
+    /// Replace the text and start scrolling it in from the right.
+    pub fn set_text(&mut self, text: &'a str) {
+        self.text = text;
+        self.offset = 0;
+    }
+
+    /// Draw the current frame and write the custom characters into the marquee cells.
+    /// This only has to be called again if the cells are overwritten or cleared.
+    pub fn show<L>(&mut self, lcd: &mut L)
+    where
+        L: CharacterDisplay,
+    {
+        let position = lcd.position();
+        self.upload(lcd);
+
+        lcd.set_position(self.col, self.row);
+        for cell in 0..self.cells {
+            lcd.write(self.slot(cell));
+        }
+        lcd.set_position(position.0, position.1);
+    }
+
+    /// Move the text one pixel to the left and redraw it. When the text has scrolled
+    /// completely out of view it starts again from the right.
+    pub fn tick<L>(&mut self, lcd: &mut L)
+    where
+        L: CharacterDisplay,
+    {
+        self.offset = (self.offset + 1) % self.length();
+
+        let position = lcd.position();
+        self.upload(lcd);
+        lcd.set_position(position.0, position.1);
+    }
+
+    /// Upload the visible part of the text to the CGRAM slots.
+    fn upload<L>(&self, lcd: &mut L)
+    where
+        L: CharacterDisplay,
+    {
+        for cell in 0..self.cells {
+            let mut map = [0; 8];
+            for x in 0..CELL_WIDTH {
+                let column = self.column(self.offset + cell as usize * CELL_WIDTH + x);
+                for (row, line) in map.iter_mut().enumerate() {
+                    if (column >> row) & 1 != 0 {
+                        *line |= 1 << (CELL_WIDTH - 1 - x);
+                    }
+                }
+            }
+            lcd.set_character(self.slot(cell), map);
+        }
+    }
+
+    /// Get the column of pixels at position `x`, where the text is preceded by
+    /// a blank stretch as wide as the marquee.
+    fn column(&self, x: usize) -> u8 {
+        let x = x % self.length();
+        let width = self.width();
+        if x < width {
+            return 0;
+        }
+
+        let x = x - width;
+        match self.text.chars().nth(x / CHAR_WIDTH) {
+            Some(ch) if x % CHAR_WIDTH < FONT_WIDTH => font::columns(ch)[x % CHAR_WIDTH],
+            _ => 0,
+        }
+    }
+
+    /// Get the CGRAM slot for a cell of the marquee.
+    fn slot(&self, cell: u8) -> u8 {
+        (self.first + cell) & 0x7
+    }
+
+    /// Get the width of the marquee in pixels.
+    fn width(&self) -> usize {
+        self.cells as usize * CELL_WIDTH
+    }
+
+    /// Get the length of one full scroll, in pixels.
+    fn length(&self) -> usize {
+        self.width() + self.text.chars().count() * CHAR_WIDTH
+    }
+}
@@ -0,0 +1,181 @@
+//! Allows driving several displays that share RS, RW and data lines but have their own EN pin
+
+use crate::display::Context;
+use crate::{Blink, CharacterDisplay, Cursor, LcdDisplay};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+
+/// A group of LCD displays sharing a single bus
+///
+/// HD44780 controllers ignore the bus until their EN pin is pulsed, so any number of
+/// displays can share the RS, RW and data pins as long as each one has its own enable
+/// line. The first display uses the EN pin the [LcdDisplay] was created with and the
+/// others use the `N` extra enable pins given to [new][MultiLcd::new].
+///
+/// Each display keeps its own state: settings, cursor position and display shift,
+/// sleep and screensaver state, custom character slots, and the controller options
+/// (controller, double height, icons and contrast, character ROM). The bus itself is
+/// shared, so the pins, delays, retry policy and hooks are the same for every display,
+/// and so is the backlight pin with its timeout and notifications.
+pub struct MultiLcd<T, D, const N: usize>
+where
+    T: OutputPin + Sized,
+    D: DelayNs + Sized,
+{
+    lcd: LcdDisplay<T, D>,
+    enables: [Option<T>; N],
+    contexts: [Context; N],
+    ids: [usize; N],
+    selected: usize,
+}
+
+impl<T, D, const N: usize> MultiLcd<T, D, N>
+where
+    T: OutputPin + Sized,
+    D: DelayNs + Sized,
+{
+    /// Create a new group of displays from a configured (but not built) [LcdDisplay]
+    /// and the enable pins of the additional displays. Every display starts with the
+    /// settings of `lcd`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// ...
+    /// let lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en0, delay)
+    ///     .with_half_bus(d4, d5, d6, d7)
+    ///     .with_lines(Lines::TwoLines);
+    ///
+    /// let mut displays = MultiLcd::new(lcd, [en1, en2]).build();
+    ///
+    /// displays.select(0).unwrap().print("Left panel");
+    /// displays.select(2).unwrap().print("Right panel");
+    /// ```
+    pub fn new(lcd: LcdDisplay<T, D>, mut enables: [T; N]) -> Self {
+        // displays that aren't selected must never see a pulse
+        for en in enables.iter_mut() {
+            let _ = en.set_low();
+        }
+
+        let context = lcd.context();
+        let mut index = 0;
+        Self {
+            lcd,
+            enables: enables.map(Some),
+            contexts: [(); N].map(|_| context.clone()),
+            ids: [(); N].map(|_| {
+                index += 1;
+                index
+            }),
+            selected: 0,
+        }
+    }
+
+    /// Initialize every display in the group. (See [LcdDisplay::build])
+    pub fn build(mut self) -> Self {
+        for index in 0..self.len() {
+            self.select(index);
+            self.lcd = self.lcd.build();
+        }
+        self.select(0);
+        self
+    }
+
+    /// Select the display at `index` and return it. Index 0 is the display using the
+    /// original EN pin and indices 1 to N use the extra enable pins in order. Returns
+    /// `None` (leaving the selection unchanged) if the index is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut displays: MultiLcd<_,_,2> = ...;
+    /// if let Some(lcd) = displays.select(1) {
+    ///     lcd.print("Second display");
+    /// }
+    /// ```
+    pub fn select(&mut self, index: usize) -> Option<&mut LcdDisplay<T, D>> {
+        if index >= self.len() {
+            return None;
+        }
+
+        if index != self.selected {
+            let slot = self.ids.iter().position(|id| *id == index)?;
+            self.lcd
+                .swap_display(&mut self.enables[slot], &mut self.contexts[slot]);
+            self.ids[slot] = self.selected;
+            self.selected = index;
+        }
+
+        Some(&mut self.lcd)
+    }
+
+    /// Get the index of the currently selected display.
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Get the number of displays in the group.
+    pub fn len(&self) -> usize {
+        N + 1
+    }
+
+    /// Always false, because a group contains at least one display.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+}
+
+/// Draws on the selected display. (See [select][MultiLcd::select])
+impl<T, D, const N: usize> CharacterDisplay for MultiLcd<T, D, N>
+where
+    T: OutputPin + Sized,
+    D: DelayNs + Sized,
+{
+    fn cols(&self) -> u8 {
+        self.lcd.cols()
+    }
+
+    fn rows(&self) -> u8 {
+        self.lcd.rows()
+    }
+
+    fn print(&mut self, text: &str) {
+        self.lcd.print(text);
+    }
+
+    fn write(&mut self, value: u8) {
+        self.lcd.write(value);
+    }
+
+    fn set_position(&mut self, col: u8, row: u8) {
+        self.lcd.set_position(col, row);
+    }
+
+    fn position(&self) -> (u8, u8) {
+        self.lcd.position()
+    }
+
+    fn clear(&mut self) {
+        self.lcd.clear();
+    }
+
+    fn home(&mut self) {
+        self.lcd.home();
+    }
+
+    fn set_character(&mut self, location: u8, map: [u8; 8]) {
+        self.lcd.set_character(location, map);
+    }
+
+    fn set_cursor(&mut self, cursor: Cursor) {
+        self.lcd.set_cursor(cursor);
+    }
+
+    fn set_blink(&mut self, blink: Blink) {
+        self.lcd.set_blink(blink);
+    }
+
+    fn print_at(&mut self, col: u8, row: u8, text: &str) {
+        self.lcd.print_at(col, row, text);
+    }
+}
@@ -0,0 +1,576 @@
+//! Allows controlling Newhaven serial displays (NHD-0216K3Z, NHD-0420D3Z and similar)
+//!
+//! These displays have an on-board microcontroller that accepts text and prefixed
+//! commands over I2C, SPI or RS-232 instead of raw HD44780 instructions.
+
+use crate::time::YieldHook;
+use crate::{Blink, CharacterDisplay, Cursor, Display, Error, Microseconds, Scroll};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::I2c;
+use embedded_hal::spi::SpiDevice;
+
+/// Prefix byte sent before every command
+const PREFIX: u8 = 0xFE;
+
+/// Default 7-bit I2C address (0x50 in the datasheet's 8-bit notation)
+pub const NEWHAVEN_ADDRESS: u8 = 0x28;
+
+/// DDRAM offset of each row
+const ROW_OFFSETS: [u8; 4] = [0x00, 0x40, 0x14, 0x54];
+
+const CMD_DELAY: u32 = 100;
+const CLEAR_DELAY: u32 = 1500;
+const CHARACTER_DELAY: u32 = 200;
+const LEVEL_DELAY: u32 = 500;
+
+#[repr(u8)]
+#[allow(dead_code)]
+enum Command {
+    DisplayOn = 0x41,
+    DisplayOff = 0x42,
+    SetCursor = 0x45,
+    Home = 0x46,
+    UnderlineOn = 0x47,
+    UnderlineOff = 0x48,
+    BlinkOn = 0x4B,
+    BlinkOff = 0x4C,
+    Clear = 0x51,
+    SetContrast = 0x52,
+    SetBrightness = 0x53,
+    LoadCharacter = 0x54,
+    ShiftLeft = 0x55,
+    ShiftRight = 0x56,
+}
+
+/// A connection that bytes can be sent to a Newhaven display over
+///
+/// Implementations are provided for I2C ([I2cTransport]) and SPI ([SpiTransport]). For
+/// RS-232, implement this trait for your serial port.
+pub trait Transport {
+    /// Send bytes to the display, returning false if the bus reported an error
+    fn send(&mut self, bytes: &[u8]) -> bool;
+}
+
+/// I2C connection to a Newhaven display (the bus must run at 100kHz or less)
+pub struct I2cTransport<I>
+where
+    I: I2c,
+{
+    i2c: I,
+    address: u8,
+}
+
+impl<I> Transport for I2cTransport<I>
+where
+    I: I2c,
+{
+    fn send(&mut self, bytes: &[u8]) -> bool {
+        self.i2c.write(self.address, bytes).is_ok()
+    }
+}
+
+/// SPI connection to a Newhaven display (mode 3, 100kHz or less)
+pub struct SpiTransport<S>
+where
+    S: SpiDevice,
+{
+    spi: S,
+}
+
+impl<S> Transport for SpiTransport<S>
+where
+    S: SpiDevice,
+{
+    fn send(&mut self, bytes: &[u8]) -> bool {
+        self.spi.write(bytes).is_ok()
+    }
+}
+
+/// A Newhaven serial LCD display
+///
+/// Methods called on this struct will fail silently if the bus reports an error, in
+/// which case the error code is set to [Error::BusError].
+pub struct NewhavenLcd<B, D>
+where
+    B: Transport,
+    D: DelayNs,
+{
+    bus: B,
+    delay: D,
+    cols: u8,
+    rows: u8,
+    col: u8,
+    row: u8,
+    yielder: YieldHook,
+    code: Error,
+}
+
+impl<I, D> NewhavenLcd<I2cTransport<I>, D>
+where
+    I: I2c,
+    D: DelayNs,
+{
+    /// Create a new Newhaven display connected over I2C. The default address is
+    /// [NEWHAVEN_ADDRESS].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let i2c_bus = arduino_hal::i2c::I2c::new(peripherals.TWI, sda, scl, 50000);
+    ///
+    /// let mut lcd = NewhavenLcd::new_i2c(i2c_bus, NEWHAVEN_ADDRESS, delay);
+    /// lcd.clear();
+    /// lcd.print("Hello, World");
+    /// ```
+    pub fn new_i2c(i2c: I, address: u8, delay: D) -> Self {
+        Self::new(I2cTransport { i2c, address }, delay)
+    }
+}
+
+impl<S, D> NewhavenLcd<SpiTransport<S>, D>
+where
+    S: SpiDevice,
+    D: DelayNs,
+{
+    /// Create a new Newhaven display connected over SPI.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd = NewhavenLcd::new_spi(spi_device, delay);
+    /// lcd.print("Hello, World");
+    /// ```
+    pub fn new_spi(spi: S, delay: D) -> Self {
+        Self::new(SpiTransport { spi }, delay)
+    }
+}
+
+impl<B, D> NewhavenLcd<B, D>
+where
+    B: Transport,
+    D: DelayNs,
+{
+    /// Create a new Newhaven display over any [Transport], with a size of 16x2.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// struct Uart(Serial);
+    ///
+    /// impl Transport for Uart {
+    ///     fn send(&mut self, bytes: &[u8]) -> bool {
+    ///         bytes.iter().all(|b| nb::block!(self.0.write(*b)).is_ok())
+    ///     }
+    /// }
+    ///
+    /// let mut lcd = NewhavenLcd::new(Uart(serial), delay);
+    /// lcd.print("Hello, World");
+    /// ```
+    pub fn new(bus: B, delay: D) -> Self {
+        Self {
+            bus,
+            delay,
+            cols: 16,
+            rows: 2,
+            col: 0,
+            row: 0,
+            yielder: YieldHook::new(),
+            code: Error::None,
+        }
+    }
+
+    /// Set the number of columns (1-40) and rows (1-4) of the display. (Default is 16x2)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd = NewhavenLcd::new_spi(spi_device, delay)
+    ///     .with_size(20, 4);
+    /// lcd.set_position(0, 3);
+    /// ```
+    pub fn with_size(mut self, cols: u8, rows: u8) -> Self {
+        self.cols = cols.clamp(1, 40);
+        self.rows = rows.clamp(1, ROW_OFFSETS.len() as u8);
+        self
+    }
+
+    /// Set a hook that is called at least every `interval` during the driver's blocking
+    /// delays, so a watchdog can be fed while the display waits.
+    /// (See [LcdDisplay::with_yield_hook][crate::LcdDisplay::with_yield_hook])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// fn feed() {
+    ///     // reset the watchdog timer
+    /// }
+    ///
+    /// let mut lcd = NewhavenLcd::new_i2c(i2c_bus, NEWHAVEN_ADDRESS, delay)
+    ///     .with_yield_hook(feed, 1000);
+    /// ```
+    pub fn with_yield_hook<M: Microseconds>(mut self, hook: fn(), interval: M) -> Self {
+        self.set_yield_hook(Some(hook), interval);
+        self
+    }
+
+    /// Set or remove the hook called during blocking delays.
+    /// (See [with_yield_hook][NewhavenLcd::with_yield_hook])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: NewhavenLcd<_,_> = ...;
+    ///
+    /// lcd.set_yield_hook(Some(feed), 1000);
+    /// ...
+    /// lcd.set_yield_hook(None, 0);
+    /// ```
+    pub fn set_yield_hook<M: Microseconds>(&mut self, hook: Option<fn()>, interval: M) {
+        self.yielder.set(hook, interval.to_micros());
+    }
+
+    /// Print a message to the display.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: NewhavenLcd<_,_> = ...;
+    ///
+    /// lcd.print("Hello, World");
+    /// ```
+    pub fn print(&mut self, text: &str) {
+        for ch in text.chars() {
+            self.write(ch as u8);
+        }
+    }
+
+    /// Write a single character to the display. Bytes 0xFE (the command
+    /// prefix) can't be written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: NewhavenLcd<_,_> = ...;
+    ///
+    /// lcd.write(b'A');
+    /// ```
+    pub fn write(&mut self, value: u8) {
+        if value != PREFIX {
+            self.send(&[value], CMD_DELAY);
+            self.col = self.col.saturating_add(1);
+        }
+    }
+
+    /// Clear the display and move the cursor home.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: NewhavenLcd<_,_> = ...;
+    ///
+    /// lcd.clear();
+    /// ```
+    pub fn clear(&mut self) {
+        self.send(&[PREFIX, Command::Clear as u8], CLEAR_DELAY);
+        (self.col, self.row) = (0, 0);
+    }
+
+    /// Move the cursor to the home position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: NewhavenLcd<_,_> = ...;
+    ///
+    /// lcd.print("Hello");
+    /// lcd.home();
+    /// ```
+    pub fn home(&mut self) {
+        self.send(&[PREFIX, Command::Home as u8], CLEAR_DELAY);
+        (self.col, self.row) = (0, 0);
+    }
+
+    /// Set the position of the cursor. Rows past the last are clamped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: NewhavenLcd<_,_> = ...;
+    ///
+    /// lcd.set_position(4, 1);
+    /// lcd.print("World");
+    /// ```
+    pub fn set_position(&mut self, col: u8, row: u8) {
+        let row = row.min(self.rows - 1);
+        let position = ROW_OFFSETS[row as usize].saturating_add(col);
+        self.send(&[PREFIX, Command::SetCursor as u8, position], CMD_DELAY);
+        (self.col, self.row) = (col, row);
+    }
+
+    /// Get the cursor position as (column, row). Only positions set or written through
+    /// this struct are tracked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: NewhavenLcd<_,_> = ...;
+    ///
+    /// lcd.print("Hello");
+    /// assert_eq!(lcd.position(), (5, 0));
+    /// ```
+    pub fn position(&self) -> (u8, u8) {
+        (self.col, self.row)
+    }
+
+    /// Get the number of columns. (See [with_size][NewhavenLcd::with_size])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let lcd = NewhavenLcd::new_spi(spi_device, delay).with_size(20, 4);
+    /// assert_eq!(lcd.cols(), 20);
+    /// ```
+    pub fn cols(&self) -> u8 {
+        self.cols
+    }
+
+    /// Get the number of rows. (See [with_size][NewhavenLcd::with_size])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let lcd = NewhavenLcd::new_spi(spi_device, delay).with_size(20, 4);
+    /// assert_eq!(lcd.rows(), 4);
+    /// ```
+    pub fn rows(&self) -> u8 {
+        self.rows
+    }
+
+    /// Turn the display on or off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: NewhavenLcd<_,_> = ...;
+    ///
+    /// lcd.set_display(Display::Off);
+    /// ```
+    pub fn set_display(&mut self, display: Display) {
+        let command = match display {
+            Display::On => Command::DisplayOn,
+            Display::Off => Command::DisplayOff,
+        };
+        self.send(&[PREFIX, command as u8], CMD_DELAY);
+    }
+
+    /// Turn the underline cursor on or off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: NewhavenLcd<_,_> = ...;
+    ///
+    /// lcd.set_cursor(Cursor::On);
+    /// ```
+    pub fn set_cursor(&mut self, cursor: Cursor) {
+        let command = match cursor {
+            Cursor::On => Command::UnderlineOn,
+            Cursor::Off => Command::UnderlineOff,
+        };
+        self.send(&[PREFIX, command as u8], CMD_DELAY);
+    }
+
+    /// Make the cursor blink or stop blinking.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: NewhavenLcd<_,_> = ...;
+    ///
+    /// lcd.set_blink(Blink::On);
+    /// ```
+    pub fn set_blink(&mut self, blink: Blink) {
+        let command = match blink {
+            Blink::On => Command::BlinkOn,
+            Blink::Off => Command::BlinkOff,
+        };
+        self.send(&[PREFIX, command as u8], CMD_DELAY);
+    }
+
+    /// Scroll the display right or left.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: NewhavenLcd<_,_> = ...;
+    ///
+    /// lcd.set_scroll(Scroll::Left, 2);
+    /// ```
+    pub fn set_scroll(&mut self, direction: Scroll, distance: u8) {
+        let command = match direction {
+            Scroll::Right => Command::ShiftRight as u8,
+            Scroll::Left => Command::ShiftLeft as u8,
+        };
+        for _ in 0..distance {
+            self.send(&[PREFIX, command], CMD_DELAY);
+        }
+    }
+
+    /// Set the contrast, from 1 to 50.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: NewhavenLcd<_,_> = ...;
+    ///
+    /// lcd.set_contrast(40);
+    /// ```
+    pub fn set_contrast(&mut self, contrast: u8) {
+        let contrast = contrast.clamp(1, 50);
+        self.send(&[PREFIX, Command::SetContrast as u8, contrast], LEVEL_DELAY);
+    }
+
+    /// Set the backlight brightness, from 1 (off) to 8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: NewhavenLcd<_,_> = ...;
+    ///
+    /// lcd.set_brightness(8);
+    /// ```
+    pub fn set_brightness(&mut self, brightness: u8) {
+        let brightness = brightness.clamp(1, 8);
+        self.send(
+            &[PREFIX, Command::SetBrightness as u8, brightness],
+            LEVEL_DELAY,
+        );
+    }
+
+    /// Add a new character map to the display at a location from 0-7. Other locations
+    /// set the error code to [Error::OutOfBounds] and are masked to 0-7.
+    /// (See [LcdDisplay::set_character][crate::LcdDisplay::set_character])
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: NewhavenLcd<_,_> = ...;
+    ///
+    /// // set a sideways smiley face in CGRAM at location 0.
+    /// lcd.set_character(0u8,[
+    ///     0b00110,
+    ///     0b00001,
+    ///     0b11001,
+    ///     0b00001,
+    ///     0b00001,
+    ///     0b11001,
+    ///     0b00001,
+    ///     0b00110,
+    /// ]);
+    /// lcd.write(0u8);
+    /// ```
+    pub fn set_character(&mut self, location: u8, map: [u8; 8]) {
+        if location > 7 {
+            self.code = Error::OutOfBounds;
+        }
+
+        let mut bytes = [0u8; 11];
+        bytes[0] = PREFIX;
+        bytes[1] = Command::LoadCharacter as u8;
+        bytes[2] = location & 0x7;
+        bytes[3..].copy_from_slice(&map);
+        self.send(&bytes, CHARACTER_DELAY);
+    }
+
+    /// Get the current error code.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut lcd: NewhavenLcd<_,_> = ...;
+    ///
+    /// lcd.print("Hello");
+    ///
+    /// if lcd.error() == Error::BusError {
+    ///     // the display didn't acknowledge
+    /// }
+    /// ```
+    pub fn error(&self) -> Error {
+        self.code.clone()
+    }
+
+    /// Release the underlying transport and delay.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let lcd = NewhavenLcd::new_i2c(i2c_bus, NEWHAVEN_ADDRESS, delay);
+    /// let (transport, delay) = lcd.release();
+    /// ```
+    pub fn release(self) -> (B, D) {
+        (self.bus, self.delay)
+    }
+
+    /// Send bytes and wait for the display to process them.
+    fn send(&mut self, bytes: &[u8], delay: u32) {
+        if !self.bus.send(bytes) {
+            self.code = Error::BusError;
+        }
+        self.pause(delay);
+    }
+
+    /// Block for `us` microseconds, calling the yield hook as often as it asks for.
+    fn pause(&mut self, us: u32) {
+        self.yielder.pause(&mut self.delay, us);
+    }
+}
+
+impl<B, D> CharacterDisplay for NewhavenLcd<B, D>
+where
+    B: Transport,
+    D: DelayNs,
+{
+    fn cols(&self) -> u8 {
+        NewhavenLcd::cols(self)
+    }
+
+    fn rows(&self) -> u8 {
+        NewhavenLcd::rows(self)
+    }
+
+    fn print(&mut self, text: &str) {
+        NewhavenLcd::print(self, text);
+    }
+
+    fn write(&mut self, value: u8) {
+        NewhavenLcd::write(self, value);
+    }
+
+    fn set_position(&mut self, col: u8, row: u8) {
+        NewhavenLcd::set_position(self, col, row);
+    }
+
+    fn position(&self) -> (u8, u8) {
+        NewhavenLcd::position(self)
+    }
+
+    fn clear(&mut self) {
+        NewhavenLcd::clear(self);
+    }
+
+    fn home(&mut self) {
+        NewhavenLcd::home(self);
+    }
+
+    fn set_character(&mut self, location: u8, map: [u8; 8]) {
+        NewhavenLcd::set_character(self, location, map);
+    }
+
+    fn set_cursor(&mut self, cursor: Cursor) {
+        NewhavenLcd::set_cursor(self, cursor);
+    }
+
+    fn set_blink(&mut self, blink: Blink) {
+        NewhavenLcd::set_blink(self, blink);
+    }
+}
@@ -0,0 +1,70 @@
+//! Parsing of the setting flags from text, for serial consoles and config files
+
+use crate::{AutoScroll, Backlight, Blink, Cursor, Display, Layout, Lines};
+use core::fmt;
+use core::str::FromStr;
+
+/// Error returned when text doesn't name a value of a setting flag
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ParseError;
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("unrecognized setting value")
+    }
+}
+
+/// Find the value whose name matches `text`, ignoring case and surrounding whitespace.
+fn parse<V: Copy>(text: &str, names: &[(&str, V)]) -> Result<V, ParseError> {
+    let text = text.trim();
+    names
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(text))
+        .map(|(_, value)| *value)
+        .ok_or(ParseError)
+}
+
+/// Implement FromStr and TryFrom<&str> for a setting flag from a list of names.
+macro_rules! impl_parse {
+    ($flag:ty, $($name:literal => $value:expr),+ $(,)?) => {
+        impl FromStr for $flag {
+            type Err = ParseError;
+
+            fn from_str(text: &str) -> Result<Self, Self::Err> {
+                parse(text, &[$(($name, $value)),+])
+            }
+        }
+
+        impl TryFrom<&str> for $flag {
+            type Error = ParseError;
+
+            fn try_from(text: &str) -> Result<Self, Self::Error> {
+                text.parse()
+            }
+        }
+    };
+}
+
+impl_parse!(Cursor, "on" => Cursor::On, "off" => Cursor::Off);
+impl_parse!(Blink, "on" => Blink::On, "off" => Blink::Off);
+impl_parse!(Backlight, "on" => Backlight::On, "off" => Backlight::Off);
+impl_parse!(Display, "on" => Display::On, "off" => Display::Off);
+impl_parse!(AutoScroll, "on" => AutoScroll::On, "off" => AutoScroll::Off);
+
+impl_parse!(
+    Layout,
+    "ltr" => Layout::LeftToRight,
+    "left-to-right" => Layout::LeftToRight,
+    "rtl" => Layout::RightToLeft,
+    "right-to-left" => Layout::RightToLeft,
+);
+
+impl_parse!(
+    Lines,
+    "1" => Lines::OneLine,
+    "one" => Lines::OneLine,
+    "2" => Lines::TwoLines,
+    "two" => Lines::TwoLines,
+    "4" => Lines::FourLines,
+    "four" => Lines::FourLines,
+);
@@ -0,0 +1,135 @@
+//! Type erasure for pins, so a display can be built from pins of different types
+
+use embedded_hal::digital::{ErrorKind, ErrorType, InputPin, OutputPin};
+
+/// An output pin with its type (and error type) erased
+trait ErasedOutput {
+    /// Drive the pin, returning false if it reported an error.
+    fn set(&mut self, high: bool) -> bool;
+}
+
+impl<P> ErasedOutput for P
+where
+    P: OutputPin,
+{
+    fn set(&mut self, high: bool) -> bool {
+        match high {
+            true => self.set_high().is_ok(),
+            false => self.set_low().is_ok(),
+        }
+    }
+}
+
+/// A pin that can be driven and read, with its type erased
+trait ErasedIo: ErasedOutput {
+    /// Sample the pin, or None if it reported an error.
+    fn get(&mut self) -> Option<bool>;
+}
+
+impl<P> ErasedIo for P
+where
+    P: OutputPin + InputPin,
+{
+    fn get(&mut self) -> Option<bool> {
+        self.is_high().ok()
+    }
+}
+
+/// The pin behind an [AnyPin]
+enum Erased<'a> {
+    Output(&'a mut dyn ErasedOutput),
+    Io(&'a mut dyn ErasedIo),
+}
+
+/// A borrowed pin of any type
+///
+/// [LcdDisplay][crate::LcdDisplay] needs every pin to have the same type. On HALs where
+/// each GPIO is its own type (and can't be downgraded), or where pins have different
+/// error types, wrap each pin in an AnyPin instead. The pins themselves stay where they
+/// are and are only borrowed, so this works without an allocator. Errors from the
+/// underlying pins are reported as [ErrorKind::Other].
+///
+/// # Examples
+///
+/// ```
+/// let mut rs = gpio.pa0.into_push_pull_output();
+/// let mut en = gpio.pb3.into_push_pull_output();
+/// let mut d4 = gpio.pc1.into_open_drain_output();
+/// ...
+///
+/// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(AnyPin::new(&mut rs), AnyPin::new(&mut en), delay)
+///     .with_half_bus(
+///         AnyPin::new_io(&mut d4),
+///         AnyPin::new_io(&mut d5),
+///         AnyPin::new_io(&mut d6),
+///         AnyPin::new_io(&mut d7),
+///     )
+///     .build();
+/// ```
+pub struct AnyPin<'a> {
+    pin: Erased<'a>,
+}
+
+impl<'a> AnyPin<'a> {
+    /// Borrow an output pin. Reading an AnyPin created this way always fails.
+    pub fn new<P>(pin: &'a mut P) -> Self
+    where
+        P: OutputPin,
+    {
+        Self {
+            pin: Erased::Output(pin),
+        }
+    }
+
+    /// Borrow a pin that can also be read, for the data pins of displays that use
+    /// [self_test][crate::LcdDisplay::self_test] or the busy flag.
+    pub fn new_io<P>(pin: &'a mut P) -> Self
+    where
+        P: OutputPin + InputPin,
+    {
+        Self {
+            pin: Erased::Io(pin),
+        }
+    }
+
+    /// Drive the pin.
+    fn set(&mut self, high: bool) -> Result<(), ErrorKind> {
+        let ok = match &mut self.pin {
+            Erased::Output(pin) => pin.set(high),
+            Erased::Io(pin) => pin.set(high),
+        };
+        ok.then_some(()).ok_or(ErrorKind::Other)
+    }
+
+    /// Sample the pin.
+    fn get(&mut self) -> Result<bool, ErrorKind> {
+        match &mut self.pin {
+            Erased::Output(_) => Err(ErrorKind::Other),
+            Erased::Io(pin) => pin.get().ok_or(ErrorKind::Other),
+        }
+    }
+}
+
+impl ErrorType for AnyPin<'_> {
+    type Error = ErrorKind;
+}
+
+impl OutputPin for AnyPin<'_> {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.set(false)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.set(true)
+    }
+}
+
+impl InputPin for AnyPin<'_> {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        self.get()
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self.get().map(|high| !high)
+    }
+}
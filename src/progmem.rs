@@ -0,0 +1,62 @@
+//! Allows printing strings and byte arrays stored in AVR program memory (flash)
+
+use crate::LcdDisplay;
+use avr_progmem::string::PmString;
+use avr_progmem::wrapper::ProgMem;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+
+impl<T, D> LcdDisplay<T, D>
+where
+    T: OutputPin + Sized,
+    D: DelayNs + Sized,
+{
+    /// Print a string stored in program memory to the LCD display.
+    ///
+    /// Characters are loaded from flash one at a time, so the string never
+    /// has to be copied into SRAM. This method is only available if the
+    /// `progmem` feature is enabled and the target is AVR.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use avr_progmem::progmem;
+    ///
+    /// progmem! {
+    ///     static progmem string MENU = "1. Settings    2. About";
+    /// }
+    ///
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.print_progmem(&MENU);
+    /// ```
+    pub fn print_progmem<const N: usize>(&mut self, text: &PmString<N>) {
+        for ch in text.chars() {
+            self.write(ch as u8);
+        }
+    }
+
+    /// Write a byte array stored in program memory to the LCD display.
+    ///
+    /// Each byte is loaded from flash and written as-is, which makes this useful
+    /// for character codes that aren't valid UTF-8 (like custom characters or
+    /// the upper half of the character ROM). This method is only available if
+    /// the `progmem` feature is enabled and the target is AVR.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use avr_progmem::progmem;
+    ///
+    /// progmem! {
+    ///     static progmem BANNER: [u8; 4] = [0x00, 0xDF, b'C', 0x01];
+    /// }
+    ///
+    /// let mut lcd: LcdDisplay<_,_> = ...;
+    /// lcd.write_progmem(&BANNER);
+    /// ```
+    pub fn write_progmem<const N: usize>(&mut self, bytes: &ProgMem<[u8; N]>) {
+        for byte in bytes.iter() {
+            self.write(byte);
+        }
+    }
+}
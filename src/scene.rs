@@ -0,0 +1,177 @@
+//! Screen templates with static text and named fields that are updated in place
+
+use crate::CharacterDisplay;
+
+/// Maximum width of a field, in characters
+const FIELD_WIDTH: usize = 20;
+
+/// Flag that sets how a value is aligned in its field
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Align {
+    /// Values start at the left edge of the field (default)
+    Left,
+
+    /// Values end at the right edge of the field, which suits numbers
+    Right,
+}
+
+/// A named area of a [Scene] that shows a value
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Field<'a> {
+    name: &'a str,
+    col: u8,
+    row: u8,
+    width: u8,
+    align: Align,
+}
+
+impl<'a> Field<'a> {
+    /// Create a field called `name` that is `width` characters wide (at most 20),
+    /// starting at `col`, `row`.
+    pub const fn new(name: &'a str, col: u8, row: u8, width: u8) -> Self {
+        Self {
+            name,
+            col,
+            row,
+            width: if width as usize > FIELD_WIDTH {
+                FIELD_WIDTH as u8
+            } else {
+                width
+            },
+            align: Align::Left,
+        }
+    }
+
+    /// Set how values are aligned in the field. (Default is Align::Left)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let field = Field::new("temp", 6, 0, 5).with_align(Align::Right);
+    /// ```
+    pub const fn with_align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Get the name of the field.
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+}
+
+/// A screen made of static text and `N` named fields
+///
+/// The static text is drawn once with [render][Scene::render], and afterwards fields
+/// are updated by name with [set][Scene::set]. Values are padded to the width of their
+/// field and only the characters that changed are sent, so frequent updates stay cheap
+/// and don't flicker. Anything in the static text under a field (like `___`
+/// placeholders) is replaced by the value of the field.
+///
+/// # Examples
+///
+/// ```
+/// let mut lcd: LcdDisplay<_,_> = ...;
+///
+/// let mut scene = Scene::new(
+///     &["Temp: ___ C", "Fan:  ___ %"],
+///     [
+///         Field::new("temp", 6, 0, 3).with_align(Align::Right),
+///         Field::new("fan", 6, 1, 3).with_align(Align::Right),
+///     ],
+/// );
+///
+/// scene.render(&mut lcd);
+/// loop {
+///     let mut buffer: FormatBuffer<4> = FormatBuffer::new();
+///     uwrite!(&mut buffer, "{}", read_temperature()).ok();
+///     scene.set(&mut lcd, "temp", buffer.as_str());
+/// }
+/// ```
+pub struct Scene<'a, const N: usize> {
+    lines: &'a [&'a str],
+    fields: [Field<'a>; N],
+    values: [[u8; FIELD_WIDTH]; N],
+}
+
+impl<'a, const N: usize> Scene<'a, N> {
+    /// Create a scene from the static text of each row and the fields on top of it.
+    /// Every field starts out blank.
+    pub fn new(lines: &'a [&'a str], fields: [Field<'a>; N]) -> Self {
+        Self {
+            lines,
+            fields,
+            values: [[b' '; FIELD_WIDTH]; N],
+        }
+    }
+
+    /// Clear the display and draw the static text and the current value of every field.
+    pub fn render<L>(&self, lcd: &mut L)
+    where
+        L: CharacterDisplay,
+    {
+        lcd.clear();
+        for (row, line) in self.lines.iter().enumerate() {
+            lcd.print_at(0, row as u8, line);
+        }
+
+        for (field, value) in self.fields.iter().zip(self.values.iter()) {
+            lcd.set_position(field.col, field.row);
+            for ch in value.iter().take(field.width as usize) {
+                lcd.write(*ch);
+            }
+        }
+        lcd.home();
+    }
+
+    /// Show `value` in the field called `name`, only sending the characters that
+    /// changed. Values longer than the field are cut off. Returns false if there is
+    /// no field called `name`.
+    pub fn set<L>(&mut self, lcd: &mut L, name: &str, value: &str) -> bool
+    where
+        L: CharacterDisplay,
+    {
+        let Some(index) = self.fields.iter().position(|f| f.name == name) else {
+            return false;
+        };
+        let field = self.fields[index];
+        let width = field.width as usize;
+
+        let mut cells = [b' '; FIELD_WIDTH];
+        let length = value.len().min(width);
+        let start = match field.align {
+            Align::Left => 0,
+            Align::Right => width - length,
+        };
+        for (cell, byte) in cells[start..].iter_mut().zip(value.bytes()) {
+            *cell = byte;
+        }
+
+        let position = lcd.position();
+        let mut next = None;
+        for (i, cell) in cells.iter().enumerate().take(width) {
+            if self.values[index][i] == *cell {
+                continue;
+            }
+
+            // consecutive changes don't need a new position
+            if next != Some(i) {
+                lcd.set_position(field.col.saturating_add(i as u8), field.row);
+            }
+            lcd.write(*cell);
+            self.values[index][i] = *cell;
+            next = Some(i + 1);
+        }
+
+        if next.is_some() {
+            lcd.set_position(position.0, position.1);
+        }
+        true
+    }
+
+    /// Get the field called `name`, or None if there isn't one.
+    pub fn field(&self, name: &str) -> Option<&Field<'a>> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+}
@@ -0,0 +1,127 @@
+//! A scrollbar that shows the position in a list that is longer than the display
+
+use crate::CharacterDisplay;
+
+/// Pixel rows in a character cell
+const CELL_HEIGHT: usize = 8;
+
+/// Character code of a full block in the character ROM
+const FULL: u8 = 0xFF;
+
+/// A one-column scrollbar that is `N` rows high
+///
+/// The thumb shows which part of a list is visible and how much of it, with one pixel
+/// row of resolution. Cells that are completely inside or outside of the thumb use the
+/// full block and space characters from ROM, and the (at most two) cells at the ends
+/// of the thumb use two CGRAM slots, starting at slot 0 unless changed with
+/// [with_slot][Scrollbar::with_slot]. Only the cells and glyphs that changed are sent
+/// on each [update][Scrollbar::update].
+///
+/// # Examples
+///
+/// ```
+/// let mut lcd: LcdDisplay<_,_> = ...;
+/// let mut scrollbar: Scrollbar<4> = Scrollbar::new(19, 0);
+///
+/// loop {
+///     // show items[first..first + 4] in columns 0-18
+///     scrollbar.update(&mut lcd, first, 4, items.len());
+/// }
+/// ```
+pub struct Scrollbar<const N: usize> {
+    col: u8,
+    row: u8,
+    slot: u8,
+    shown: [Option<u8>; N],
+    glyphs: [Option<[u8; 8]>; 2],
+}
+
+impl<const N: usize> Scrollbar<N> {
+    /// Create a scrollbar in column `col`, from `row` down.
+    pub fn new(col: u8, row: u8) -> Self {
+        Self {
+            col,
+            row,
+            slot: 0,
+            shown: [None; N],
+            glyphs: [None; 2],
+        }
+    }
+
+    /// Set the first of the two CGRAM slots used for the ends of the thumb. (Default is 0)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut scrollbar: Scrollbar<4> = Scrollbar::new(19, 0).with_slot(6);
+    /// ```
+    pub fn with_slot(mut self, slot: u8) -> Self {
+        self.slot = slot.min(6);
+        self
+    }
+
+    /// Redraw every cell and glyph on the next update, for example after the display
+    /// was cleared or other custom characters were stored in the scrollbar's slots.
+    pub fn invalidate(&mut self) {
+        self.shown = [None; N];
+        self.glyphs = [None; 2];
+    }
+
+    /// Show that `visible` items starting at item `first` of a list of `total` items
+    /// are on the display. If the whole list is visible, the thumb fills the scrollbar.
+    pub fn update<L>(&mut self, lcd: &mut L, first: usize, visible: usize, total: usize)
+    where
+        L: CharacterDisplay,
+    {
+        let (start, end) = Self::thumb(first, visible, total);
+        let position = lcd.position();
+
+        let mut partial = 0;
+        for cell in 0..N {
+            let top = cell * CELL_HEIGHT;
+            let bottom = top + CELL_HEIGHT;
+
+            let code = if end <= top || start >= bottom {
+                b' '
+            } else if start <= top && end >= bottom {
+                FULL
+            } else {
+                let mut map = [0; 8];
+                for (line, pixels) in map.iter_mut().enumerate() {
+                    if (start..end).contains(&(top + line)) {
+                        *pixels = 0b11111;
+                    }
+                }
+
+                let slot = self.slot + partial;
+                if self.glyphs[partial as usize] != Some(map) {
+                    self.glyphs[partial as usize] = Some(map);
+                    lcd.set_character(slot, map);
+                }
+                partial += 1;
+                slot
+            };
+
+            if self.shown[cell] != Some(code) {
+                self.shown[cell] = Some(code);
+                lcd.set_position(self.col, self.row.saturating_add(cell as u8));
+                lcd.write(code);
+            }
+        }
+        lcd.set_position(position.0, position.1);
+    }
+
+    /// Find the first and last (exclusive) pixel rows of the thumb.
+    fn thumb(first: usize, visible: usize, total: usize) -> (usize, usize) {
+        let pixels = N * CELL_HEIGHT;
+        if visible >= total {
+            return (0, pixels);
+        }
+
+        // keep the thumb big enough to see
+        let length = (visible * pixels / total).clamp(2.min(pixels), pixels);
+        let first = first.min(total - visible);
+        let start = first * (pixels - length) / (total - visible);
+        (start, start + length)
+    }
+}
@@ -0,0 +1,147 @@
+//! Support for the common "LCD Keypad Shield" for the Arduino Uno
+//!
+//! The shield connects the LCD in four-bit mode (RS on D8, EN on D9 and D4-D7 on D4-D7),
+//! the backlight transistor to D10 and five buttons to a resistor ladder on A0.
+
+use crate::{Backlight, LcdDisplay, Lines};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+
+/// Upper ADC limits for each button on a 10-bit ADC (shield version 1.1)
+const BUTTON_LIMITS: [(u16, Button); 5] = [
+    (50, Button::Right),
+    (250, Button::Up),
+    (450, Button::Down),
+    (650, Button::Left),
+    (850, Button::Select),
+];
+
+/// A button on the keypad shield
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Button {
+    /// The RIGHT button
+    Right,
+
+    /// The UP button
+    Up,
+
+    /// The DOWN button
+    Down,
+
+    /// The LEFT button
+    Left,
+
+    /// The SELECT button
+    Select,
+}
+
+impl Button {
+    /// Decode a 10-bit ADC reading of the shield's button pin (A0) into the button that
+    /// is pressed, or `None` if no button is pressed. Only one button can be detected at
+    /// a time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut adc = arduino_hal::Adc::new(dp.ADC, Default::default());
+    /// let a0 = pins.a0.into_analog_input(&mut adc);
+    ///
+    /// if let Some(Button::Select) = Button::from_adc(a0.analog_read(&mut adc)) {
+    ///     lcd.print("Selected!");
+    /// }
+    /// ```
+    pub fn from_adc(value: u16) -> Option<Button> {
+        BUTTON_LIMITS
+            .iter()
+            .find(|(limit, _)| value < *limit)
+            .map(|(_, button)| *button)
+    }
+}
+
+/// Backlight control for the keypad shield
+///
+/// Many versions of the shield drive the backlight transistor's base directly from D10
+/// without a resistor, so setting D10 as a high output can draw enough current to damage
+/// the microcontroller. The safe way to turn the backlight on is to release the pin and
+/// let the shield pull it up, and to drive it low to turn the backlight off.
+///
+/// The pin given to this type must therefore be configured as an *open-drain* output (for
+/// example with `into_opendrain_high()` in avr-hal), where setting it high releases it.
+pub struct ShieldBacklight<P>
+where
+    P: OutputPin,
+{
+    pin: P,
+}
+
+impl<P> ShieldBacklight<P>
+where
+    P: OutputPin,
+{
+    /// Create backlight control from the open-drain D10 pin.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut backlight = ShieldBacklight::new(pins.d10.into_opendrain_high());
+    /// backlight.off();
+    /// ```
+    pub fn new(pin: P) -> Self {
+        Self { pin }
+    }
+
+    /// Turn the backlight on or off.
+    pub fn set(&mut self, backlight: Backlight) {
+        match backlight {
+            Backlight::On => self.on(),
+            Backlight::Off => self.off(),
+        }
+    }
+
+    /// Turn the backlight on by releasing the pin.
+    pub fn on(&mut self) {
+        let _ = self.pin.set_high();
+    }
+
+    /// Turn the backlight off by driving the pin low.
+    pub fn off(&mut self) {
+        let _ = self.pin.set_low();
+    }
+
+    /// Release the underlying pin.
+    pub fn release(self) -> P {
+        self.pin
+    }
+}
+
+impl<T, D> LcdDisplay<T, D>
+where
+    T: OutputPin + Sized,
+    D: DelayNs + Sized,
+{
+    /// Create a new [LcdDisplay] configured for the 16x2 display on the LCD Keypad Shield.
+    ///
+    /// The backlight is not part of the display, see [ShieldBacklight] for how to control
+    /// it safely.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let rs = pins.d8.into_output().downgrade();
+    /// let en = pins.d9.into_output().downgrade();
+    /// let d4 = pins.d4.into_output().downgrade();
+    /// let d5 = pins.d5.into_output().downgrade();
+    /// let d6 = pins.d6.into_output().downgrade();
+    /// let d7 = pins.d7.into_output().downgrade();
+    ///
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new_keypad_shield(rs, en, d4, d5, d6, d7, delay)
+    ///     .build();
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_keypad_shield(rs: T, en: T, d4: T, d5: T, d6: T, d7: T, delay: D) -> Self {
+        LcdDisplay::new(rs, en, delay)
+            .with_half_bus(d4, d5, d6, d7)
+            .with_lines(Lines::TwoLines)
+            .with_cols(16)
+    }
+}
@@ -0,0 +1,222 @@
+//! Drive a display through a 74HC595 shift register, as on LCD117-style and other
+//! three-wire adapter boards
+
+use core::cell::RefCell;
+use embedded_hal::digital::{ErrorKind, ErrorType, OutputPin};
+
+/// A 74HC595 (or compatible) shift register driven through data, clock and latch pins
+///
+/// The register is [split][ShiftRegister::split] into eight [ShiftPin]s that can be
+/// given to [LcdDisplay][crate::LcdDisplay] like any other pins. Setting a pin shifts
+/// out the whole cached byte and then pulses the latch, so all eight outputs change
+/// together and the other outputs (like a backlight) are never glitched.
+///
+/// If the board connects the output-enable pin (OE) to the microcontroller, it can be
+/// given with [with_output_enable][ShiftRegister::with_output_enable] to blank the
+/// outputs, or released with [release_output_enable][ShiftRegister::release_output_enable]
+/// and driven with PWM to dim a backlight wired to the register.
+pub struct ShiftRegister<P>
+where
+    P: OutputPin,
+{
+    inner: RefCell<Inner<P>>,
+}
+
+struct Inner<P> {
+    data: P,
+    clock: P,
+    latch: P,
+    oe: Option<P>,
+    out: u8,
+}
+
+/// The eight outputs of a [ShiftRegister]
+pub struct ShiftPins<'a, P>
+where
+    P: OutputPin,
+{
+    /// Output Q0
+    pub q0: ShiftPin<'a, P>,
+    /// Output Q1
+    pub q1: ShiftPin<'a, P>,
+    /// Output Q2
+    pub q2: ShiftPin<'a, P>,
+    /// Output Q3
+    pub q3: ShiftPin<'a, P>,
+    /// Output Q4
+    pub q4: ShiftPin<'a, P>,
+    /// Output Q5
+    pub q5: ShiftPin<'a, P>,
+    /// Output Q6
+    pub q6: ShiftPin<'a, P>,
+    /// Output Q7
+    pub q7: ShiftPin<'a, P>,
+}
+
+/// A single output of a [ShiftRegister]
+pub struct ShiftPin<'a, P>
+where
+    P: OutputPin,
+{
+    mask: u8,
+    register: &'a ShiftRegister<P>,
+}
+
+impl<P> ShiftRegister<P>
+where
+    P: OutputPin,
+{
+    /// Create a shift register from the serial data (DS), shift clock (SHCP) and latch
+    /// (STCP) pins. All outputs start low.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let data = pins.d11.into_output().downgrade();
+    /// let clock = pins.d13.into_output().downgrade();
+    /// let latch = pins.d10.into_output().downgrade();
+    ///
+    /// let register = ShiftRegister::new(data, clock, latch);
+    /// let pins = register.split();
+    ///
+    /// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(pins.q1, pins.q2, delay)
+    ///     .with_half_bus(pins.q3, pins.q4, pins.q5, pins.q6)
+    ///     .with_backlight(pins.q7)
+    ///     .build();
+    /// ```
+    pub fn new(data: P, clock: P, latch: P) -> Self {
+        Self {
+            inner: RefCell::new(Inner {
+                data,
+                clock,
+                latch,
+                oe: None,
+                out: 0x00,
+            }),
+        }
+    }
+
+    /// Set the output-enable pin (OE, active low). The outputs are enabled until
+    /// [blank][ShiftRegister::blank] is called.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let register = ShiftRegister::new(data, clock, latch)
+    ///     .with_output_enable(pins.d9.into_output().downgrade());
+    /// ```
+    pub fn with_output_enable(mut self, mut oe: P) -> Self {
+        let _ = oe.set_low();
+        self.inner.get_mut().oe = Some(oe);
+        self
+    }
+
+    /// Split the register into its eight outputs.
+    pub fn split(&self) -> ShiftPins<'_, P> {
+        let pin = |index: u8| ShiftPin {
+            mask: 1 << index,
+            register: self,
+        };
+        ShiftPins {
+            q0: pin(0),
+            q1: pin(1),
+            q2: pin(2),
+            q3: pin(3),
+            q4: pin(4),
+            q5: pin(5),
+            q6: pin(6),
+            q7: pin(7),
+        }
+    }
+
+    /// Enable or disable the outputs with the output-enable pin. Disabled outputs float,
+    /// which blanks the display and backlight without losing the output state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// register.set_output_enable(false);
+    /// ```
+    pub fn set_output_enable(&self, enabled: bool) {
+        if let Ok(mut inner) = self.inner.try_borrow_mut() {
+            if let Some(oe) = &mut inner.oe {
+                let _ = match enabled {
+                    true => oe.set_low(),
+                    false => oe.set_high(),
+                };
+            }
+        }
+    }
+
+    /// Disable the outputs. (See [set_output_enable][ShiftRegister::set_output_enable])
+    pub fn blank(&self) {
+        self.set_output_enable(false);
+    }
+
+    /// Enable the outputs. (See [set_output_enable][ShiftRegister::set_output_enable])
+    pub fn unblank(&self) {
+        self.set_output_enable(true);
+    }
+
+    /// Take back the output-enable pin, for example to reconfigure it as a PWM output
+    /// for hardware dimming.
+    pub fn release_output_enable(&mut self) -> Option<P> {
+        self.inner.get_mut().oe.take()
+    }
+
+    /// Release the data, clock and latch pins.
+    pub fn release(self) -> (P, P, P) {
+        let inner = self.inner.into_inner();
+        (inner.data, inner.clock, inner.latch)
+    }
+
+    /// Update the cached output byte and shift it out, most significant bit first.
+    /// The outputs only change on the rising edge of the latch, after all eight bits
+    /// have been clocked in.
+    fn write(&self, mask: u8, value: bool) -> Result<(), ErrorKind> {
+        let mut inner = self.inner.try_borrow_mut().map_err(|_| ErrorKind::Other)?;
+        match value {
+            true => inner.out |= mask,
+            false => inner.out &= !mask,
+        }
+
+        let out = inner.out;
+        let Inner {
+            data, clock, latch, ..
+        } = &mut *inner;
+
+        latch.set_low().map_err(|_| ErrorKind::Other)?;
+        for bit in (0..8).rev() {
+            clock.set_low().map_err(|_| ErrorKind::Other)?;
+            match (out >> bit) & 1 {
+                1 => data.set_high(),
+                _ => data.set_low(),
+            }
+            .map_err(|_| ErrorKind::Other)?;
+            clock.set_high().map_err(|_| ErrorKind::Other)?;
+        }
+        clock.set_low().map_err(|_| ErrorKind::Other)?;
+        latch.set_high().map_err(|_| ErrorKind::Other)?;
+        latch.set_low().map_err(|_| ErrorKind::Other)
+    }
+}
+
+impl<P> ErrorType for ShiftPin<'_, P>
+where
+    P: OutputPin,
+{
+    type Error = ErrorKind;
+}
+
+impl<P> OutputPin for ShiftPin<'_, P>
+where
+    P: OutputPin,
+{
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.register.write(self.mask, false)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.register.write(self.mask, true)
+    }
+}
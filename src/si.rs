@@ -0,0 +1,190 @@
+//! Values with SI-prefixed units, formatted to fit a fixed width
+
+use core::fmt;
+
+/// Widest field that can be formatted, in characters
+pub const SI_MAX_WIDTH: u8 = 20;
+
+/// Bytes of text a formatted value can hold (some prefixes and units take several)
+const TEXT_SIZE: usize = 48;
+
+/// SI prefixes from 10^-12 to 10^12, in steps of 10^3
+const PREFIXES: [&str; 9] = ["p", "n", "µ", "m", "", "k", "M", "G", "T"];
+
+/// Power of ten of the first prefix
+const SMALLEST: i32 = -12;
+
+/// A value with a unit, scaled with an SI prefix to fit in a field of fixed width
+///
+/// The value is given as an integer and a power of ten (like 3300 and -3 for 3.3), so no
+/// floating point is needed. The prefix is chosen so the value has one to three digits
+/// before the decimal point, or a larger prefix if that doesn't fit, and then as many
+/// decimals as fit in the field (but no more than the value has). The text is aligned to
+/// the right of the field. If the value doesn't fit at all the field is filled with `#`.
+///
+/// The micro prefix is `µ`, which the character ROM has, so print the text with
+/// [print_utf8][crate::LcdDisplay::print_utf8]. With the `ufmt` feature SiValue can also
+/// be formatted with uwrite!, and it implements `core::fmt::Display`.
+///
+/// # Examples
+///
+/// ```
+/// let mut lcd: LcdDisplay<_,_> = ...;
+///
+/// // 3300 mV in a five character field prints "3.30V"
+/// lcd.print_utf8(SiValue::new(3300, -3, "V", 5).as_str());
+///
+/// // 150 µA prints "150µA", and 12400 prints "12.4k"
+/// lcd.print_utf8(SiValue::new(150, -6, "A", 5).as_str());
+/// lcd.print_utf8(SiValue::new(12400, 0, "", 5).as_str());
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SiValue {
+    text: [u8; TEXT_SIZE],
+    len: usize,
+}
+
+impl SiValue {
+    /// Format `value` x 10^`exponent` with `unit` in a field `width` characters wide
+    /// (at most [SI_MAX_WIDTH]).
+    pub fn new(value: i32, exponent: i8, unit: &str, width: u8) -> Self {
+        let width = width.min(SI_MAX_WIDTH) as usize;
+        let mut si = Self {
+            text: [0; TEXT_SIZE],
+            len: 0,
+        };
+
+        let first = (engineering(value, exponent as i32) - SMALLEST) as usize / 3;
+        for prefix in first..PREFIXES.len() {
+            if si.format(value, exponent as i32, prefix, unit, width) {
+                return si;
+            }
+        }
+
+        si.len = 0;
+        for _ in 0..width {
+            si.push("#");
+        }
+        si
+    }
+
+    /// Get the formatted text.
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.text[..self.len]).unwrap_or_default()
+    }
+
+    /// Format the value with the prefix at `prefix`, with as many decimals as fit.
+    /// Returns false if the integer part doesn't fit, or if a non-zero value would be
+    /// rounded to zero.
+    fn format(
+        &mut self,
+        value: i32,
+        exponent: i32,
+        prefix: usize,
+        unit: &str,
+        width: usize,
+    ) -> bool {
+        let power = SMALLEST + prefix as i32 * 3;
+        let fixed = PREFIXES[prefix].chars().count() + unit.chars().count() + (value < 0) as usize;
+
+        // decimals beyond the precision of the value would only be zeros
+        let precise = (power - exponent).max(0) as usize;
+        let room = width.saturating_sub(fixed + 2);
+
+        for decimals in (0..=room.min(precise)).rev() {
+            let digits = scale(value, exponent - power + decimals as i32);
+            let mut buffer = [0u8; 40];
+            let mut len = 0;
+            let mut rest = digits;
+            loop {
+                buffer[len] = b'0' + (rest % 10) as u8;
+                len += 1;
+                rest /= 10;
+                if rest == 0 {
+                    break;
+                }
+            }
+
+            // at least one digit before the point
+            let integers = len.saturating_sub(decimals).max(1);
+            let point = (decimals > 0) as usize;
+            if fixed + integers + point + decimals > width || (digits == 0 && value != 0) {
+                continue;
+            }
+
+            self.len = 0;
+            for _ in fixed + integers + point + decimals..width {
+                self.push(" ");
+            }
+            if value < 0 {
+                self.push("-");
+            }
+            for i in (0..integers + decimals).rev() {
+                let digit = match i < len {
+                    true => buffer[i],
+                    false => b'0',
+                };
+                self.push_byte(digit);
+                if i == decimals && decimals > 0 {
+                    self.push(".");
+                }
+            }
+            self.push(PREFIXES[prefix]);
+            self.push(unit);
+            return true;
+        }
+        false
+    }
+
+    /// Add text, dropping it if the buffer is full.
+    fn push(&mut self, text: &str) {
+        if let Some(bytes) = self.text.get_mut(self.len..self.len + text.len()) {
+            bytes.copy_from_slice(text.as_bytes());
+            self.len += text.len();
+        }
+    }
+
+    /// Add a single ASCII byte.
+    fn push_byte(&mut self, byte: u8) {
+        if let Some(slot) = self.text.get_mut(self.len) {
+            *slot = byte;
+            self.len += 1;
+        }
+    }
+}
+
+/// Find the power of ten of the prefix that leaves one to three digits before the point.
+fn engineering(value: i32, exponent: i32) -> i32 {
+    let digits = value.unsigned_abs().checked_ilog10().unwrap_or(0) as i32 + 1;
+    let leading = if value == 0 { 0 } else { exponent + digits - 1 };
+    (leading.div_euclid(3) * 3).clamp(SMALLEST, -SMALLEST)
+}
+
+/// Get the magnitude of `value` x 10^`shift`, rounded half away from zero.
+fn scale(value: i32, shift: i32) -> u64 {
+    let magnitude = value.unsigned_abs() as u64;
+    match shift {
+        0.. => magnitude.saturating_mul(10u64.saturating_pow(shift as u32)),
+        ..=-20 => 0,
+        _ => {
+            let divisor = 10u64.pow(shift.unsigned_abs());
+            (magnitude + divisor / 2) / divisor
+        }
+    }
+}
+
+impl fmt::Display for SiValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl ufmt::uDisplay for SiValue {
+    fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+    where
+        W: ufmt::uWrite + ?Sized,
+    {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,117 @@
+//! A sparkline that shows the recent history of a value with bar glyphs
+
+use crate::CharacterDisplay;
+
+/// Number of bar heights, each stored in one of the CGRAM slots
+const LEVELS: u8 = 8;
+
+/// A sparkline showing the last `N` samples of a value, one cell per sample
+///
+/// Each cell shows a vertical bar from 0 to 8 pixels high. The eight bar heights are
+/// custom characters, so the sparkline uses all of CGRAM and they have to be sent with
+/// [upload][Sparkline::upload] before the first [update][Sparkline::update] (or after
+/// other custom characters were loaded). Updates only rewrite the cells whose bar
+/// height changed.
+///
+/// # Examples
+///
+/// ```
+/// let mut lcd: LcdDisplay<_,_> = ...;
+/// let mut history = [0i32; 16];
+/// let mut spark: Sparkline<16> = Sparkline::new(0, 1).with_range(15, 35);
+///
+/// spark.upload(&mut lcd);
+/// loop {
+///     history.rotate_left(1);
+///     history[15] = read_temperature();
+///     spark.update(&mut lcd, &history);
+/// }
+/// ```
+pub struct Sparkline<const N: usize> {
+    col: u8,
+    row: u8,
+    range: Option<(i32, i32)>,
+    shown: [Option<u8>; N],
+}
+
+impl<const N: usize> Sparkline<N> {
+    /// Create a sparkline in `N` cells starting at `col`, `row`. The bars are scaled
+    /// between the smallest and largest visible sample unless a range is set with
+    /// [with_range][Sparkline::with_range].
+    pub fn new(col: u8, row: u8) -> Self {
+        Self {
+            col,
+            row,
+            range: None,
+            shown: [None; N],
+        }
+    }
+
+    /// Set a fixed range for the samples, where `min` shows an empty cell and `max`
+    /// shows a full-height bar. Samples outside of the range are clamped.
+    pub fn with_range(mut self, min: i32, max: i32) -> Self {
+        self.range = Some((min.min(max), min.max(max)));
+        self
+    }
+
+    /// Upload the bar glyphs to CGRAM and redraw every cell on the next update.
+    pub fn upload<L>(&mut self, lcd: &mut L)
+    where
+        L: CharacterDisplay,
+    {
+        let position = lcd.position();
+        for level in 1..=LEVELS {
+            let mut map = [0; 8];
+            for line in map.iter_mut().skip((LEVELS - level) as usize) {
+                *line = 0b11111;
+            }
+            lcd.set_character(level - 1, map);
+        }
+        lcd.set_position(position.0, position.1);
+        self.shown = [None; N];
+    }
+
+    /// Show the last `N` samples, oldest on the left. If there are fewer than `N`
+    /// samples they are aligned to the right.
+    pub fn update<L>(&mut self, lcd: &mut L, samples: &[i32])
+    where
+        L: CharacterDisplay,
+    {
+        let samples = &samples[samples.len().saturating_sub(N)..];
+        let (min, max) = self.range.unwrap_or_else(|| {
+            let min = samples.iter().copied().min().unwrap_or(0);
+            let max = samples.iter().copied().max().unwrap_or(0);
+            (min, max)
+        });
+
+        let position = lcd.position();
+        let start = N - samples.len();
+        for cell in 0..N {
+            let level = match cell.checked_sub(start) {
+                Some(i) => Self::level(samples[i], min, max),
+                None => 0,
+            };
+
+            if self.shown[cell] != Some(level) {
+                self.shown[cell] = Some(level);
+                lcd.set_position(self.col.saturating_add(cell as u8), self.row);
+                lcd.write(match level {
+                    0 => b' ',
+                    _ => level - 1,
+                });
+            }
+        }
+        lcd.set_position(position.0, position.1);
+    }
+
+    /// Scale a sample to a bar height from 0 to 8.
+    fn level(sample: i32, min: i32, max: i32) -> u8 {
+        if max == min {
+            return LEVELS / 2;
+        }
+
+        let sample = sample.clamp(min, max) as i64;
+        let scaled = (sample - min as i64) * LEVELS as i64 / (max as i64 - min as i64);
+        scaled as u8
+    }
+}
@@ -0,0 +1,160 @@
+//! A status bar on one row with a scrolling log on the others
+
+use crate::{CharacterDisplay, Window};
+
+/// Which row holds the status bar
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StatusRow {
+    /// The first row
+    Top,
+
+    /// The last row
+    Bottom,
+}
+
+/// A status bar pinned to one row of a `COLS` x `ROWS` display, with the other rows
+/// acting as a console that scrolls up as lines are logged
+///
+/// The display can't be read back, so the log keeps its own copy of the visible lines
+/// to redraw them when it scrolls. Lines longer than the display wrap onto the next row,
+/// and a `\n` in the logged text starts a new line. The status bar is only rewritten by
+/// [set_status][StatusLog::set_status], and neither part touches the other. The cursor
+/// position on the display is not changed.
+///
+/// # Examples
+///
+/// ```
+/// let mut lcd: LcdDisplay<_,_> = ...;
+/// let mut console: StatusLog<20, 4> = StatusLog::new(StatusRow::Top);
+///
+/// console.set_status(&mut lcd, "WiFi: up   12:00");
+/// console.log(&mut lcd, "boot ok");
+/// console.log(&mut lcd, "sensor 2 timeout");
+/// ```
+pub struct StatusLog<const COLS: usize, const ROWS: usize> {
+    status_row: StatusRow,
+    lines: [[u8; COLS]; ROWS],
+    len: usize,
+}
+
+impl<const COLS: usize, const ROWS: usize> StatusLog<COLS, ROWS> {
+    /// Create an empty log with the status bar on `status_row`.
+    pub fn new(status_row: StatusRow) -> Self {
+        Self {
+            status_row,
+            lines: [[b' '; COLS]; ROWS],
+            len: 0,
+        }
+    }
+
+    /// Get the area of the display used by the status bar.
+    pub fn status_window(&self) -> Window {
+        let row = match self.status_row {
+            StatusRow::Top => 0,
+            StatusRow::Bottom => self.log_rows(),
+        };
+        Window::new(0, row as u8, COLS as u8, 1)
+    }
+
+    /// Get the area of the display used by the log.
+    pub fn log_window(&self) -> Window {
+        Window::new(0, self.first_log_row(), COLS as u8, self.log_rows() as u8)
+    }
+
+    /// Show `text` in the status bar, padded with spaces to the width of the display.
+    pub fn set_status<L>(&mut self, lcd: &mut L, text: &str)
+    where
+        L: CharacterDisplay,
+    {
+        let mut row = [b' '; COLS];
+        for (cell, ch) in row.iter_mut().zip(text.chars()) {
+            *cell = ch as u8;
+        }
+
+        let (col, line) = self.status_window().position();
+        let position = lcd.position();
+        lcd.set_position(col, line);
+        for byte in row {
+            lcd.write(byte);
+        }
+        lcd.set_position(position.0, position.1);
+    }
+
+    /// Add a line to the bottom of the log, scrolling older lines up and off the top
+    /// when the log is full.
+    pub fn log<L>(&mut self, lcd: &mut L, text: &str)
+    where
+        L: CharacterDisplay,
+    {
+        let rows = self.log_rows();
+        if rows == 0 {
+            return;
+        }
+
+        let mut col = 0;
+        self.push_line();
+        for ch in text.chars() {
+            if ch == '\n' || col == COLS {
+                self.push_line();
+                col = 0;
+                if ch == '\n' {
+                    continue;
+                }
+            }
+            self.lines[self.len - 1][col] = ch as u8;
+            col += 1;
+        }
+        self.redraw_log(lcd);
+    }
+
+    /// Empty the log and blank its rows on the display.
+    pub fn clear<L>(&mut self, lcd: &mut L)
+    where
+        L: CharacterDisplay,
+    {
+        self.lines = [[b' '; COLS]; ROWS];
+        self.len = 0;
+        self.redraw_log(lcd);
+    }
+
+    /// Draw the logged lines again, for example after the display was cleared.
+    pub fn redraw_log<L>(&mut self, lcd: &mut L)
+    where
+        L: CharacterDisplay,
+    {
+        let position = lcd.position();
+        let first = self.first_log_row();
+        for (row, line) in self.lines[..self.log_rows()].iter().enumerate() {
+            lcd.set_position(0, first + row as u8);
+            for byte in line {
+                lcd.write(*byte);
+            }
+        }
+        lcd.set_position(position.0, position.1);
+    }
+
+    /// Start a new, blank line at the bottom of the log.
+    fn push_line(&mut self) {
+        let rows = self.log_rows();
+        if self.len == rows {
+            self.lines.copy_within(1..rows, 0);
+            self.len -= 1;
+        }
+        self.lines[self.len] = [b' '; COLS];
+        self.len += 1;
+    }
+
+    /// Number of rows left for the log.
+    fn log_rows(&self) -> usize {
+        ROWS.saturating_sub(1)
+    }
+
+    /// The display row the log starts on.
+    fn first_log_row(&self) -> u8 {
+        match self.status_row {
+            StatusRow::Top => 1,
+            StatusRow::Bottom => 0,
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! A set of custom characters that can be saved to and restored from storage
+
+use crate::CharacterDisplay;
+
+/// Number of bytes in a serialized [GlyphSet]
+pub const GLYPH_SET_SIZE: usize = 68;
+
+/// Marks the start of a serialized glyph set, so erased or unrelated memory isn't loaded
+const MAGIC: [u8; 2] = *b"GL";
+
+/// Custom characters for the eight CGRAM slots
+///
+/// Devices with user-customizable symbols need to keep them across resets, but CGRAM
+/// is lost when the display powers off. A GlyphSet holds the maps for any of the eight
+/// slots, converts them to and from a fixed [GLYPH_SET_SIZE] byte format (with a marker
+/// and a checksum) and uploads them to the display. With the `embedded-storage` feature
+/// it can also be [stored][GlyphSet::store] to and [loaded][GlyphSet::load] from any
+/// EEPROM or NOR flash driver that implements `embedded_storage::Storage`.
+///
+/// # Examples
+///
+/// ```
+/// let mut lcd: LcdDisplay<_,_> = ...;
+///
+/// // at boot, restore the symbols the user drew last time
+/// if let Ok(Some(glyphs)) = GlyphSet::load(&mut eeprom, 0) {
+///     glyphs.upload(&mut lcd);
+/// }
+///
+/// // after the user edits a symbol
+/// glyphs.set(3, bell);
+/// glyphs.store(&mut eeprom, 0)?;
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GlyphSet {
+    glyphs: [Option<[u8; 8]>; 8],
+}
+
+impl GlyphSet {
+    /// Create an empty set.
+    pub const fn new() -> Self {
+        Self { glyphs: [None; 8] }
+    }
+
+    /// Set the map for a slot (0-7). Slots outside of this range are ignored.
+    pub fn set(&mut self, slot: u8, map: [u8; 8]) {
+        if let Some(glyph) = self.glyphs.get_mut(slot as usize) {
+            *glyph = Some(map.map(|row| row & 0x1F));
+        }
+    }
+
+    /// Get the map for a slot, or None if it is empty.
+    pub fn get(&self, slot: u8) -> Option<[u8; 8]> {
+        self.glyphs.get(slot as usize).copied().flatten()
+    }
+
+    /// Empty a slot.
+    pub fn remove(&mut self, slot: u8) {
+        if let Some(glyph) = self.glyphs.get_mut(slot as usize) {
+            *glyph = None;
+        }
+    }
+
+    /// Store every map in the set in its CGRAM slot. Empty slots are left as they are.
+    pub fn upload<L>(&self, lcd: &mut L)
+    where
+        L: CharacterDisplay,
+    {
+        for (slot, glyph) in (0..).zip(self.glyphs.iter()) {
+            if let Some(map) = glyph {
+                lcd.set_character(slot, *map);
+            }
+        }
+    }
+
+    /// Convert the set to bytes for storage
+    pub fn to_bytes(&self) -> [u8; GLYPH_SET_SIZE] {
+        let mut bytes = [0; GLYPH_SET_SIZE];
+        bytes[..2].copy_from_slice(&MAGIC);
+
+        for (slot, glyph) in self.glyphs.iter().enumerate() {
+            if let Some(map) = glyph {
+                bytes[2] |= 1 << slot;
+                bytes[3 + slot * 8..][..8].copy_from_slice(map);
+            }
+        }
+
+        bytes[GLYPH_SET_SIZE - 1] = checksum(&bytes[..GLYPH_SET_SIZE - 1]);
+        bytes
+    }
+
+    /// Convert bytes created by [to_bytes][GlyphSet::to_bytes] back into a set, or
+    /// None if they don't hold one (like erased or corrupted memory)
+    pub fn from_bytes(bytes: &[u8; GLYPH_SET_SIZE]) -> Option<Self> {
+        let (body, sum) = bytes.split_at(GLYPH_SET_SIZE - 1);
+        if body[..2] != MAGIC || checksum(body) != sum[0] {
+            return None;
+        }
+
+        let mut set = Self::new();
+        for slot in 0..8 {
+            if body[2] & (1 << slot) != 0 {
+                let mut map = [0; 8];
+                map.copy_from_slice(&body[3 + slot * 8..][..8]);
+                set.set(slot as u8, map);
+            }
+        }
+        Some(set)
+    }
+
+    /// Write the set to `storage` at `offset`, using [GLYPH_SET_SIZE] bytes. This method is
+    /// only available if the `embedded-storage` feature is enabled.
+    #[cfg(feature = "embedded-storage")]
+    pub fn store<S>(&self, storage: &mut S, offset: u32) -> Result<(), S::Error>
+    where
+        S: embedded_storage::Storage,
+    {
+        storage.write(offset, &self.to_bytes())
+    }
+
+    /// Read a set from `storage` at `offset`. Returns None if there isn't a valid set
+    /// there, for example on the first boot. This method is only available if the
+    /// `embedded-storage` feature is enabled.
+    #[cfg(feature = "embedded-storage")]
+    pub fn load<S>(storage: &mut S, offset: u32) -> Result<Option<Self>, S::Error>
+    where
+        S: embedded_storage::ReadStorage,
+    {
+        let mut bytes = [0; GLYPH_SET_SIZE];
+        storage.read(offset, &mut bytes)?;
+        Ok(Self::from_bytes(&bytes))
+    }
+}
+
+/// Checksum of the stored bytes, to catch corrupted or partly written data
+pub(crate) fn checksum(bytes: &[u8]) -> u8 {
+    bytes
+        .iter()
+        .fold(0xA5u8, |sum, byte| sum.rotate_left(1) ^ byte)
+}
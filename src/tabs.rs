@@ -0,0 +1,117 @@
+//! Tab stops for lining up columns of text
+
+use crate::CharacterDisplay;
+
+/// Maximum number of tab stops
+pub const MAX_TAB_STOPS: usize = 8;
+
+/// A set of column positions that tab-separated text is lined up on
+///
+/// [print_tabbed][TabStops::print_tabbed] prints each tab-separated field of a line at
+/// the next stop on the cursor's row, so labels, values and units stay in their columns
+/// without working out positions by hand. Each field is filled with spaces up to the next
+/// stop (the last one up to the end of the row) so shorter values overwrite longer ones,
+/// and is cut off where the next column starts. Fields after the last stop follow the
+/// previous one after a space, so without any stops tabs work like spaces.
+///
+/// # Examples
+///
+/// ```
+/// let mut lcd: LcdDisplay<_,_> = ...;
+/// let tabs = TabStops::new(&[0, 8, 14]);
+///
+/// lcd.set_position(0, 0);
+/// tabs.print_tabbed(&mut lcd, "Temp\t21.5\tC");
+/// lcd.set_position(0, 1);
+/// tabs.print_tabbed(&mut lcd, "Humid\t40\t%");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TabStops {
+    stops: [u8; MAX_TAB_STOPS],
+    len: usize,
+}
+
+impl TabStops {
+    /// Create tab stops at the given columns. (See [set_tab_stops][TabStops::set_tab_stops])
+    pub fn new(stops: &[u8]) -> Self {
+        let mut tabs = Self {
+            stops: [0; MAX_TAB_STOPS],
+            len: 0,
+        };
+        tabs.set_tab_stops(stops);
+        tabs
+    }
+
+    /// Set the columns that fields start at. Stops must be in increasing order; any that
+    /// aren't past the previous one are skipped, and only the first [MAX_TAB_STOPS] are
+    /// kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut tabs = TabStops::new(&[0, 10]);
+    /// tabs.set_tab_stops(&[0, 8, 12]);
+    /// ```
+    pub fn set_tab_stops(&mut self, stops: &[u8]) {
+        self.len = 0;
+        for stop in stops {
+            if self.len == MAX_TAB_STOPS {
+                break;
+            }
+            if self.len == 0 || *stop > self.stops[self.len - 1] {
+                self.stops[self.len] = *stop;
+                self.len += 1;
+            }
+        }
+    }
+
+    /// Get the columns that fields start at.
+    pub fn stops(&self) -> &[u8] {
+        &self.stops[..self.len]
+    }
+
+    /// Print `text` on the cursor's row, with each tab-separated field starting at the
+    /// next tab stop. The rest of the row is cleared.
+    pub fn print_tabbed<L>(&self, lcd: &mut L, text: &str)
+    where
+        L: CharacterDisplay,
+    {
+        let cols = lcd.cols();
+        let row = lcd.position().1;
+        let stops = self.stops();
+
+        let mut col = stops.first().copied().unwrap_or(0).min(cols);
+        lcd.set_position(col, row);
+
+        for (index, field) in text.split('\t').enumerate() {
+            if index > 0 {
+                let next = match stops.get(index) {
+                    Some(stop) => (*stop).min(cols),
+                    None => col.saturating_add(1).min(cols),
+                };
+                pad(lcd, &mut col, next);
+            }
+
+            let end = stops.get(index + 1).copied().unwrap_or(cols).min(cols);
+            for ch in field.chars() {
+                if col >= end {
+                    break;
+                }
+                lcd.write(ch as u8);
+                col += 1;
+            }
+        }
+        pad(lcd, &mut col, cols);
+    }
+}
+
+/// Write spaces from `col` up to (but not including) `end`.
+fn pad<L>(lcd: &mut L, col: &mut u8, end: u8)
+where
+    L: CharacterDisplay,
+{
+    while *col < end {
+        lcd.write(b' ');
+        *col += 1;
+    }
+}
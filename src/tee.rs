@@ -0,0 +1,119 @@
+//! A writer that mirrors everything shown on the display to a serial port
+
+use crate::LcdDisplay;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+use ufmt::uWrite;
+
+/// An [LcdDisplay] that copies its text to a second writer
+///
+/// Field devices often need a record of what was on the screen. A Tee prints to the
+/// display and writes the same text to `sink` (usually a serial port, or anything else
+/// that implements `ufmt::uWrite`), so the log doesn't need a second copy of every
+/// print. Moving the cursor or clearing the display starts a new line in the copy.
+/// Errors from the sink don't stop the display; the print methods ignore them, and the
+/// uwrite!/uwriteln! macros return them.
+///
+/// This type is only available if the `ufmt` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// let lcd: LcdDisplay<_,_> = ...;
+/// let serial = arduino_hal::default_serial!(dp, pins, 57600);
+///
+/// let mut screen = Tee::new(lcd, serial);
+/// screen.print_at(0, 0, "PUMP ON");
+/// uwrite!(&mut screen, "{} L/min", rate);
+/// ```
+pub struct Tee<T, D, W>
+where
+    T: OutputPin + Sized,
+    D: DelayNs + Sized,
+    W: uWrite,
+{
+    lcd: LcdDisplay<T, D>,
+    sink: W,
+}
+
+impl<T, D, W> Tee<T, D, W>
+where
+    T: OutputPin + Sized,
+    D: DelayNs + Sized,
+    W: uWrite,
+{
+    /// Create a Tee from a built [LcdDisplay] and the writer that gets the copy.
+    pub fn new(lcd: LcdDisplay<T, D>, sink: W) -> Self {
+        Self { lcd, sink }
+    }
+
+    /// Print a message at the cursor position.
+    pub fn print(&mut self, text: &str) {
+        let _ = self.mirror(text);
+    }
+
+    /// Move the cursor to a position and print a message there.
+    pub fn print_at(&mut self, col: u8, row: u8, text: &str) {
+        self.set_position(col, row);
+        self.print(text);
+    }
+
+    /// Write a single character at the cursor position.
+    pub fn write(&mut self, value: u8) {
+        self.lcd.write(value);
+        let _ = self.sink.write_char(value as char);
+    }
+
+    /// Move the cursor to a position.
+    pub fn set_position(&mut self, col: u8, row: u8) {
+        self.lcd.set_position(col, row);
+        let _ = self.sink.write_char('\n');
+    }
+
+    /// Clear the display and move the cursor home.
+    pub fn clear(&mut self) {
+        self.lcd.clear();
+        let _ = self.sink.write_char('\n');
+    }
+
+    /// Get the underlying display.
+    pub fn lcd(&self) -> &LcdDisplay<T, D> {
+        &self.lcd
+    }
+
+    /// Get mutable access to the underlying display. Anything done through it isn't
+    /// copied to the sink.
+    pub fn lcd_mut(&mut self) -> &mut LcdDisplay<T, D> {
+        &mut self.lcd
+    }
+
+    /// Get mutable access to the sink, for writing text that only goes to the copy.
+    pub fn sink_mut(&mut self) -> &mut W {
+        &mut self.sink
+    }
+
+    /// Release the display and the sink.
+    pub fn release(self) -> (LcdDisplay<T, D>, W) {
+        (self.lcd, self.sink)
+    }
+
+    /// Print text on the display and write it to the sink.
+    fn mirror(&mut self, text: &str) -> Result<(), W::Error> {
+        self.lcd.print(text);
+        self.sink.write_str(text)
+    }
+}
+
+/// Implementation of ufmt::uWrite, so the uwrite/uwriteln macros print to both writers
+impl<T, D, W> uWrite for Tee<T, D, W>
+where
+    T: OutputPin + Sized,
+    D: DelayNs + Sized,
+    W: uWrite,
+{
+    type Error = W::Error;
+
+    fn write_str(&mut self, s: &str) -> Result<(), Self::Error> {
+        self.mirror(s)
+    }
+}
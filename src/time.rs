@@ -0,0 +1,107 @@
+//! Conversion of the durations accepted by timing settings, and blocking delays that
+//! yield to the application
+
+use embedded_hal::delay::DelayNs;
+
+/// A duration that can be used for the display's timing settings
+///
+/// Plain `u32` values are taken as microseconds, which keeps existing code working.
+/// With the `fugit` feature enabled, `fugit` microsecond durations (like `50.micros()`
+/// or `2.millis()`) can be passed instead, so the unit is checked by the compiler.
+///
+/// This covers the settings that are waited out with the delay: the command, clear and
+/// character delays, the enable pulse width and hold time, the busy flag timeout, the
+/// reliable init toggle delay and the yield hook interval. The screensaver, backlight
+/// timeout and refresh interval, and the intervals of [Ui][crate::Ui] elements (like
+/// the speed of a marquee or the rate of blinking text) are measured in the units of the
+/// timestamps the application passes in, which may not be microseconds, so they stay
+/// plain `u32` values.
+///
+/// # Examples
+///
+/// ```
+/// use fugit::ExtU32;
+///
+/// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, delay)
+///     .with_half_bus(d4, d5, d6, d7)
+///     .with_delays(50.micros(), 2.millis(), 50.micros())
+///     .with_pulse_width_us(5.micros())
+///     .with_reliable_init(10.millis())
+///     .build();
+/// ```
+pub trait Microseconds {
+    /// Get the duration in whole microseconds.
+    fn to_micros(self) -> u32;
+}
+
+impl Microseconds for u32 {
+    fn to_micros(self) -> u32 {
+        self
+    }
+}
+
+// only microsecond durations are accepted, so literals like `2.millis()` can be
+// inferred; other durations can be changed with `convert()`
+#[cfg(feature = "fugit")]
+impl Microseconds for fugit::MicrosDurationU32 {
+    fn to_micros(self) -> u32 {
+        self.ticks()
+    }
+}
+
+#[cfg(feature = "fugit")]
+impl Microseconds for fugit::MicrosDurationU64 {
+    fn to_micros(self) -> u32 {
+        self.ticks().min(u32::MAX as u64) as u32
+    }
+}
+
+/// Default time between calls of the yield hook during delays, in microseconds
+const DEFAULT_YIELD_INTERVAL: u32 = 1000;
+
+/// A hook called at least every `interval` microseconds of blocking delays
+/// (See [with_yield_hook][crate::LcdDisplay::with_yield_hook])
+#[derive(Clone, Copy)]
+pub(crate) struct YieldHook {
+    hook: Option<fn()>,
+    interval: u32,
+    unyielded: u32,
+}
+
+impl YieldHook {
+    /// Create a yield hook that isn't set.
+    pub(crate) const fn new() -> Self {
+        Self {
+            hook: None,
+            interval: DEFAULT_YIELD_INTERVAL,
+            unyielded: 0,
+        }
+    }
+
+    /// Set or remove the hook, called every `interval` microseconds (at least 1).
+    pub(crate) fn set(&mut self, hook: Option<fn()>, interval: u32) {
+        self.hook = hook;
+        self.interval = interval.max(1);
+        self.unyielded = 0;
+    }
+
+    /// Block for `us` microseconds, calling the hook each time the interval has passed.
+    /// The time since the last call is carried over to the next delay.
+    pub(crate) fn pause<D: DelayNs>(&mut self, delay: &mut D, us: u32) {
+        let hook = match self.hook {
+            Some(hook) => hook,
+            None => return delay.delay_us(us),
+        };
+
+        let mut left = us;
+        while self.unyielded.saturating_add(left) >= self.interval {
+            let step = self.interval.saturating_sub(self.unyielded);
+            delay.delay_us(step);
+            hook();
+            left -= step;
+            self.unyielded = 0;
+        }
+        delay.delay_us(left);
+        self.unyielded += left;
+    }
+}
@@ -0,0 +1,100 @@
+//! A delay provider backed by a hardware count-down timer
+
+use core::convert::Infallible;
+use embedded_hal::delay::DelayNs;
+
+/// A hardware timer that counts down a number of microseconds
+///
+/// embedded-hal 1.0 dropped its `timer::CountDown` trait, so this is the same interface
+/// without the generic time unit. Implement it for a timer of your HAL to use it with
+/// [TimerDelay].
+pub trait CountDown {
+    /// Start counting down from `us` microseconds, restarting the timer if it is
+    /// already running.
+    fn start(&mut self, us: u32);
+
+    /// Returns `WouldBlock` until the count down has expired.
+    fn wait(&mut self) -> nb::Result<(), Infallible>;
+}
+
+/// A [DelayNs] implementation that arms a [CountDown] timer for every delay
+///
+/// Instead of counting cycles, the display's waits (like the 1.5 ms after
+/// [clear][crate::LcdDisplay::clear] and [home][crate::LcdDisplay::home]) start the
+/// timer and then call an idle function until it expires. The idle function can put
+/// the CPU to sleep until the timer interrupt or do a bit of other work. This is only
+/// available if the `nb` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// let timer = TimerDelay::new(hal_timer).with_idle(cortex_m::asm::wfi);
+///
+/// let mut lcd: LcdDisplay<_,_> = LcdDisplay::new(rs, en, timer)
+///     .with_half_bus(d4, d5, d6, d7)
+///     .build();
+/// ```
+pub struct TimerDelay<C>
+where
+    C: CountDown,
+{
+    timer: C,
+    idle: fn(),
+}
+
+impl<C> TimerDelay<C>
+where
+    C: CountDown,
+{
+    /// Create a delay provider from a count-down timer. While waiting it spins on the
+    /// timer until an idle function is set.
+    pub fn new(timer: C) -> Self {
+        Self { timer, idle: || {} }
+    }
+
+    /// Set a function that is called repeatedly while waiting for the timer to expire.
+    /// (Default does nothing)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let timer = TimerDelay::new(hal_timer).with_idle(cortex_m::asm::wfi);
+    /// ```
+    pub fn with_idle(mut self, idle: fn()) -> Self {
+        self.idle = idle;
+        self
+    }
+
+    /// Release the timer.
+    pub fn release(self) -> C {
+        self.timer
+    }
+}
+
+impl<C> DelayNs for TimerDelay<C>
+where
+    C: CountDown,
+{
+    fn delay_ns(&mut self, ns: u32) {
+        self.delay_us(ns.div_ceil(1000));
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        if us == 0 {
+            return;
+        }
+
+        self.timer.start(us);
+        while self.timer.wait().is_err() {
+            (self.idle)();
+        }
+    }
+
+    fn delay_ms(&mut self, ms: u32) {
+        // split long delays so the microsecond count can't overflow
+        for _ in 0..ms / 1000 {
+            self.delay_us(1_000_000);
+        }
+        self.delay_us((ms % 1000) * 1000);
+    }
+}
@@ -0,0 +1,226 @@
+//! A scheduler that drives animated elements and display timers from the main loop
+
+use crate::{CharacterDisplay, LcdDisplay, Marquee};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+
+/// An element that changes on screen over time, advanced by [Ui::update]
+///
+/// The elements in this crate can be drawn on any [CharacterDisplay], so they can also
+/// be advanced by hand without a [Ui].
+pub trait Animate<L>
+where
+    L: CharacterDisplay,
+{
+    /// Draw the next frame. This is called once per interval given to [Ui::add], and
+    /// should leave the cursor where it was.
+    fn animate(&mut self, lcd: &mut L);
+}
+
+impl<L> Animate<L> for Marquee<'_>
+where
+    L: CharacterDisplay,
+{
+    fn animate(&mut self, lcd: &mut L) {
+        self.tick(lcd);
+    }
+}
+
+/// An element registered with a [Ui], along with when it runs
+struct Slot<'a, T, D>
+where
+    T: OutputPin + Sized,
+    D: DelayNs + Sized,
+{
+    element: &'a mut dyn Animate<LcdDisplay<T, D>>,
+    interval: u32,
+    last: u32,
+}
+
+/// A display together with up to `N` animated elements
+///
+/// Instead of calling the tick method of every marquee, spinner and timer separately,
+/// register them once with [add][Ui::add] and call [update][Ui::update] from the main
+/// loop. Each element is advanced at its own interval, and the display's
+/// [screensaver][LcdDisplay::with_screensaver], backlight timeout and
+/// [refresh][LcdDisplay::with_refresh_interval] timers are run as well.
+///
+/// # Examples
+///
+/// ```
+/// let lcd: LcdDisplay<_,_> = ...;
+///
+/// let mut marquee = Marquee::new("Breaking news!", 0, 0, 8);
+/// let mut spinner = Spinner::new(15, 1);
+///
+/// let mut ui: Ui<_, _, 4> = Ui::new(lcd);
+/// marquee.show(ui.lcd_mut());
+/// ui.add(&mut marquee, 40);
+/// ui.add(&mut spinner, 250);
+///
+/// loop {
+///     ui.update(millis());
+/// }
+/// ```
+pub struct Ui<'a, T, D, const N: usize>
+where
+    T: OutputPin + Sized,
+    D: DelayNs + Sized,
+{
+    lcd: LcdDisplay<T, D>,
+    slots: [Option<Slot<'a, T, D>>; N],
+}
+
+impl<'a, T, D, const N: usize> Ui<'a, T, D, N>
+where
+    T: OutputPin + Sized,
+    D: DelayNs + Sized,
+{
+    /// Create a scheduler for a built [LcdDisplay].
+    pub fn new(lcd: LcdDisplay<T, D>) -> Self {
+        Self {
+            lcd,
+            slots: [(); N].map(|_| None),
+        }
+    }
+
+    /// Register an element that is advanced every `interval`, in the units of the
+    /// timestamps passed to [update][Ui::update]. Returns false if all `N` slots are in use.
+    pub fn add(&mut self, element: &'a mut dyn Animate<LcdDisplay<T, D>>, interval: u32) -> bool {
+        match self.slots.iter_mut().find(|slot| slot.is_none()) {
+            Some(slot) => {
+                *slot = Some(Slot {
+                    element,
+                    interval,
+                    last: 0,
+                });
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove every registered element.
+    pub fn clear(&mut self) {
+        self.slots = [(); N].map(|_| None);
+    }
+
+    /// Advance the display timers and every element whose interval has passed. `now` is
+    /// a timestamp in wrapping units, like milliseconds since boot. Writes made by the
+    /// elements don't count as activity for the screensaver and backlight timeout.
+    pub fn update(&mut self, now: u32) {
+        self.lcd.tick(now);
+        self.lcd.maintain(now);
+
+        for slot in self.slots.iter_mut().flatten() {
+            if now.wrapping_sub(slot.last) >= slot.interval {
+                slot.last = now;
+                self.lcd.passive(|lcd| slot.element.animate(lcd));
+            }
+        }
+    }
+
+    /// Get the display.
+    pub fn lcd(&self) -> &LcdDisplay<T, D> {
+        &self.lcd
+    }
+
+    /// Get mutable access to the display, to print static content.
+    pub fn lcd_mut(&mut self) -> &mut LcdDisplay<T, D> {
+        &mut self.lcd
+    }
+
+    /// Release the display.
+    pub fn release(self) -> LcdDisplay<T, D> {
+        self.lcd
+    }
+}
+
+/// A single-character activity indicator that steps through a sequence of characters
+///
+/// The default frames are `.oOo`, which look the same in every character ROM.
+pub struct Spinner {
+    col: u8,
+    row: u8,
+    frames: &'static [u8],
+    index: usize,
+}
+
+impl Spinner {
+    /// Create a spinner at `col`, `row`.
+    pub fn new(col: u8, row: u8) -> Self {
+        Self {
+            col,
+            row,
+            frames: b".oOo",
+            index: 0,
+        }
+    }
+
+    /// Set the characters the spinner steps through.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let spinner = Spinner::new(15, 0).with_frames(b"-|/");
+    /// ```
+    pub fn with_frames(mut self, frames: &'static [u8]) -> Self {
+        self.frames = frames;
+        self
+    }
+}
+
+impl<L> Animate<L> for Spinner
+where
+    L: CharacterDisplay,
+{
+    fn animate(&mut self, lcd: &mut L) {
+        if let Some(frame) = self.frames.get(self.index) {
+            let position = lcd.position();
+            lcd.set_position(self.col, self.row);
+            lcd.write(*frame);
+            lcd.set_position(position.0, position.1);
+        }
+        self.index = (self.index + 1) % self.frames.len().max(1);
+    }
+}
+
+/// Text that blinks on and off, like an alarm or a value being edited
+pub struct BlinkText<'a> {
+    col: u8,
+    row: u8,
+    text: &'a str,
+    visible: bool,
+}
+
+impl<'a> BlinkText<'a> {
+    /// Create blinking `text` at `col`, `row`. It is shown on the first update.
+    pub fn new(col: u8, row: u8, text: &'a str) -> Self {
+        Self {
+            col,
+            row,
+            text,
+            visible: false,
+        }
+    }
+}
+
+impl<L> Animate<L> for BlinkText<'_>
+where
+    L: CharacterDisplay,
+{
+    fn animate(&mut self, lcd: &mut L) {
+        self.visible = !self.visible;
+        let position = lcd.position();
+        lcd.set_position(self.col, self.row);
+        match self.visible {
+            true => lcd.print(self.text),
+            false => {
+                for _ in self.text.chars() {
+                    lcd.write(b' ');
+                }
+            }
+        }
+        lcd.set_position(position.0, position.1);
+    }
+}
@@ -0,0 +1,126 @@
+//! Property tests for the mapping from unicode characters to character ROM codes
+
+use ag_lcd::{Error, LcdDisplay, Rom, Unmappable};
+use core::convert::Infallible;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{ErrorType, OutputPin};
+use proptest::prelude::*;
+
+/// A pin that ignores every level
+struct NoPin;
+
+impl ErrorType for NoPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for NoPin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// A delay that returns immediately
+struct NoDelay;
+
+impl DelayNs for NoDelay {
+    fn delay_ns(&mut self, _: u32) {}
+}
+
+/// Characters that share a ROM code with another character on purpose
+const ALIASES: [(char, char); 2] = [('μ', 'µ'), ('\u{3A9}', '\u{2126}')];
+
+fn display(rom: Rom, unmappable: Unmappable) -> LcdDisplay<NoPin, NoDelay> {
+    LcdDisplay::new(NoPin, NoPin, NoDelay)
+        .with_half_bus(NoPin, NoPin, NoPin, NoPin)
+        .with_rom(rom)
+        .with_unmappable(unmappable)
+        .build()
+}
+
+fn rom() -> impl Strategy<Value = Rom> {
+    prop_oneof![Just(Rom::A00), Just(Rom::A02)]
+}
+
+/// Characters near the ones in the tables, so mappable ones come up often
+fn character() -> impl Strategy<Value = char> {
+    prop_oneof![
+        proptest::char::range(' ', '\u{FF}'),
+        proptest::char::range('\u{390}', '\u{3C9}'),
+        proptest::char::range('\u{2100}', '\u{25FF}'),
+        proptest::char::range('\u{FF61}', '\u{FF9F}'),
+        any::<char>(),
+    ]
+}
+
+fn unmappable() -> impl Strategy<Value = Unmappable> {
+    prop_oneof![
+        Just(Unmappable::Skip),
+        any::<u8>().prop_map(Unmappable::Replace),
+        Just(Unmappable::Error),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn codes_are_never_custom_characters(rom in rom(), ch in character()) {
+        // 0x00-0x0F are CGRAM (and its mirror) in both ROMs
+        if let Some(code) = rom.encode(ch) {
+            prop_assert!(code >= 0x10, "{:?} maps {:?} to {:#04x}", rom, ch, code);
+        }
+    }
+
+    #[test]
+    fn ascii_round_trips(rom in rom(), ch in proptest::char::range(' ', '}')) {
+        if rom == Rom::A00 && ch == '\\' {
+            prop_assert_eq!(rom.encode(ch), None);
+        } else {
+            prop_assert_eq!(rom.encode(ch), Some(ch as u8));
+        }
+    }
+
+    #[test]
+    fn latin1_round_trips_in_a02(ch in proptest::char::range('\u{A0}', '\u{FF}')) {
+        prop_assert_eq!(Rom::A02.encode(ch), Some(ch as u8));
+    }
+
+    #[test]
+    fn codes_are_unique(rom in rom(), a in character(), b in character()) {
+        if a != b && rom.encode(a).is_some() && rom.encode(a) == rom.encode(b) {
+            prop_assert!(
+                ALIASES.contains(&(a, b)) || ALIASES.contains(&(b, a)),
+                "{:?} maps both {:?} and {:?} to the same code", rom, a, b
+            );
+        }
+    }
+
+    #[test]
+    fn kana_are_half_width_katakana(rom in rom(), ch in any::<char>()) {
+        if let Some((code, mark)) = rom.encode_kana(ch) {
+            prop_assert_eq!(rom, Rom::A00);
+            prop_assert!((0xA1..=0xDF).contains(&code));
+            prop_assert!(matches!(mark, None | Some(0xDE) | Some(0xDF)));
+        }
+    }
+
+    #[test]
+    fn full_width_kana_are_mapped(ch in proptest::char::range('\u{30A1}', '\u{30FA}')) {
+        prop_assert!(Rom::A00.encode_kana(ch).is_some());
+    }
+
+    #[test]
+    fn fallback_never_panics(rom in rom(), policy in unmappable(), text in ".{0,16}") {
+        let mut lcd = display(rom, policy);
+        lcd.print_utf8(&text);
+        lcd.print_kana(&text);
+
+        let error = lcd.error();
+        match policy {
+            Unmappable::Error => prop_assert!(error == Error::None || error == Error::Unmappable),
+            _ => prop_assert!(error == Error::None),
+        }
+    }
+}
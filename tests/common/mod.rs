@@ -0,0 +1,461 @@
+//! Test doubles shared by the integration tests: a model of an HD44780 controller
+//! driven through mock pins, and a character display that records what it shows
+
+#![allow(dead_code)]
+
+use ag_lcd::{Blink, CharacterDisplay, Cursor, LcdDisplay, Transport};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{ErrorKind, ErrorType, InputPin, OutputPin};
+use embedded_hal::i2c::{self, I2c, Operation, SevenBitAddress};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Register select line
+pub const RS: usize = 0;
+
+/// Enable line
+pub const EN: usize = 1;
+
+/// Read/write line
+pub const RW: usize = 2;
+
+/// First data line, followed by D1 to D7
+pub const D0: usize = 3;
+
+/// A model of an HD44780 controller, updated by the pins connected to it
+pub struct Hd44780 {
+    /// Levels of RS, EN, RW and D0-D7
+    pub lines: [bool; 11],
+    pub ddram: [u8; 128],
+    pub cgram: [u8; 64],
+    pub address: u8,
+    pub in_cgram: bool,
+    pub four_bit: bool,
+    pub increment: bool,
+    pub shift_on_write: bool,
+    pub shift: i32,
+    pub function: u8,
+    pub control: u8,
+    pub commands: Vec<u8>,
+    pub data: Vec<u8>,
+    pub elapsed_us: u64,
+    /// Number of pin writes that report an error (without changing the line) from now
+    pub failures: u32,
+    /// Whether the function set selects ST7036 instruction tables, and the one selected
+    pub tables: bool,
+    pub table: u8,
+    nibble: Option<u8>,
+    read: Option<u8>,
+}
+
+/// A controller shared by the pins and the delay
+pub type Shared = Rc<RefCell<Hd44780>>;
+
+impl Hd44780 {
+    /// Create a controller in the state it powers up in (eight-bit mode).
+    pub fn new() -> Shared {
+        Rc::new(RefCell::new(Self {
+            lines: [false; 11],
+            ddram: [b' '; 128],
+            cgram: [0; 64],
+            address: 0,
+            in_cgram: false,
+            four_bit: false,
+            increment: true,
+            shift_on_write: false,
+            shift: 0,
+            function: 0,
+            control: 0,
+            commands: Vec::new(),
+            data: Vec::new(),
+            elapsed_us: 0,
+            failures: 0,
+            tables: false,
+            table: 0,
+            nibble: None,
+            read: None,
+        }))
+    }
+
+    /// Get `len` characters of DDRAM starting at `address`.
+    pub fn text(&self, address: u8, len: usize) -> String {
+        let start = address as usize;
+        self.ddram[start..start + len]
+            .iter()
+            .map(|b| *b as char)
+            .collect()
+    }
+
+    /// Get a custom character from CGRAM.
+    pub fn glyph(&self, slot: usize) -> [u8; 8] {
+        let mut map = [0; 8];
+        map.copy_from_slice(&self.cgram[slot * 8..][..8]);
+        map
+    }
+
+    /// Read the data lines, only D4-D7 in four-bit mode.
+    fn bus(&self) -> u8 {
+        let mut value = 0;
+        for bit in 0..8 {
+            if self.lines[D0 + bit] {
+                value |= 1 << bit;
+            }
+        }
+        match self.four_bit {
+            true => value >> 4,
+            false => value,
+        }
+    }
+
+    /// Take the value on the bus when EN falls.
+    fn latch(&mut self) {
+        if self.lines[RW] {
+            return;
+        }
+
+        let value = self.bus();
+        let byte = match (self.four_bit, self.nibble.take()) {
+            (false, _) => value,
+            (true, None) => {
+                self.nibble = Some(value);
+                return;
+            }
+            (true, Some(high)) => (high << 4) | value,
+        };
+
+        match self.lines[RS] {
+            true => self.write(byte),
+            false => self.execute(byte),
+        }
+    }
+
+    /// Put the next value (or nibble of it) on the bus when EN rises for a read.
+    fn drive(&mut self) {
+        let value = match self.read.take() {
+            Some(low) => low << 4,
+            None => {
+                let address = self.address as usize;
+                let value = match self.lines[RS] {
+                    true => self.memory()[address],
+                    false => self.address,
+                };
+                if self.four_bit {
+                    self.read = Some(value & 0x0F);
+                }
+                value
+            }
+        };
+
+        for bit in 0..8 {
+            self.lines[D0 + bit] = value & (1 << bit) != 0;
+        }
+        if self.read.is_none() && self.lines[RS] {
+            self.advance();
+        }
+    }
+
+    fn memory(&mut self) -> &mut [u8] {
+        match self.in_cgram {
+            true => &mut self.cgram,
+            false => &mut self.ddram,
+        }
+    }
+
+    fn write(&mut self, byte: u8) {
+        self.data.push(byte);
+        let address = self.address as usize;
+        self.memory()[address] = byte;
+        self.advance();
+        if self.shift_on_write && !self.in_cgram {
+            self.shift += if self.increment { -1 } else { 1 };
+        }
+    }
+
+    fn advance(&mut self) {
+        let mask = if self.in_cgram { 0x3F } else { 0x7F };
+        self.address = match self.increment {
+            true => self.address.wrapping_add(1),
+            false => self.address.wrapping_sub(1),
+        } & mask;
+    }
+
+    fn execute(&mut self, byte: u8) {
+        self.commands.push(byte);
+        if self.table != 0 && (0x10..0x80).contains(&byte) && byte & 0xE0 != 0x20 {
+            // extended instructions don't touch the address counter
+            return;
+        }
+        match byte {
+            0x80.. => {
+                self.address = byte & 0x7F;
+                self.in_cgram = false;
+            }
+            0x40.. => {
+                self.address = byte & 0x3F;
+                self.in_cgram = true;
+            }
+            0x20.. => {
+                self.function = byte;
+                self.four_bit = byte & 0x10 == 0;
+                if self.tables {
+                    self.table = byte & 0x03;
+                }
+            }
+            0x10.. => match (byte & 0x08 != 0, byte & 0x04 != 0) {
+                (true, true) => self.shift += 1,
+                (true, false) => self.shift -= 1,
+                (false, true) => self.address = self.address.wrapping_add(1) & 0x7F,
+                (false, false) => self.address = self.address.wrapping_sub(1) & 0x7F,
+            },
+            0x08.. => self.control = byte & 0x07,
+            0x04.. => {
+                self.increment = byte & 0x02 != 0;
+                self.shift_on_write = byte & 0x01 != 0;
+            }
+            0x02.. => {
+                self.address = 0;
+                self.in_cgram = false;
+                self.shift = 0;
+            }
+            0x01 => {
+                self.ddram = [b' '; 128];
+                self.address = 0;
+                self.in_cgram = false;
+                self.increment = true;
+                self.shift = 0;
+            }
+            0x00 => {}
+        }
+    }
+}
+
+/// A pin connected to one line of one or more controllers
+pub struct Pin {
+    line: usize,
+    hds: Vec<Shared>,
+}
+
+impl Pin {
+    fn set(&mut self, level: bool) -> Result<(), ErrorKind> {
+        {
+            let mut hd = self.hds[0].borrow_mut();
+            if hd.failures > 0 {
+                hd.failures -= 1;
+                return Err(ErrorKind::Other);
+            }
+        }
+
+        for hd in self.hds.iter() {
+            let mut hd = hd.borrow_mut();
+            let previous = hd.lines[self.line];
+            hd.lines[self.line] = level;
+
+            if self.line == EN && previous && !level {
+                hd.latch();
+            }
+            if self.line == EN && !previous && level && hd.lines[RW] {
+                hd.drive();
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ErrorType for Pin {
+    type Error = ErrorKind;
+}
+
+impl OutputPin for Pin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.set(false)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.set(true)
+    }
+}
+
+impl InputPin for Pin {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.hds[0].borrow().lines[self.line])
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.hds[0].borrow().lines[self.line])
+    }
+}
+
+/// A delay that adds up the time waited
+pub struct Delay(pub Shared);
+
+impl DelayNs for Delay {
+    fn delay_ns(&mut self, ns: u32) {
+        self.0.borrow_mut().elapsed_us += ns as u64 / 1000;
+    }
+}
+
+/// Get a pin connected to `line` of the controller.
+pub fn pin(hd: &Shared, line: usize) -> Pin {
+    shared_pin(core::slice::from_ref(hd), line)
+}
+
+/// Get a pin connected to `line` of every controller in `hds`, like a shared bus.
+pub fn shared_pin(hds: &[Shared], line: usize) -> Pin {
+    Pin {
+        line,
+        hds: hds.to_vec(),
+    }
+}
+
+/// Connect a display to the controller with RW and a four-bit bus (not built yet).
+pub fn half_bus(hd: &Shared) -> LcdDisplay<Pin, Delay> {
+    LcdDisplay::new(pin(hd, RS), pin(hd, EN), Delay(hd.clone()))
+        .with_rw(pin(hd, RW))
+        .with_half_bus(
+            pin(hd, D0 + 4),
+            pin(hd, D0 + 5),
+            pin(hd, D0 + 6),
+            pin(hd, D0 + 7),
+        )
+}
+
+/// Connect a display to the controller with RW and an eight-bit bus (not built yet).
+pub fn full_bus(hd: &Shared) -> LcdDisplay<Pin, Delay> {
+    LcdDisplay::new(pin(hd, RS), pin(hd, EN), Delay(hd.clone()))
+        .with_rw(pin(hd, RW))
+        .with_full_bus(
+            pin(hd, D0),
+            pin(hd, D0 + 1),
+            pin(hd, D0 + 2),
+            pin(hd, D0 + 3),
+            pin(hd, D0 + 4),
+            pin(hd, D0 + 5),
+            pin(hd, D0 + 6),
+            pin(hd, D0 + 7),
+        )
+}
+
+/// A character display that records what it shows, for testing code written against
+/// [CharacterDisplay] without hardware
+pub struct Recorder {
+    pub cols: u8,
+    pub rows: u8,
+    pub cells: [[u8; 40]; 4],
+    pub glyphs: [[u8; 8]; 8],
+    pub col: u8,
+    pub row: u8,
+    pub cursor: Cursor,
+    pub blink: Blink,
+    /// Number of characters written, to check that widgets only redraw what changed
+    pub writes: usize,
+}
+
+impl Recorder {
+    pub fn new(cols: u8, rows: u8) -> Self {
+        Self {
+            cols,
+            rows,
+            cells: [[b' '; 40]; 4],
+            glyphs: [[0; 8]; 8],
+            col: 0,
+            row: 0,
+            cursor: Cursor::Off,
+            blink: Blink::Off,
+            writes: 0,
+        }
+    }
+
+    /// Get the visible text of a row.
+    pub fn line(&self, row: usize) -> String {
+        self.cells[row][..self.cols as usize]
+            .iter()
+            .map(|b| *b as char)
+            .collect()
+    }
+}
+
+impl CharacterDisplay for Recorder {
+    fn cols(&self) -> u8 {
+        self.cols
+    }
+
+    fn rows(&self) -> u8 {
+        self.rows
+    }
+
+    fn print(&mut self, text: &str) {
+        for ch in text.chars() {
+            self.write(ch as u8);
+        }
+    }
+
+    fn write(&mut self, value: u8) {
+        if let Some(cell) = self.cells[self.row as usize].get_mut(self.col as usize) {
+            *cell = value;
+        }
+        self.col = self.col.saturating_add(1);
+        self.writes += 1;
+    }
+
+    fn set_position(&mut self, col: u8, row: u8) {
+        self.col = col;
+        self.row = row.min(self.rows - 1);
+    }
+
+    fn position(&self) -> (u8, u8) {
+        (self.col, self.row)
+    }
+
+    fn clear(&mut self) {
+        self.cells = [[b' '; 40]; 4];
+        self.col = 0;
+        self.row = 0;
+    }
+
+    fn set_character(&mut self, location: u8, map: [u8; 8]) {
+        self.glyphs[(location & 0x7) as usize] = map;
+    }
+
+    fn set_cursor(&mut self, cursor: Cursor) {
+        self.cursor = cursor;
+    }
+
+    fn set_blink(&mut self, blink: Blink) {
+        self.blink = blink;
+    }
+}
+
+/// A Newhaven transport that keeps every byte sent
+pub struct Bytes(pub Vec<u8>);
+
+impl Transport for Bytes {
+    fn send(&mut self, bytes: &[u8]) -> bool {
+        self.0.extend_from_slice(bytes);
+        true
+    }
+}
+
+/// An I2C bus that keeps every write as (address, bytes)
+#[derive(Default)]
+pub struct Bus {
+    pub writes: Vec<(u8, Vec<u8>)>,
+}
+
+impl i2c::ErrorType for Bus {
+    type Error = i2c::ErrorKind;
+}
+
+impl I2c<SevenBitAddress> for Bus {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        for operation in operations {
+            if let Operation::Write(bytes) = operation {
+                self.writes.push((address, bytes.to_vec()));
+            }
+        }
+        Ok(())
+    }
+}
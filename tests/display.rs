@@ -0,0 +1,540 @@
+//! Tests for the LcdDisplay driver against a model of the controller
+
+mod common;
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use ag_lcd::{
+    Backlight, Controller, Display, DoubleHeight, Error, Layout, LcdDisplay, Lines, RetryPolicy,
+    Size,
+};
+use common::{half_bus, pin, Delay, Hd44780, Pin, Shared, D0, EN, RS, RW};
+
+#[test]
+fn set_position_out_of_bounds_is_clamped() {
+    let hd = Hd44780::new();
+    let mut lcd = half_bus(&hd)
+        .with_lines(Lines::FourLines)
+        .with_cols(20)
+        .build();
+
+    lcd.set_position(250, 3);
+    assert_eq!(lcd.error(), Error::OutOfBounds);
+    assert_eq!(lcd.position(), (39, 3));
+    assert_eq!(
+        hd.borrow().commands.last(),
+        Some(&(0x80 | ((0x54 + 39) & 0x7F)))
+    );
+
+    lcd.clear_error();
+    lcd.set_position(5, 9);
+    assert_eq!(lcd.error(), Error::OutOfBounds);
+    assert_eq!(lcd.position(), (5, 3));
+}
+
+#[test]
+fn add_character_uses_free_slots() {
+    let hd = Hd44780::new();
+    let mut lcd = half_bus(&hd).with_lines(Lines::TwoLines).build();
+
+    lcd.set_character(0, [0x01; 8]);
+    assert_eq!(lcd.add_character([0x02; 8]), Some(1));
+    assert_eq!(hd.borrow().glyph(1), [0x02; 8]);
+
+    for slot in 2..8 {
+        assert_eq!(lcd.add_character([slot; 8]), Some(slot));
+    }
+    assert_eq!(lcd.error(), Error::None);
+
+    assert_eq!(lcd.add_character([0x1F; 8]), None);
+    assert_eq!(lcd.error(), Error::CgRamFull);
+    assert_eq!(hd.borrow().glyph(0), [0x01; 8]);
+
+    lcd.remove_character(4);
+    assert_eq!(lcd.add_character([0x1F; 8]), Some(4));
+    assert_eq!(hd.borrow().glyph(4), [0x1F; 8]);
+}
+
+#[test]
+fn clear_fast_blanks_every_row() {
+    let hd = Hd44780::new();
+    let mut lcd = half_bus(&hd).with_lines(Lines::TwoLines).build();
+    lcd.print_at(0, 0, "hello");
+    lcd.print_at(0, 1, "world");
+    lcd.print_at(20, 0, "hidden");
+
+    let start = hd.borrow().commands.len();
+    lcd.clear_fast();
+    assert!(!hd.borrow().commands[start..].contains(&0x01));
+    assert_eq!(hd.borrow().text(0x00, 16), " ".repeat(16));
+    assert_eq!(hd.borrow().text(0x40, 16), " ".repeat(16));
+    assert_eq!(hd.borrow().text(20, 6), "hidden");
+    assert_eq!(lcd.position(), (0, 0));
+}
+
+#[test]
+fn clear_fast_follows_the_ks0073_layout() {
+    let hd = Hd44780::new();
+    let mut lcd = half_bus(&hd)
+        .with_controller(Controller::Ks0073)
+        .with_lines(Lines::FourLines)
+        .with_cols(20)
+        .build();
+
+    for row in 0..4 {
+        lcd.print_at(0, row, "XXXXXXXXXXXXXXXXXXXX");
+    }
+    lcd.clear_fast();
+    for line in [0x00, 0x20, 0x40, 0x60] {
+        assert_eq!(hd.borrow().text(line, 32), " ".repeat(32), "{:x}", line);
+    }
+
+    // scrolled, each row wraps around within its own 32 character line
+    for row in 0..4 {
+        lcd.print_at(0, row, "XXXXXXXXXXXXXXXXXXXX");
+    }
+    lcd.scroll_left(15);
+    lcd.clear_fast();
+    for line in [0x00u8, 0x20, 0x40, 0x60] {
+        let text = hd.borrow().text(line, 32);
+        assert_eq!(&text[..3], "   ", "{:x}", line);
+        assert_eq!(&text[3..15], "XXXXXXXXXXXX", "{:x}", line);
+        assert_eq!(&text[15..], " ".repeat(17), "{:x}", line);
+    }
+}
+
+#[test]
+fn failed_transfers_are_not_retried_by_default() {
+    let hd = Hd44780::new();
+    let mut lcd = half_bus(&hd).with_lines(Lines::TwoLines).build();
+    assert_eq!(lcd.retry_policy(), RetryPolicy::new(1, 0));
+
+    let start = hd.borrow().data.len();
+    hd.borrow_mut().failures = 1;
+    lcd.write(b'a');
+    assert_ne!(lcd.error(), Error::None);
+    assert!(hd.borrow().data.len() - start <= 1);
+}
+
+#[test]
+fn failed_transfers_are_retried_by_the_policy() {
+    let hd = Hd44780::new();
+    let mut lcd = half_bus(&hd)
+        .with_lines(Lines::TwoLines)
+        .with_retry_policy(RetryPolicy::new(3, 100))
+        .build();
+    lcd.print("ab");
+
+    // the first pin write of the character fails, and then the resync as well
+    hd.borrow_mut().failures = 2;
+    let elapsed = hd.borrow().elapsed_us;
+    lcd.write(b'c');
+    assert_eq!(lcd.error(), Error::None);
+    assert_eq!(hd.borrow().text(0, 3), "abc");
+    assert!(hd.borrow().elapsed_us - elapsed >= 200);
+
+    // more failures than attempts are reported
+    hd.borrow_mut().failures = 1000;
+    lcd.write(b'd');
+    assert_ne!(lcd.error(), Error::None);
+}
+
+#[test]
+fn st7036_table_commands_are_not_cgram_addresses() {
+    let hd = Hd44780::new();
+    hd.borrow_mut().tables = true;
+    let mut lcd = half_bus(&hd)
+        .with_controller(Controller::St7036)
+        .with_lines(Lines::TwoLines)
+        .with_retry_policy(RetryPolicy::new(3, 0))
+        .build();
+
+    // 0x70 | contrast in instruction table 1 looks like a CGRAM address in table 0
+    lcd.set_contrast(40);
+    lcd.print("ab");
+    hd.borrow_mut().failures = 2;
+    lcd.write(b'c');
+
+    assert_eq!(lcd.error(), Error::None);
+    assert_eq!(hd.borrow().text(0, 3), "abc");
+    assert_eq!(hd.borrow().cgram, [0; 64]);
+}
+
+#[test]
+fn double_height_can_be_set_before_the_controller() {
+    let hd = Hd44780::new();
+    hd.borrow_mut().tables = true;
+    let lcd = half_bus(&hd)
+        .with_double_height(DoubleHeight::Top)
+        .with_controller(Controller::St7036)
+        .with_lines(Lines::TwoLines)
+        .build();
+    assert_eq!(lcd.double_height(), DoubleHeight::Top);
+    assert_eq!(hd.borrow().function & 0x04, 0x04);
+
+    let hd = Hd44780::new();
+    let lcd = half_bus(&hd)
+        .with_double_height(DoubleHeight::Top)
+        .with_lines(Lines::TwoLines)
+        .build();
+    assert_eq!(lcd.double_height(), DoubleHeight::Off);
+    assert!(hd.borrow().commands.iter().all(|c| c & 0xE4 != 0x24));
+}
+
+#[test]
+fn ks0073_keeps_custom_row_offsets_outside_four_line_mode() {
+    let hd = Hd44780::new();
+    let mut lcd = half_bus(&hd)
+        .with_controller(Controller::Ks0073)
+        .with_lines(Lines::FourLines)
+        .with_cols(16)
+        .with_row_offsets([0x00, 0x48, 0x18, 0x58])
+        .build();
+
+    lcd.set_position(0, 3);
+    assert_eq!(hd.borrow().commands.last(), Some(&(0x80 | 0x60)));
+
+    lcd.set_lines(Lines::TwoLines);
+    lcd.set_lines(Lines::FourLines);
+    lcd.set_lines(Lines::TwoLines);
+    lcd.set_position(0, 1);
+    assert_eq!(hd.borrow().commands.last(), Some(&(0x80 | 0x48)));
+}
+
+#[test]
+fn size_is_kept_until_the_display_has_one_line() {
+    let hd = Hd44780::new();
+    let mut lcd = half_bus(&hd).with_lines(Lines::TwoLines).build();
+
+    lcd.set_size(Size::Dots5x10);
+    assert_eq!(lcd.lines(), Lines::TwoLines);
+    assert_eq!(hd.borrow().function & 0x0C, 0x08);
+
+    lcd.set_lines(Lines::OneLine);
+    assert_eq!(lcd.size(), Size::Dots5x10);
+    assert_eq!(hd.borrow().function & 0x0C, 0x04);
+
+    lcd.set_lines(Lines::TwoLines);
+    lcd.set_lines(Lines::OneLine);
+    assert_eq!(lcd.size(), Size::Dots5x10);
+}
+
+#[test]
+fn with_size_before_with_lines_does_not_select_four_lines() {
+    let hd = Hd44780::new();
+    let lcd = half_bus(&hd)
+        .with_size(Size::Dots5x10)
+        .with_lines(Lines::TwoLines)
+        .build();
+    assert_eq!(lcd.lines(), Lines::TwoLines);
+    assert_eq!(lcd.rows(), 2);
+}
+
+#[test]
+fn graphics_are_only_sent_to_a_ws0010() {
+    let hd = Hd44780::new();
+    let mut lcd = half_bus(&hd).build();
+    let (commands, data) = (hd.borrow().commands.len(), hd.borrow().data.len());
+    lcd.graphic_mode();
+    assert_eq!(lcd.error(), Error::Unsupported);
+    lcd.clear_error();
+    lcd.set_graphic_position(8, 1);
+    assert_eq!(lcd.error(), Error::Unsupported);
+    lcd.clear_error();
+    lcd.write_graphic(&[0xFF, 0x81]);
+    assert_eq!(lcd.error(), Error::Unsupported);
+    lcd.clear_error();
+    lcd.character_mode();
+    assert_eq!(lcd.error(), Error::Unsupported);
+    assert_eq!(hd.borrow().commands.len(), commands);
+    assert_eq!(hd.borrow().data.len(), data);
+
+    let hd = Hd44780::new();
+    let mut lcd = half_bus(&hd).with_controller(Controller::Ws0010).build();
+    lcd.graphic_mode();
+    lcd.set_graphic_position(8, 1);
+    lcd.write_graphic(&[0xFF, 0x81]);
+    lcd.character_mode();
+    assert_eq!(lcd.error(), Error::None);
+    assert!(hd.borrow().data.ends_with(&[0xFF, 0x81]));
+}
+
+static SWITCHED_ON: AtomicU32 = AtomicU32::new(0);
+
+fn count_power_on(on: bool) -> bool {
+    if on {
+        SWITCHED_ON.fetch_add(1, Ordering::SeqCst);
+    }
+    true
+}
+
+#[test]
+fn power_switch_is_turned_on_by_build() {
+    let hd = Hd44780::new();
+    let lcd = half_bus(&hd).with_power_switch(count_power_on).build();
+    assert_eq!(SWITCHED_ON.load(Ordering::SeqCst), 1);
+    assert_eq!(lcd.error(), Error::None);
+}
+
+#[test]
+fn power_switch_failures_are_reported() {
+    let hd = Hd44780::new();
+    let mut lcd = half_bus(&hd).with_power_switch(|_| false).build();
+    assert_eq!(lcd.error(), Error::PowerFailed);
+
+    lcd.clear_error();
+    lcd.power_cycle();
+    assert_eq!(lcd.error(), Error::PowerFailed);
+}
+
+#[test]
+fn power_pin_failures_are_reported() {
+    let hd = Hd44780::new();
+    let power = pin(&Hd44780::new(), 0);
+    let lcd = half_bus(&hd).with_power_pin(power).build();
+    assert_eq!(lcd.error(), Error::None);
+
+    let other = Hd44780::new();
+    other.borrow_mut().failures = 1;
+    let lcd = half_bus(&hd).with_power_pin(pin(&other, 0)).build();
+    assert_eq!(lcd.error(), Error::PowerFailed);
+}
+
+static CYCLED_OFF: AtomicU32 = AtomicU32::new(0);
+
+fn count_power_off(on: bool) -> bool {
+    if !on {
+        CYCLED_OFF.fetch_add(1, Ordering::SeqCst);
+    }
+    true
+}
+
+#[test]
+fn verified_init_power_cycles_only_when_the_display_does_not_respond() {
+    let hd = Hd44780::new();
+    let lcd = half_bus(&hd)
+        .with_power_switch(count_power_off)
+        .with_verified_init(3)
+        .build();
+    assert_eq!(lcd.error(), Error::None);
+    assert_eq!(CYCLED_OFF.load(Ordering::SeqCst), 0);
+
+    // RW isn't connected, so nothing is read back
+    let mut lcd = LcdDisplay::new(pin(&hd, RS), pin(&hd, EN), Delay(hd.clone()))
+        .with_rw(pin(&Hd44780::new(), RW))
+        .with_half_bus(
+            pin(&hd, D0 + 4),
+            pin(&hd, D0 + 5),
+            pin(&hd, D0 + 6),
+            pin(&hd, D0 + 7),
+        )
+        .with_power_switch(count_power_off)
+        .with_verified_init(3)
+        .build();
+    assert_eq!(lcd.error(), Error::VerifyFailed);
+    assert_eq!(CYCLED_OFF.load(Ordering::SeqCst), 2);
+
+    lcd.clear_error();
+    lcd.reinit();
+    assert_eq!(lcd.error(), Error::VerifyFailed);
+    assert_eq!(CYCLED_OFF.load(Ordering::SeqCst), 4);
+}
+
+#[test]
+fn verified_init_leaves_the_display_cleared() {
+    let hd = Hd44780::new();
+    let mut lcd = half_bus(&hd).build();
+    lcd.print("hello");
+    assert!(lcd.reliable_init_verified(2));
+    assert_eq!(hd.borrow().text(0, 5), "     ");
+    assert_eq!(lcd.position(), (0, 0));
+}
+
+#[test]
+fn rtl_text_runs_left_from_the_cursor() {
+    let hd = Hd44780::new();
+    let mut lcd = half_bus(&hd).with_lines(Lines::TwoLines).build();
+    lcd.set_position(15, 0);
+    lcd.print_rtl("HELLO 123");
+    assert_eq!(hd.borrow().text(0, 16), "       123 OLLEH");
+    assert_eq!(lcd.position(), (6, 0));
+
+    lcd.set_position(15, 1);
+    lcd.print_rtl("AB12CD (X)");
+    assert_eq!(hd.borrow().text(0x40, 16), "      (X) DC12BA");
+}
+
+#[test]
+fn rtl_text_past_the_first_column_is_dropped() {
+    let hd = Hd44780::new();
+    let mut lcd = half_bus(&hd).with_lines(Lines::TwoLines).build();
+    lcd.set_position(2, 1);
+    lcd.print_rtl("ABCDE");
+    assert_eq!(hd.borrow().text(0x40, 4), "CBA ");
+    assert_eq!(lcd.position(), (0, 1));
+}
+
+#[test]
+fn rtl_text_keeps_a_right_to_left_layout() {
+    let hd = Hd44780::new();
+    let mut lcd = half_bus(&hd)
+        .with_lines(Lines::TwoLines)
+        .with_layout(Layout::RightToLeft)
+        .build();
+    lcd.set_position(9, 0);
+    lcd.print_rtl("ABC");
+    assert_eq!(hd.borrow().text(5, 5), "  CBA");
+    assert_eq!(lcd.layout(), Layout::RightToLeft);
+    assert!(!hd.borrow().increment);
+    assert_eq!(lcd.position(), (6, 0));
+}
+
+/// Connect a display whose data pin `n` is wired to line D4+`order[n]` of the controller
+/// (D0+`order[n]` and D4+`order[n]` on an eight-bit bus).
+fn swapped_bus(hd: &Shared, order: [usize; 4], eight_bits: bool) -> LcdDisplay<Pin, Delay> {
+    let lcd = LcdDisplay::new(pin(hd, RS), pin(hd, EN), Delay(hd.clone())).with_rw(pin(hd, RW));
+    let line = |n: usize, half: usize| pin(hd, D0 + half + order[n]);
+    match eight_bits {
+        true => lcd.with_full_bus(
+            line(0, 0),
+            line(1, 0),
+            line(2, 0),
+            line(3, 0),
+            line(0, 4),
+            line(1, 4),
+            line(2, 4),
+            line(3, 4),
+        ),
+        false => lcd.with_half_bus(line(0, 4), line(1, 4), line(2, 4), line(3, 4)),
+    }
+}
+
+#[test]
+fn swapped_data_lines_are_remapped() {
+    for eight_bits in [false, true] {
+        let hd = Hd44780::new();
+        let mut lcd = swapped_bus(&hd, [1, 2, 3, 0], eight_bits)
+            .with_data_order([1, 2, 3, 0])
+            .with_lines(Lines::TwoLines)
+            .build();
+        lcd.print("Hi!");
+        lcd.set_position(2, 1);
+        lcd.print_verified("ok");
+
+        assert_eq!(lcd.error(), Error::None);
+        assert_eq!(hd.borrow().text(0, 3), "Hi!");
+        assert_eq!(hd.borrow().text(0x42, 2), "ok");
+        assert_eq!(lcd.address_counter(), 0x44);
+    }
+}
+
+#[test]
+fn swapped_data_lines_garble_text_without_a_data_order() {
+    let hd = Hd44780::new();
+    let mut lcd = swapped_bus(&hd, [3, 2, 1, 0], false).build();
+    lcd.print("Hi");
+    assert_ne!(hd.borrow().text(0, 2), "Hi");
+}
+
+#[test]
+fn data_order_must_be_a_permutation() {
+    let hd = Hd44780::new();
+    let mut lcd = half_bus(&hd).with_data_order([0, 1, 1, 3]).build();
+    assert_eq!(lcd.error(), Error::InvalidMode);
+
+    // the default order is kept
+    lcd.clear_error();
+    lcd.print("Hi");
+    assert_eq!(hd.borrow().text(0, 2), "Hi");
+}
+
+#[test]
+fn wake_turns_on_a_display_that_slept_while_blanked() {
+    let hd = Hd44780::new();
+    let mut lcd = half_bus(&hd)
+        .with_backlight(pin(&Hd44780::new(), RS))
+        .with_screensaver(10)
+        .build();
+    lcd.backlight_on();
+    lcd.tick(0);
+    lcd.tick(10);
+    assert_eq!(lcd.display(), Display::Off);
+
+    lcd.sleep();
+    lcd.wake();
+    assert!(!lcd.is_sleeping());
+    assert_eq!(lcd.display(), Display::On);
+    assert_eq!(hd.borrow().control & 0x04, 0x04);
+    assert_eq!(lcd.backlight(), Backlight::On);
+}
+
+#[test]
+fn printing_while_asleep_does_not_wake_the_display() {
+    let hd = Hd44780::new();
+    let mut lcd = half_bus(&hd)
+        .with_backlight(pin(&Hd44780::new(), RS))
+        .with_backlight_timeout(10)
+        .build();
+    lcd.backlight_on();
+    lcd.tick(0);
+    lcd.tick(10);
+    assert_eq!(lcd.backlight(), Backlight::Off);
+
+    lcd.sleep();
+    lcd.print("hi");
+    assert!(lcd.is_sleeping());
+    assert_eq!(lcd.display(), Display::Off);
+    assert_eq!(lcd.backlight(), Backlight::Off);
+
+    lcd.wake();
+    assert_eq!(lcd.display(), Display::On);
+    assert_eq!(lcd.backlight(), Backlight::On);
+}
+
+#[test]
+fn ks0073_columns_are_clamped_to_its_row_length() {
+    let hd = Hd44780::new();
+    let mut lcd = half_bus(&hd)
+        .with_controller(Controller::Ks0073)
+        .with_lines(Lines::FourLines)
+        .with_cols(20)
+        .build();
+    lcd.set_position(31, 0);
+    assert_eq!(lcd.error(), Error::None);
+    assert_eq!(hd.borrow().commands.last(), Some(&(0x80 | 31)));
+
+    lcd.set_position(35, 0);
+    assert_eq!(lcd.error(), Error::OutOfBounds);
+    assert_eq!(lcd.position(), (31, 0));
+    assert_eq!(hd.borrow().commands.last(), Some(&(0x80 | 31)));
+}
+
+#[test]
+fn validation_requires_every_data_pin_of_the_bus() {
+    let hd = Hd44780::new();
+    let lcd = half_bus(&hd).build();
+    assert_eq!(lcd.error(), Error::None);
+
+    let lcd = LcdDisplay::new(pin(&hd, RS), pin(&hd, EN), Delay(hd.clone())).build();
+    assert_eq!(lcd.error(), Error::InvalidMode);
+}
+
+#[test]
+fn scroll_reset_goes_the_shorter_way_around() {
+    let hd = Hd44780::new();
+    let mut lcd = half_bus(&hd).build();
+
+    lcd.scroll_left(39);
+    assert_eq!(lcd.scroll_offset(), -39);
+
+    let start = hd.borrow().commands.len();
+    lcd.scroll_reset();
+    assert_eq!(hd.borrow().commands[start..], [0x18]);
+    assert_eq!(lcd.scroll_offset(), 0);
+
+    lcd.scroll_right(3);
+    let start = hd.borrow().commands.len();
+    lcd.scroll_reset();
+    assert_eq!(hd.borrow().commands[start..], [0x18; 3]);
+    assert_eq!(lcd.scroll_offset(), 0);
+}
@@ -0,0 +1,76 @@
+//! Tests for the drivers of displays with their own serial interface
+
+mod common;
+
+use ag_lcd::{Aip31068Lcd, Error, NewhavenLcd, AIP31068_ADDRESS};
+use common::{half_bus, Bus, Bytes, Delay, Hd44780};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+static NEWHAVEN_YIELDS: AtomicU32 = AtomicU32::new(0);
+static AIP31068_YIELDS: AtomicU32 = AtomicU32::new(0);
+
+#[test]
+fn newhaven_delays_call_the_yield_hook() {
+    let hd = Hd44780::new();
+    let mut lcd = NewhavenLcd::new(Bytes(Vec::new()), Delay(hd.clone())).with_yield_hook(
+        || {
+            NEWHAVEN_YIELDS.fetch_add(1, Ordering::Relaxed);
+        },
+        1000,
+    );
+
+    lcd.clear();
+    lcd.print("abcdefghij");
+    let waited = hd.borrow().elapsed_us as u32;
+    assert_eq!(NEWHAVEN_YIELDS.load(Ordering::Relaxed), waited / 1000);
+    assert!(waited >= 1500 + 10 * 100);
+}
+
+#[test]
+fn aip31068_initialization_calls_the_yield_hook() {
+    let hd = Hd44780::new();
+    let lcd = Aip31068Lcd::new(Bus::default(), Delay(hd.clone()))
+        .with_yield_hook(
+            || {
+                AIP31068_YIELDS.fetch_add(1, Ordering::Relaxed);
+            },
+            1000,
+        )
+        .build();
+
+    // 50ms power-on delay, three function sets and a clear
+    assert!(AIP31068_YIELDS.load(Ordering::Relaxed) >= 50 + 13);
+    let (bus, _) = lcd.release();
+    assert!(bus
+        .writes
+        .iter()
+        .all(|(address, _)| *address == AIP31068_ADDRESS));
+}
+
+#[test]
+fn character_locations_past_seven_are_reported_by_every_driver() {
+    let hd = Hd44780::new();
+    let mut newhaven = NewhavenLcd::new(Bytes(Vec::new()), Delay(hd.clone()));
+    newhaven.set_character(7, [0x1F; 8]);
+    assert_eq!(newhaven.error(), Error::None);
+    newhaven.set_character(9, [0x1F; 8]);
+    assert_eq!(newhaven.error(), Error::OutOfBounds);
+    let (bytes, _) = newhaven.release();
+    assert_eq!(&bytes.0[bytes.0.len() - 10..][..2], &[0x54, 1]);
+
+    let mut aip = Aip31068Lcd::new(Bus::default(), Delay(hd.clone())).build();
+    aip.set_character(7, [0x1F; 8]);
+    assert_eq!(aip.error(), Error::None);
+    aip.set_character(9, [0x1F; 8]);
+    assert_eq!(aip.error(), Error::OutOfBounds);
+    let (bus, _) = aip.release();
+    assert!(bus
+        .writes
+        .iter()
+        .any(|(_, bytes)| bytes.ends_with(&[0x40 | 8])));
+
+    let mut lcd = half_bus(&hd).build();
+    lcd.set_character(9, [0x1F; 8]);
+    assert_eq!(lcd.error(), Error::OutOfBounds);
+    assert_eq!(hd.borrow().glyph(1), [0x1F; 8]);
+}
@@ -0,0 +1,35 @@
+//! Tests for formatting values with ufmt
+#![cfg(feature = "ufmt")]
+
+use ag_lcd::{Fixed, FormatBuffer};
+
+/// Format a value into a string.
+fn show<T: ufmt::uDisplay>(value: T) -> String {
+    let mut buffer: FormatBuffer<40> = FormatBuffer::new();
+    ufmt::uwrite!(&mut buffer, "{}", value).unwrap();
+    buffer.as_str().to_string()
+}
+
+#[test]
+fn fixed_places_the_decimal_point() {
+    assert_eq!(show(Fixed::<1>(235)), "23.5");
+    assert_eq!(show(Fixed::<2>(12345)), "123.45");
+    assert_eq!(show(Fixed::<3>(1000)), "1.000");
+    assert_eq!(show(Fixed::<0>(42)), "42");
+}
+
+#[test]
+fn fixed_pads_small_values_with_zeros() {
+    assert_eq!(show(Fixed::<2>(5)), "0.05");
+    assert_eq!(show(Fixed::<2>(-5)), "-0.05");
+    assert_eq!(show(Fixed::<3>(0)), "0.000");
+    assert_eq!(show(Fixed::<12>(1)), "0.000000000001");
+}
+
+#[test]
+fn fixed_keeps_the_sign_and_every_digit() {
+    assert_eq!(show(Fixed::<1>(-10)), "-1.0");
+    assert_eq!(show(Fixed::<0>(0)), "0");
+    assert_eq!(show(Fixed::<2>(i32::MAX)), "21474836.47");
+    assert_eq!(show(Fixed::<2>(i32::MIN)), "-21474836.48");
+}
@@ -0,0 +1,70 @@
+//! Tests for the input sources
+
+use ag_lcd::{InputEvent, InputSource, KeypadInput};
+
+/// Poll a keypad that reads each of `values` in turn and collect what it reports.
+fn poll_all(values: &[u16], debounce: u8) -> Vec<Option<InputEvent>> {
+    let mut readings = values.iter().copied();
+    let mut keypad = KeypadInput::new(|| readings.next().unwrap_or(1023)).with_debounce(debounce);
+    values.iter().map(|_| keypad.poll()).collect()
+}
+
+#[test]
+fn keypad_reports_a_press_once_it_is_stable() {
+    let events = poll_all(&[1023, 100, 100, 100, 100, 1023], 3);
+    assert_eq!(events, [None, None, None, Some(InputEvent::Up), None, None]);
+}
+
+#[test]
+fn keypad_ignores_bounces_and_transitions() {
+    // bounces back to released, then passes through DOWN on the way to LEFT
+    let events = poll_all(&[100, 1023, 100, 100, 300, 500, 500, 500], 2);
+    assert_eq!(
+        events,
+        [
+            None,
+            None,
+            None,
+            Some(InputEvent::Up),
+            None,
+            None,
+            Some(InputEvent::Left),
+            None
+        ]
+    );
+}
+
+#[test]
+fn keypad_reports_a_held_button_once() {
+    let events = poll_all(&[700, 700, 700, 700, 1023, 700], 1);
+    assert_eq!(
+        events,
+        [
+            Some(InputEvent::Select),
+            None,
+            None,
+            None,
+            None,
+            Some(InputEvent::Select)
+        ]
+    );
+}
+
+#[test]
+fn keypad_needs_a_stable_release_before_the_next_press() {
+    let events = poll_all(&[0, 0, 1023, 0, 0, 1023, 1023, 0, 0], 2);
+    assert_eq!(
+        events,
+        [
+            None,
+            Some(InputEvent::Right),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(InputEvent::Right)
+        ]
+    );
+}
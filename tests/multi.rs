@@ -0,0 +1,68 @@
+//! Tests for several displays sharing one bus
+
+mod common;
+
+use ag_lcd::{Controller, Display, Error, LcdDisplay, Lines, MultiLcd, RetryPolicy};
+use common::{shared_pin, Delay, Hd44780, D0, EN, RS};
+
+#[test]
+fn each_display_keeps_its_own_state() {
+    let hds = [Hd44780::new(), Hd44780::new()];
+    let bus = |line| shared_pin(&hds, line);
+
+    let lcd = LcdDisplay::new(bus(RS), shared_pin(&hds[..1], EN), Delay(hds[0].clone()))
+        .with_half_bus(bus(D0 + 4), bus(D0 + 5), bus(D0 + 6), bus(D0 + 7))
+        .with_lines(Lines::TwoLines);
+    let mut displays = MultiLcd::new(lcd, [shared_pin(&hds[1..], EN)]).build();
+
+    let first = displays.select(0).unwrap();
+    first.print("one");
+    assert_eq!(first.add_character([0x01; 8]), Some(0));
+    first.sleep();
+    assert_eq!(first.display(), Display::Off);
+
+    let second = displays.select(1).unwrap();
+    assert_eq!(second.display(), Display::On);
+    assert_eq!(second.position(), (0, 0));
+    assert_eq!(second.add_character([0x02; 8]), Some(0));
+    second.print("two");
+    second.wake();
+
+    let first = displays.select(0).unwrap();
+    assert_eq!(first.position(), (3, 0));
+    first.wake();
+    assert_eq!(first.display(), Display::On);
+
+    assert_eq!(hds[0].borrow().text(0, 3), "one");
+    assert_eq!(hds[1].borrow().text(0, 3), "two");
+    assert_eq!(hds[0].borrow().glyph(0), [0x01; 8]);
+    assert_eq!(hds[1].borrow().glyph(0), [0x02; 8]);
+    assert_eq!(hds[0].borrow().control & 0x04, 0x04);
+    assert_eq!(hds[1].borrow().control & 0x04, 0x04);
+}
+
+#[test]
+fn each_display_keeps_its_own_instruction_table() {
+    let hds = [Hd44780::new(), Hd44780::new()];
+    hds[0].borrow_mut().tables = true;
+    let bus = |line| shared_pin(&hds, line);
+
+    let lcd = LcdDisplay::new(bus(RS), shared_pin(&hds[..1], EN), Delay(hds[0].clone()))
+        .with_half_bus(bus(D0 + 4), bus(D0 + 5), bus(D0 + 6), bus(D0 + 7))
+        .with_controller(Controller::St7036)
+        .with_lines(Lines::TwoLines)
+        .with_retry_policy(RetryPolicy::new(3, 0));
+    let mut displays = MultiLcd::new(lcd, [shared_pin(&hds[1..], EN)]).build();
+
+    // the first display is left in instruction table 1
+    let first = displays.select(0).unwrap();
+    first.raw_command(0x29);
+
+    // a retry on the second display goes back to its CGRAM address
+    let second = displays.select(1).unwrap();
+    second.raw_command(0x48);
+    hds[0].borrow_mut().failures = 2;
+    second.write(0x1F);
+    assert_eq!(second.error(), Error::None);
+    assert_eq!(hds[1].borrow().cgram[8], 0x1F);
+}
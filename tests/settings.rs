@@ -0,0 +1,155 @@
+//! Tests for saving and restoring display settings
+
+mod common;
+
+use ag_lcd::{
+    Backlight, Blink, Controller, Cursor, Display, Error, FunctionSet, Lines, Settings, Size,
+    SETTINGS_SIZE,
+};
+use common::{half_bus, Hd44780};
+
+#[test]
+fn settings_round_trip_through_bytes() {
+    let hd = Hd44780::new();
+    let mut lcd = half_bus(&hd)
+        .with_lines(Lines::TwoLines)
+        .with_cursor(Cursor::On)
+        .with_blink(Blink::On)
+        .build();
+
+    let settings = lcd.settings();
+    assert_eq!(Settings::from_bytes(settings.to_bytes()), Some(settings));
+
+    lcd.set_cursor(Cursor::Off);
+    lcd.restore(Settings::from_bytes(settings.to_bytes()).unwrap());
+    assert_eq!(lcd.settings(), settings);
+    assert_eq!(hd.borrow().control, 0x07);
+}
+
+#[test]
+fn erased_or_corrupted_bytes_are_rejected() {
+    assert_eq!(Settings::from_bytes([0xFF; SETTINGS_SIZE]), None);
+    assert_eq!(Settings::from_bytes([0x00; SETTINGS_SIZE]), None);
+
+    let hd = Hd44780::new();
+    let lcd = half_bus(&hd).build();
+    let mut bytes = lcd.settings().to_bytes();
+    bytes[2] ^= 0x80;
+    assert_eq!(Settings::from_bytes(bytes), None);
+}
+
+/// Checksum used for stored settings, to build bytes that pass the check
+fn checksum(bytes: &[u8]) -> u8 {
+    bytes
+        .iter()
+        .fold(0xA5u8, |sum, byte| sum.rotate_left(1) ^ byte)
+}
+
+#[test]
+fn restore_only_sends_instruction_bits() {
+    let hd = Hd44780::new();
+    let mut lcd = half_bus(&hd).with_lines(Lines::TwoLines).build();
+    lcd.print("ab");
+
+    // every bit set, but with a valid marker and checksum
+    let mut bytes = [0xFF; SETTINGS_SIZE];
+    bytes[0] = lcd.settings().to_bytes()[0];
+    bytes[SETTINGS_SIZE - 1] = checksum(&bytes[..SETTINGS_SIZE - 1]);
+    let settings = Settings::from_bytes(bytes).unwrap();
+
+    let start = hd.borrow().commands.len();
+    lcd.restore(settings);
+    let commands = hd.borrow().commands[start..].to_vec();
+    assert!(!commands.contains(&0xFF), "{:x?}", commands);
+    assert!(commands.contains(&0x0F) && commands.contains(&0x07));
+    assert_eq!(hd.borrow().text(0, 2), "ab");
+    assert_eq!(lcd.display(), Display::On);
+    assert_eq!(lcd.error(), Error::None);
+}
+
+#[test]
+fn apply_reconfigures_a_ks0073() {
+    let hd = Hd44780::new();
+    let mut lcd = half_bus(&hd)
+        .with_controller(Controller::Ks0073)
+        .with_lines(Lines::FourLines)
+        .with_cols(20)
+        .build();
+
+    let two_lines = Settings::new(
+        FunctionSet {
+            lines: Lines::TwoLines,
+            ..lcd.function_set()
+        },
+        lcd.display_control(),
+        lcd.entry_mode(),
+        Backlight::On,
+    );
+    lcd.apply(two_lines);
+    assert_eq!(lcd.lines(), Lines::TwoLines);
+    lcd.set_position(0, 1);
+    assert_eq!(hd.borrow().commands.last(), Some(&0xC0));
+    assert_eq!(lcd.error(), Error::None);
+}
+
+#[test]
+fn restore_sends_st7036_options_again() {
+    let hd = Hd44780::new();
+    let mut lcd = half_bus(&hd)
+        .with_controller(Controller::St7036)
+        .with_contrast(40)
+        .with_lines(Lines::TwoLines)
+        .build();
+    let settings = lcd.settings();
+
+    lcd.set_lines(Lines::OneLine);
+    let start = hd.borrow().commands.len();
+    lcd.restore(settings);
+    let commands = hd.borrow().commands[start..].to_vec();
+    assert!(commands.contains(&(0x70 | (40 & 0x0F))), "{:x?}", commands);
+    assert_eq!(lcd.lines(), Lines::TwoLines);
+}
+
+#[test]
+fn contrast_can_be_set_before_the_controller() {
+    let hd = Hd44780::new();
+    hd.borrow_mut().tables = true;
+    half_bus(&hd)
+        .with_contrast(40)
+        .with_controller(Controller::St7036)
+        .build();
+    assert!(hd.borrow().commands.contains(&(0x70 | (40 & 0x0F))));
+
+    let hd = Hd44780::new();
+    half_bus(&hd).with_contrast(40).build();
+    assert!(!hd.borrow().commands.contains(&(0x70 | (40 & 0x0F))));
+}
+
+#[test]
+fn settings_from_a_function_set_keep_the_line_count() {
+    let hd = Hd44780::new();
+    let mut lcd = half_bus(&hd).with_lines(Lines::OneLine).build();
+
+    for lines in [Lines::OneLine, Lines::TwoLines, Lines::FourLines] {
+        let function = FunctionSet {
+            lines,
+            font: Size::Dots5x10,
+            ..lcd.function_set()
+        };
+        let settings = Settings::new(
+            function,
+            lcd.display_control(),
+            lcd.entry_mode(),
+            Backlight::Off,
+        );
+        assert_eq!(Settings::from_bytes(settings.to_bytes()), Some(settings));
+
+        lcd.apply(settings);
+        assert_eq!(lcd.lines(), lines);
+        let font = match lines {
+            Lines::OneLine => Size::Dots5x10,
+            _ => Size::Dots5x8,
+        };
+        assert_eq!(lcd.function_set(), FunctionSet { font, ..function });
+    }
+}
@@ -0,0 +1,72 @@
+//! Tests for formatting values with SI prefixes
+
+use ag_lcd::{SiValue, SI_MAX_WIDTH};
+
+/// Format a value and get its text.
+fn si(value: i32, exponent: i8, unit: &str, width: u8) -> String {
+    SiValue::new(value, exponent, unit, width)
+        .as_str()
+        .to_string()
+}
+
+#[test]
+fn si_prefix_leaves_one_to_three_integer_digits() {
+    assert_eq!(si(3300, -3, "V", 5), "3.30V");
+    assert_eq!(si(150, -6, "A", 5), "150µA");
+    assert_eq!(si(12400, 0, "", 5), "12.4k");
+    assert_eq!(si(47, 3, "Ω", 6), "  47kΩ");
+    assert_eq!(si(1, -12, "F", 3), "1pF");
+    assert_eq!(si(2, 12, "B", 3), "2TB");
+    assert_eq!(si(0, 0, "V", 4), "  0V");
+}
+
+#[test]
+fn si_decimals_fill_the_field_up_to_the_precision_of_the_value() {
+    assert_eq!(si(99996, -2, "V", 8), " 999.96V");
+    assert_eq!(si(123456789, -3, "V", 20), "        123.456789kV");
+    assert_eq!(si(0, -6, "A", 5), "0.00A");
+}
+
+#[test]
+fn si_negative_values_count_the_sign() {
+    assert_eq!(si(-3300, -3, "V", 6), "-3.30V");
+    assert_eq!(si(-3300, -3, "V", 5), "-3.3V");
+    assert_eq!(si(i32::MIN, 0, "", 20), "       -2.147483648G");
+    assert_eq!(si(i32::MIN, 0, "", 5), "-2.1G");
+}
+
+#[test]
+fn si_rounding_carries_into_the_next_digit() {
+    assert_eq!(si(99996, -2, "V", 5), "1000V");
+    assert_eq!(si(9999, -1, "V", 5), "1000V");
+    assert_eq!(si(999_999, 0, "Hz", 6), "1.0MHz");
+    assert_eq!(si(5, -13, "F", 4), " 1pF");
+}
+
+#[test]
+fn si_uses_a_larger_prefix_when_the_integer_part_does_not_fit() {
+    assert_eq!(si(99996, -2, "V", 4), " 1kV");
+    assert_eq!(si(-99996, -2, "V", 5), " -1kV");
+    assert_eq!(si(999_500, 0, "Hz", 5), " 1MHz");
+}
+
+#[test]
+fn si_fills_the_field_when_nothing_fits() {
+    assert_eq!(si(123, 0, "V", 4), "123V");
+    assert_eq!(si(123, 0, "V", 3), "###");
+    assert_eq!(si(-150, -6, "A", 5), "#####");
+    assert_eq!(si(12345, 0, "V", 2), "##");
+    assert_eq!(si(1, 0, "V", 0), "");
+
+    // beyond the smallest and largest prefix, or rounded to nothing
+    assert_eq!(si(1, -15, "F", 4), "####");
+    assert_eq!(si(49, -14, "F", 4), "####");
+    assert_eq!(si(5, 15, "B", 4), "####");
+}
+
+#[test]
+fn si_width_is_limited() {
+    let text = si(1234, 0, "V", 40);
+    assert_eq!(text.chars().count(), SI_MAX_WIDTH as usize);
+    assert_eq!(text.trim_start(), "1.234kV");
+}
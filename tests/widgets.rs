@@ -0,0 +1,337 @@
+//! Tests for widgets drawn through the CharacterDisplay trait
+
+mod common;
+
+use ag_lcd::{
+    Align, Animate, BlinkText, CharacterDisplay, Field, NewhavenLcd, Scene, Scrollbar, Sparkline,
+    Spinner, Window, FRAME_SLOT,
+};
+use common::{Bytes, Delay, Hd44780, Recorder};
+
+/// Code written once against the trait, as an application would
+fn show_temperature<L: CharacterDisplay>(lcd: &mut L, text: &str) {
+    lcd.print_at(0, 0, "Temp:");
+    lcd.print_at(6, 0, text);
+}
+
+#[test]
+fn generic_code_runs_on_a_recorder() {
+    let mut lcd = Recorder::new(16, 2);
+    show_temperature(&mut lcd, "21.5");
+    assert_eq!(lcd.line(0), "Temp: 21.5      ");
+    assert_eq!(lcd.position(), (10, 0));
+}
+
+#[test]
+fn generic_code_runs_on_a_newhaven_display() {
+    let hd = Hd44780::new();
+    let mut lcd = NewhavenLcd::new(Bytes(Vec::new()), Delay(hd)).with_size(20, 4);
+    show_temperature(&mut lcd, "21.5");
+    assert_eq!(CharacterDisplay::position(&lcd), (10, 0));
+    assert_eq!(CharacterDisplay::rows(&lcd), 4);
+
+    let (bytes, _) = lcd.release();
+    assert_eq!(&bytes.0[..3], &[0xFE, 0x45, 0x00]);
+    assert!(bytes
+        .0
+        .ends_with(&[0xFE, 0x45, 0x06, b'2', b'1', b'.', b'5']));
+}
+
+#[test]
+fn frame_is_drawn_with_border_glyphs() {
+    let mut lcd = Recorder::new(8, 4);
+    lcd.set_position(3, 2);
+    lcd.draw_frame(Window::new(1, 0, 4, 3));
+
+    let (h, v) = (FRAME_SLOT, FRAME_SLOT + 1);
+    assert_eq!(&lcd.cells[0][..6], &[b' ', h + 2, h, h, h + 3, b' ']);
+    assert_eq!(&lcd.cells[1][..6], &[b' ', v, b' ', b' ', v, b' ']);
+    assert_eq!(&lcd.cells[2][..6], &[b' ', h + 4, h, h, h + 5, b' ']);
+    assert_ne!(lcd.glyphs[FRAME_SLOT as usize], [0; 8]);
+    assert_eq!(lcd.position(), (3, 2));
+}
+
+#[test]
+fn frame_parts_off_the_display_are_skipped() {
+    let mut lcd = Recorder::new(4, 2);
+    lcd.draw_frame(Window::new(2, 1, 5, 3));
+    assert_eq!(lcd.writes, 2);
+    assert_eq!(lcd.line(0), "    ");
+
+    lcd.draw_hline(5, 0, 3);
+    assert_eq!(lcd.writes, 2);
+}
+
+#[test]
+fn spinner_steps_through_its_frames() {
+    let mut lcd = Recorder::new(16, 2);
+    let mut spinner = Spinner::new(15, 1).with_frames(b"-|/");
+    lcd.set_position(2, 0);
+
+    let mut shown = Vec::new();
+    for _ in 0..4 {
+        spinner.animate(&mut lcd);
+        shown.push(lcd.cells[1][15]);
+    }
+    assert_eq!(shown, b"-|/-");
+    assert_eq!(lcd.position(), (2, 0));
+}
+
+#[test]
+fn blink_text_alternates_with_blanks() {
+    let mut lcd = Recorder::new(16, 2);
+    let mut text = BlinkText::new(4, 1, "ALARM");
+
+    text.animate(&mut lcd);
+    assert_eq!(lcd.line(1), "    ALARM       ");
+    text.animate(&mut lcd);
+    assert_eq!(lcd.line(1), " ".repeat(16));
+    assert_eq!(lcd.position(), (0, 0));
+}
+
+#[cfg(feature = "logger")]
+#[test]
+fn log_is_flushed_to_any_display() {
+    let log = ag_lcd::LcdLog::<32>::new();
+    let mut lcd = Recorder::new(8, 2);
+    lcd.print("old");
+
+    log.push("\nfirst\nsecond");
+    log.flush(&mut lcd);
+    assert!(log.is_empty());
+    assert_eq!(lcd.line(0), "second  ");
+    assert_eq!(lcd.line(1), "first   ");
+}
+
+/// Get the bar height shown in a sparkline cell (0 for a blank cell).
+fn bar(cell: u8) -> u8 {
+    match cell {
+        b' ' => 0,
+        slot => slot + 1,
+    }
+}
+
+#[test]
+fn sparkline_scales_samples_to_a_fixed_range() {
+    let mut lcd = Recorder::new(16, 2);
+    let mut spark: Sparkline<5> = Sparkline::new(2, 1).with_range(100, 0);
+    spark.update(&mut lcd, &[-5, 0, 25, 50, 200]);
+
+    let bars: Vec<u8> = lcd.cells[1][2..7].iter().map(|c| bar(*c)).collect();
+    assert_eq!(bars, [0, 0, 2, 4, 8]);
+}
+
+#[test]
+fn sparkline_scales_to_the_visible_samples_without_a_range() {
+    let mut lcd = Recorder::new(16, 2);
+    let mut spark: Sparkline<4> = Sparkline::new(0, 0);
+
+    // the first sample scrolled out, so it doesn't count for the range
+    spark.update(&mut lcd, &[1000, 10, 20, 30, 40]);
+    let bars: Vec<u8> = lcd.cells[0][..4].iter().map(|c| bar(*c)).collect();
+    assert_eq!(bars, [0, 2, 5, 8]);
+
+    // fewer samples than cells are aligned right, and a flat line is drawn half height
+    spark.update(&mut lcd, &[7, 7]);
+    let bars: Vec<u8> = lcd.cells[0][..4].iter().map(|c| bar(*c)).collect();
+    assert_eq!(bars, [0, 0, 4, 4]);
+}
+
+#[test]
+fn sparkline_handles_the_whole_i32_range() {
+    let mut lcd = Recorder::new(16, 2);
+    let mut spark: Sparkline<3> = Sparkline::new(0, 0);
+    spark.update(&mut lcd, &[i32::MIN, 0, i32::MAX]);
+
+    let bars: Vec<u8> = lcd.cells[0][..3].iter().map(|c| bar(*c)).collect();
+    assert_eq!(bars, [0, 4, 8]);
+}
+
+#[test]
+fn sparkline_only_redraws_changed_cells() {
+    let mut lcd = Recorder::new(16, 2);
+    let mut spark: Sparkline<4> = Sparkline::new(0, 0).with_range(0, 8);
+    lcd.set_position(9, 1);
+
+    spark.update(&mut lcd, &[1, 2, 3, 4]);
+    assert_eq!(lcd.writes, 4);
+    spark.update(&mut lcd, &[1, 2, 3, 4]);
+    assert_eq!(lcd.writes, 4);
+    spark.update(&mut lcd, &[1, 2, 8, 4]);
+    assert_eq!(lcd.writes, 5);
+    assert_eq!(lcd.position(), (9, 1));
+
+    // uploading the glyphs again redraws everything
+    spark.upload(&mut lcd);
+    spark.update(&mut lcd, &[1, 2, 8, 4]);
+    assert_eq!(lcd.writes, 9);
+}
+
+#[test]
+fn sparkline_glyphs_fill_from_the_bottom() {
+    let mut lcd = Recorder::new(16, 2);
+    Sparkline::<4>::new(0, 0).upload(&mut lcd);
+    assert_eq!(lcd.glyphs[0], [0, 0, 0, 0, 0, 0, 0, 0b11111]);
+    assert_eq!(
+        lcd.glyphs[3],
+        [0, 0, 0, 0, 0b11111, 0b11111, 0b11111, 0b11111]
+    );
+    assert_eq!(lcd.glyphs[7], [0b11111; 8]);
+}
+
+/// A scene with a right-aligned and a left-aligned field
+fn thermostat() -> Scene<'static, 2> {
+    Scene::new(
+        &["Temp: ___ C", "Mode: ____"],
+        [
+            Field::new("temp", 6, 0, 3).with_align(Align::Right),
+            Field::new("mode", 6, 1, 4),
+        ],
+    )
+}
+
+#[test]
+fn scene_renders_text_with_fields_over_the_placeholders() {
+    let mut lcd = Recorder::new(16, 2);
+    let mut scene = thermostat();
+    scene.set(&mut lcd, "mode", "AUTO");
+
+    scene.render(&mut lcd);
+    assert_eq!(lcd.line(0), "Temp:     C     ");
+    assert_eq!(lcd.line(1), "Mode: AUTO      ");
+    assert_eq!(lcd.position(), (0, 0));
+}
+
+#[test]
+fn scene_only_sends_changed_characters() {
+    let mut lcd = Recorder::new(16, 2);
+    let mut scene = thermostat();
+    scene.render(&mut lcd);
+    lcd.set_position(15, 1);
+    let writes = lcd.writes;
+
+    assert!(scene.set(&mut lcd, "temp", "21"));
+    assert_eq!(lcd.line(0), "Temp:  21 C     ");
+    assert_eq!(lcd.writes, writes + 2);
+
+    assert!(scene.set(&mut lcd, "temp", "22"));
+    assert_eq!(lcd.writes, writes + 3);
+
+    assert!(scene.set(&mut lcd, "temp", "22"));
+    assert_eq!(lcd.writes, writes + 3);
+
+    assert!(scene.set(&mut lcd, "temp", "5"));
+    assert_eq!(lcd.line(0), "Temp:   5 C     ");
+    assert_eq!(lcd.writes, writes + 5);
+    assert_eq!(lcd.position(), (15, 1));
+}
+
+#[test]
+fn scene_cuts_long_values_and_pads_short_ones() {
+    let mut lcd = Recorder::new(16, 2);
+    let mut scene = thermostat();
+    scene.render(&mut lcd);
+
+    scene.set(&mut lcd, "mode", "HEATING");
+    assert_eq!(lcd.line(1), "Mode: HEAT      ");
+    scene.set(&mut lcd, "mode", "OFF");
+    assert_eq!(lcd.line(1), "Mode: OFF       ");
+    scene.set(&mut lcd, "temp", "1234");
+    assert_eq!(lcd.line(0), "Temp: 123 C     ");
+}
+
+#[test]
+fn scene_ignores_unknown_fields() {
+    let mut lcd = Recorder::new(16, 2);
+    let mut scene = thermostat();
+    scene.render(&mut lcd);
+    let writes = lcd.writes;
+
+    assert!(!scene.set(&mut lcd, "humidity", "40"));
+    assert_eq!(lcd.writes, writes);
+    assert!(scene.field("humidity").is_none());
+    assert_eq!(scene.field("temp").map(|f| f.name()), Some("temp"));
+}
+
+/// Get the pixel rows filled in a one-column scrollbar from `row` down.
+fn thumb_pixels(lcd: &Recorder, col: usize, rows: usize) -> Vec<bool> {
+    let mut pixels = Vec::new();
+    for row in 0..rows {
+        match lcd.cells[row][col] {
+            b' ' => pixels.extend([false; 8]),
+            0xFF => pixels.extend([true; 8]),
+            slot => pixels.extend(lcd.glyphs[slot as usize].iter().map(|l| *l == 0b11111)),
+        }
+    }
+    pixels
+}
+
+/// Get the first and last (exclusive) filled pixel rows.
+fn thumb(lcd: &Recorder, col: usize, rows: usize) -> (usize, usize) {
+    let pixels = thumb_pixels(lcd, col, rows);
+    let start = pixels.iter().position(|p| *p).unwrap();
+    let end = pixels.iter().rposition(|p| *p).unwrap() + 1;
+    assert!(pixels[start..end].iter().all(|p| *p), "thumb has a gap");
+    (start, end)
+}
+
+#[test]
+fn scrollbar_thumb_fills_the_bar_when_everything_is_visible() {
+    let mut lcd = Recorder::new(16, 2);
+    let mut scrollbar: Scrollbar<2> = Scrollbar::new(15, 0);
+    scrollbar.update(&mut lcd, 0, 4, 4);
+    assert_eq!((lcd.cells[0][15], lcd.cells[1][15]), (0xFF, 0xFF));
+
+    scrollbar.update(&mut lcd, 0, 0, 0);
+    assert_eq!((lcd.cells[0][15], lcd.cells[1][15]), (0xFF, 0xFF));
+}
+
+#[test]
+fn scrollbar_thumb_is_sized_and_placed_by_the_visible_part() {
+    let mut lcd = Recorder::new(16, 2);
+    let mut scrollbar: Scrollbar<2> = Scrollbar::new(15, 0);
+
+    scrollbar.update(&mut lcd, 1, 2, 4);
+    assert_eq!(thumb(&lcd, 15, 2), (4, 12));
+    assert_eq!((lcd.cells[0][15], lcd.cells[1][15]), (0, 1));
+
+    scrollbar.update(&mut lcd, 0, 8, 16);
+    assert_eq!(thumb(&lcd, 15, 2), (0, 8));
+    assert_eq!((lcd.cells[0][15], lcd.cells[1][15]), (0xFF, b' '));
+}
+
+#[test]
+fn scrollbar_thumb_stays_visible_and_inside_the_bar() {
+    let mut lcd = Recorder::new(16, 2);
+    let mut scrollbar: Scrollbar<2> = Scrollbar::new(15, 0);
+
+    // a tiny thumb is kept two pixels long
+    scrollbar.update(&mut lcd, 0, 10, 1000);
+    assert_eq!(thumb(&lcd, 15, 2), (0, 2));
+    scrollbar.update(&mut lcd, 990, 10, 1000);
+    assert_eq!(thumb(&lcd, 15, 2), (14, 16));
+
+    // scrolling past the end is clamped to the last page
+    scrollbar.update(&mut lcd, 5000, 10, 1000);
+    assert_eq!(thumb(&lcd, 15, 2), (14, 16));
+}
+
+#[test]
+fn scrollbar_uses_its_slots_and_only_redraws_changes() {
+    let mut lcd = Recorder::new(16, 2);
+    let mut scrollbar: Scrollbar<2> = Scrollbar::new(15, 0).with_slot(9);
+    lcd.set_position(3, 1);
+
+    scrollbar.update(&mut lcd, 1, 2, 4);
+    assert_eq!((lcd.cells[0][15], lcd.cells[1][15]), (6, 7));
+    assert_eq!(lcd.glyphs[..6], [[0; 8]; 6]);
+    assert_eq!(lcd.writes, 2);
+    assert_eq!(lcd.position(), (3, 1));
+
+    scrollbar.update(&mut lcd, 1, 2, 4);
+    assert_eq!(lcd.writes, 2);
+
+    scrollbar.invalidate();
+    scrollbar.update(&mut lcd, 1, 2, 4);
+    assert_eq!(lcd.writes, 4);
+}